@@ -1,10 +1,30 @@
-use std::{collections::HashMap, fmt};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    net::IpAddr,
+};
 
 use log::error;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tauri::State;
 
-use crate::tauri_state::SonarState;
+use crate::{
+    sniff::capture_packet::layer_2_infos::{
+        layer_3_infos::{
+            layer_4_infos::{
+                layer_7_infos::{
+                    dhcp::DhcpLease,
+                    dns::DnsNameMap,
+                    local_names::{LocalHost, LocalNameInventory},
+                },
+                ndp::{self, NdpInfos},
+            },
+            lldp::LldpInfos,
+        },
+        PacketInfos,
+    },
+    tauri_state::{DhcpLeasesState, DnsNamesState, SonarState},
+};
 
 // [(PacketInfos  {
 //     mac_address_source: "2c:fd:a1:60:a1:83",       mac_address_destination: "f4:05:95:5b:58:4c",
@@ -56,6 +76,9 @@ struct GraphData {
 #[derive(Serialize, Clone)]
 struct Node {
     name: String,
+    /// Attributs de l'hôte appris passivement (nom LLDP, port...).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, String>,
 }
 
 #[derive(Serialize, Clone)]
@@ -86,6 +109,7 @@ impl GraphBuilder {
                 mac_address.clone(),
                 Node {
                     name: mac_address.clone(),
+                    attributes: BTreeMap::new(),
                 },
             );
         }
@@ -109,6 +133,144 @@ impl GraphBuilder {
         }
     }
 
+    fn set_node_attribute(&mut self, mac_address: &str, key: &str, value: String) {
+        if let Some(node) = self.nodes.get_mut(mac_address) {
+            node.attributes.insert(key.to_string(), value);
+        }
+    }
+
+    /// Ajoute une valeur à un attribut multivalué (valeurs distinctes séparées par des virgules).
+    fn append_node_attribute(&mut self, mac_address: &str, key: &str, value: &str) {
+        if let Some(node) = self.nodes.get_mut(mac_address) {
+            let values = node.attributes.entry(key.to_string()).or_default();
+            if !values.split(", ").any(|existing| existing == value) {
+                if !values.is_empty() {
+                    values.push_str(", ");
+                }
+                values.push_str(value);
+            }
+        }
+    }
+
+    /// Ajoute aux nœuds les associations IPv6 ↔ MAC et les routeurs découverts par Neighbor Discovery.
+    fn add_ndp_attributes(
+        &mut self,
+        source_mac: &str,
+        ip_source: Option<&str>,
+        icmp_type: u8,
+        ndp: &NdpInfos,
+    ) {
+        // L'option Source Link-Layer Address associe l'adresse source du paquet à une MAC
+        if let (Some(mac), Some(ip)) = (&ndp.source_link_layer_address, ip_source) {
+            if ip != "::" {
+                self.add_node(mac.clone());
+                self.append_node_attribute(mac, "ipv6_addresses", ip);
+            }
+        }
+
+        // Une annonce de voisin lie l'adresse cible à la MAC annoncée, ou à défaut à l'émetteur
+        if let Some(target) = &ndp.target_address {
+            let mac = match (&ndp.target_link_layer_address, icmp_type) {
+                (Some(mac), _) => Some(mac.as_str()),
+                (None, ndp::NEIGHBOR_ADVERTISEMENT) => Some(source_mac),
+                _ => None,
+            };
+            if let Some(mac) = mac {
+                self.add_node(mac.to_string());
+                self.append_node_attribute(mac, "ipv6_addresses", target);
+            }
+        }
+
+        if icmp_type == ndp::ROUTER_ADVERTISEMENT {
+            let router_mac = ndp
+                .source_link_layer_address
+                .as_deref()
+                .unwrap_or(source_mac);
+            self.add_node(router_mac.to_string());
+            self.set_node_attribute(router_mac, "ipv6_router", "true".to_string());
+            for prefix in &ndp.prefixes {
+                self.append_node_attribute(router_mac, "ipv6_prefixes", &prefix.prefix);
+            }
+            for dns_server in &ndp.dns_servers {
+                self.append_node_attribute(router_mac, "ipv6_dns_servers", dns_server);
+            }
+            if let Some(mtu) = ndp.mtu {
+                self.set_node_attribute(router_mac, "ipv6_mtu", mtu.to_string());
+            }
+        }
+    }
+
+    /// Ajoute aux attributs du nœud émetteur les informations annoncées par LLDP.
+    fn add_lldp_attributes(&mut self, mac_address: &str, lldp: &LldpInfos) {
+        let attributes = [
+            ("lldp_chassis_id", lldp.chassis_id.clone()),
+            ("lldp_port_id", lldp.port_id.clone()),
+            ("lldp_port_description", lldp.port_description.clone()),
+            ("lldp_system_name", lldp.system_name.clone()),
+            ("lldp_system_description", lldp.system_description.clone()),
+            ("lldp_management_address", lldp.management_address.clone()),
+        ];
+        for (key, value) in attributes {
+            if let Some(value) = value {
+                self.set_node_attribute(mac_address, key, value);
+            }
+        }
+        if !lldp.enabled_capabilities.is_empty() {
+            self.set_node_attribute(
+                mac_address,
+                "lldp_capabilities",
+                lldp.enabled_capabilities.join(", "),
+            );
+        }
+    }
+
+    /// Ajoute au nœud d'un client DHCP l'adresse IP qui lui a été attribuée, son nom d'hôte et sa classe de fabricant.
+    fn add_dhcp_lease_attributes(&mut self, lease: &DhcpLease) {
+        let attributes = [
+            ("dhcp_ip", &lease.ip_address),
+            ("dhcp_hostname", &lease.hostname),
+            ("dhcp_vendor_class", &lease.vendor_class),
+        ];
+        for (key, value) in attributes {
+            if let Some(value) = value {
+                self.set_node_attribute(&lease.mac_address, key, value.clone());
+            }
+        }
+    }
+
+    /// Ajoute au nœud d'un équipement les noms, services et groupes de travail qu'il annonce
+    /// par mDNS, LLMNR ou NBNS.
+    fn add_local_name_attributes(&mut self, host: &LocalHost) {
+        let attributes = [
+            ("local_names", &host.names.hostnames),
+            ("services", &host.names.services),
+            ("workgroups", &host.names.workgroups),
+        ];
+        for (key, values) in attributes {
+            for value in values {
+                self.append_node_attribute(&host.mac_address, key, value);
+            }
+        }
+    }
+
+    /// Ajoute au nœud d'un équipement les noms DNS de son adresse IP.
+    ///
+    /// Seules les adresses privées ou de lien local sont retenues : derrière un routeur, l'adresse MAC est celle
+    /// du routeur, qui serait sinon annoté avec les noms de tous les serveurs distants.
+    fn add_dns_name_attributes(
+        &mut self,
+        mac_address: &str,
+        ip: Option<&str>,
+        dns_names: &DnsNameMap,
+    ) {
+        if let Some(name) = ip
+            .filter(|ip| is_local_address(ip))
+            .and_then(|ip| dns_names.name_of(ip))
+        {
+            self.append_node_attribute(mac_address, "dns_names", name);
+        }
+    }
+
     fn build_graph_data(&self) -> GraphData {
         GraphData {
             nodes: self.nodes.clone(),
@@ -151,19 +313,149 @@ impl fmt::Debug for Edge {
     }
 }
 
-pub fn get_graph_data(shared_vec_infopackets: State<SonarState>) -> Result<String, String> {
+/// Indique si une adresse IP est privée ou de lien local.
+fn is_local_address(ip: &str) -> bool {
+    match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_private() || ip.is_link_local(),
+        // Adresses uniques locales (fc00::/7) et de lien local (fe80::/10)
+        Ok(IpAddr::V6(ip)) => {
+            ip.segments()[0] & 0xfe00 == 0xfc00 || ip.segments()[0] & 0xffc0 == 0xfe80
+        }
+        Err(_) => false,
+    }
+}
+
+/// Information affichée sur les arêtes du graphe.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeLabelKind {
+    /// Protocole de couche 3.
+    #[default]
+    L3Protocol,
+    /// Nom de serveur TLS (SNI) des flux chiffrés, ou à défaut le protocole de couche 3.
+    TlsServerName,
+}
+
+/// Extrémités d'un flux : adresse IP et port source, adresse IP et port de destination.
+type FlowEndpoints<'a> = (
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+);
+
+fn flow_endpoints(packet: &PacketInfos) -> FlowEndpoints<'_> {
+    let layer_3_infos = &packet.layer_3_infos;
+    (
+        layer_3_infos.ip_source.as_deref(),
+        layer_3_infos.layer_4_infos.port_source.as_deref(),
+        layer_3_infos.ip_destination.as_deref(),
+        layer_3_infos.layer_4_infos.port_destination.as_deref(),
+    )
+}
+
+/// Associe à chaque flux client → serveur le nom de serveur TLS (SNI) de son ClientHello.
+fn tls_server_names(packets: &[(PacketInfos, u32)]) -> HashMap<FlowEndpoints<'_>, &str> {
+    packets
+        .iter()
+        .filter_map(|(packet, _)| {
+            let server_name = packet
+                .layer_3_infos
+                .layer_4_infos
+                .layer_7_infos
+                .as_ref()?
+                .tls
+                .as_ref()?
+                .server_name
+                .as_deref()?;
+            Some((flow_endpoints(packet), server_name))
+        })
+        .collect()
+}
+
+/// Retourne le nom de serveur TLS d'un flux : celui du flux lui-même pour le sens client → serveur,
+/// ou celui du flux inverse pour les réponses du serveur.
+fn tls_server_name<'a>(
+    packet: &PacketInfos,
+    server_names: &HashMap<FlowEndpoints<'_>, &'a str>,
+) -> Option<&'a str> {
+    let (ip_source, port_source, ip_destination, port_destination) = flow_endpoints(packet);
+    server_names
+        .get(&(ip_source, port_source, ip_destination, port_destination))
+        .or_else(|| server_names.get(&(ip_destination, port_destination, ip_source, port_source)))
+        .copied()
+}
+
+/// Construit le graphe des échanges entre adresses MAC, sérialisé en JSON.
+///
+/// # Arguments
+///
+/// * `shared_vec_infopackets` - Les flux capturés.
+/// * `dns_names` - La table des noms DNS, utilisée pour annoter les nœuds.
+/// * `dhcp_leases` - L'inventaire des baux DHCP, utilisé pour annoter les nœuds.
+/// * `edge_label` - L'information affichée sur les arêtes.
+pub fn get_graph_data(
+    shared_vec_infopackets: State<SonarState>,
+    dns_names: State<DnsNamesState>,
+    dhcp_leases: State<DhcpLeasesState>,
+    edge_label: EdgeLabelKind,
+) -> Result<String, String> {
+    let dns_names = dns_names.snapshot();
+    let dhcp_leases = dhcp_leases.snapshot();
+
     // Attempt to acquire the lock on the shared state
     match shared_vec_infopackets.0.lock() {
         Ok(matrice) => {
             let mut graph_builder = GraphBuilder::new();
+            let server_names = match edge_label {
+                EdgeLabelKind::TlsServerName => tls_server_names(&matrice),
+                EdgeLabelKind::L3Protocol => HashMap::new(),
+            };
 
             // Process your packet data here to populate nodes and edges
             for (packet, _) in matrice.iter() {
                 let source_mac = packet.mac_address_source.clone();
                 let target_mac = packet.mac_address_destination.clone();
-                let l3_protocol_label = packet.l_3_protocol.clone(); // Assume this is a String
+                let label = match tls_server_name(packet, &server_names) {
+                    Some(server_name) => server_name.to_string(),
+                    None => packet.l_3_protocol.clone(),
+                };
+
+                graph_builder.add_edge(source_mac.clone(), target_mac.clone(), label);
+
+                graph_builder.add_dns_name_attributes(
+                    &source_mac,
+                    packet.layer_3_infos.ip_source.as_deref(),
+                    &dns_names,
+                );
+                graph_builder.add_dns_name_attributes(
+                    &target_mac,
+                    packet.layer_3_infos.ip_destination.as_deref(),
+                    &dns_names,
+                );
 
-                graph_builder.add_edge(source_mac, target_mac, l3_protocol_label);
+                if let Some(lldp) = &packet.layer_3_infos.lldp {
+                    graph_builder.add_lldp_attributes(&source_mac, lldp);
+                }
+
+                if let Some(icmp) = &packet.layer_3_infos.layer_4_infos.icmp {
+                    if let Some(ndp) = &icmp.ndp {
+                        graph_builder.add_ndp_attributes(
+                            &source_mac,
+                            packet.layer_3_infos.ip_source.as_deref(),
+                            icmp.icmp_type,
+                            ndp,
+                        );
+                    }
+                }
+            }
+
+            for lease in dhcp_leases.leases() {
+                graph_builder.add_dhcp_lease_attributes(lease);
+            }
+
+            for host in LocalNameInventory::from_packets(&matrice).hosts() {
+                graph_builder.add_local_name_attributes(host);
             }
 
             let graph_data = graph_builder.build_graph_data();
@@ -184,4 +476,183 @@ pub fn get_graph_data(shared_vec_infopackets: State<SonarState>) -> Result<Strin
             Err(err_msg)
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::layer_4_infos::layer_7_infos::{
+        dns::{DnsInfos, DnsRecord},
+        tls::TlsInfos,
+        Layer7Infos,
+    };
+
+    #[test]
+    fn test_add_ndp_attributes() {
+        let mut graph_builder = GraphBuilder::new();
+        graph_builder.add_edge(
+            "02:00:00:00:00:fe".to_string(),
+            "33:33:00:00:00:01".to_string(),
+            "Ipv6".to_string(),
+        );
+
+        let advertisement = NdpInfos {
+            source_link_layer_address: Some("02:00:00:00:00:fe".to_string()),
+            prefixes: vec![ndp::NdpPrefix {
+                prefix: "2001:db8::/64".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        graph_builder.add_ndp_attributes(
+            "02:00:00:00:00:fe",
+            Some("fe80::1"),
+            ndp::ROUTER_ADVERTISEMENT,
+            &advertisement,
+        );
+
+        let neighbor = NdpInfos {
+            target_address: Some("2001:db8::1".to_string()),
+            ..Default::default()
+        };
+        graph_builder.add_ndp_attributes(
+            "02:00:00:00:00:fe",
+            Some("2001:db8::1"),
+            ndp::NEIGHBOR_ADVERTISEMENT,
+            &neighbor,
+        );
+
+        let attributes = &graph_builder.nodes["02:00:00:00:00:fe"].attributes;
+        assert_eq!(attributes["ipv6_addresses"], "fe80::1, 2001:db8::1");
+        assert_eq!(attributes["ipv6_router"], "true");
+        assert_eq!(attributes["ipv6_prefixes"], "2001:db8::/64");
+    }
+
+    #[test]
+    fn test_add_dhcp_lease_attributes() {
+        let mut graph_builder = GraphBuilder::new();
+        graph_builder.add_edge(
+            "02:00:00:00:00:01".to_string(),
+            "ff:ff:ff:ff:ff:ff".to_string(),
+            "Ipv4".to_string(),
+        );
+
+        graph_builder.add_dhcp_lease_attributes(&DhcpLease {
+            mac_address: "02:00:00:00:00:01".to_string(),
+            ip_address: Some("192.168.1.20".to_string()),
+            hostname: Some("plc-1".to_string()),
+            vendor_class: None,
+        });
+
+        let attributes = &graph_builder.nodes["02:00:00:00:00:01"].attributes;
+        assert_eq!(attributes["dhcp_ip"], "192.168.1.20");
+        assert_eq!(attributes["dhcp_hostname"], "plc-1");
+        assert!(!attributes.contains_key("dhcp_vendor_class"));
+    }
+
+    #[test]
+    fn test_add_local_name_attributes() {
+        let mut graph_builder = GraphBuilder::new();
+        graph_builder.add_edge(
+            "02:00:00:00:00:30".to_string(),
+            "01:00:5e:00:00:fb".to_string(),
+            "Ipv4".to_string(),
+        );
+
+        let mut host = LocalHost {
+            mac_address: "02:00:00:00:00:30".to_string(),
+            ..Default::default()
+        };
+        host.names.hostnames.insert("printer-1".to_string());
+        host.names.services.insert("_ipp._tcp".to_string());
+        host.names.services.insert("_printer._tcp".to_string());
+        graph_builder.add_local_name_attributes(&host);
+
+        let attributes = &graph_builder.nodes["02:00:00:00:00:30"].attributes;
+        assert_eq!(attributes["local_names"], "printer-1");
+        assert_eq!(attributes["services"], "_ipp._tcp, _printer._tcp");
+        assert!(!attributes.contains_key("workgroups"));
+    }
+
+    #[test]
+    fn test_add_dns_name_attributes() {
+        let mut graph_builder = GraphBuilder::new();
+        graph_builder.add_edge(
+            "02:00:00:00:00:01".to_string(),
+            "02:00:00:00:00:fe".to_string(),
+            "Ipv4".to_string(),
+        );
+
+        let mut dns_names = DnsNameMap::default();
+        dns_names.learn(&DnsInfos {
+            is_response: true,
+            response_code: Some("NoError".to_string()),
+            answers: ["192.168.1.20", "93.184.216.34"]
+                .into_iter()
+                .map(|ip| DnsRecord {
+                    name: format!("host-{}.example.com", ip),
+                    record_type: "A".to_string(),
+                    data: ip.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        });
+
+        graph_builder.add_dns_name_attributes(
+            "02:00:00:00:00:01",
+            Some("192.168.1.20"),
+            &dns_names,
+        );
+        graph_builder.add_dns_name_attributes(
+            "02:00:00:00:00:fe",
+            Some("93.184.216.34"),
+            &dns_names,
+        );
+
+        let nodes = &graph_builder.nodes;
+        assert_eq!(
+            nodes["02:00:00:00:00:01"].attributes["dns_names"],
+            "host-192.168.1.20.example.com"
+        );
+        assert!(!nodes["02:00:00:00:00:fe"]
+            .attributes
+            .contains_key("dns_names"));
+    }
+
+    fn tcp_flow(source: (&str, &str), destination: (&str, &str)) -> PacketInfos {
+        let mut packet = PacketInfos::default();
+        packet.layer_3_infos.ip_source = Some(source.0.to_string());
+        packet.layer_3_infos.layer_4_infos.port_source = Some(source.1.to_string());
+        packet.layer_3_infos.ip_destination = Some(destination.0.to_string());
+        packet.layer_3_infos.layer_4_infos.port_destination = Some(destination.1.to_string());
+        packet
+    }
+
+    #[test]
+    fn test_tls_server_name_both_directions() {
+        let mut client_flow = tcp_flow(("10.0.0.1", "50000"), ("10.0.0.2", "443"));
+        client_flow.layer_3_infos.layer_4_infos.layer_7_infos = Some(Layer7Infos {
+            tls: Some(TlsInfos {
+                server_name: Some("scada.example".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let packets = vec![
+            (client_flow, 1),
+            (tcp_flow(("10.0.0.2", "443"), ("10.0.0.1", "50000")), 1),
+            (tcp_flow(("10.0.0.2", "443"), ("10.0.0.3", "50000")), 1),
+        ];
+
+        let server_names = tls_server_names(&packets);
+        assert_eq!(
+            tls_server_name(&packets[0].0, &server_names),
+            Some("scada.example")
+        );
+        assert_eq!(
+            tls_server_name(&packets[1].0, &server_names),
+            Some("scada.example")
+        );
+        assert_eq!(tls_server_name(&packets[2].0, &server_names), None);
+    }
+}
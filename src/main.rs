@@ -1,17 +1,28 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use log::info;
 
 use sonar_desktop_app::{
     cli::print_banner,
     get_interfaces::get_interfaces,
-    get_matrice::{get_graph_data::get_graph_data, get_matrice_data::get_matrice_data},
+    get_matrice::{
+        get_graph_data::{get_graph_data, EdgeLabelKind},
+        get_matrice_data::get_matrice_data,
+    },
     save_packets::{cmd_save_packets_to_csv, cmd_save_packets_to_excel, MyError},
-    sniff::scan_until_interrupt,
-    tauri_state::SonarState,
+    sniff::{
+        check_capture_permissions, scan_until_interrupt, stop_capture, CaptureConfig,
+        CaptureDiagnostic, InterfaceStats,
+    },
+    tauri_state::{
+        CaptureConfigState, DhcpLeasesState, DnsNamesState, InterfaceStatsState, SonarState,
+    },
 };
 use tauri::{Manager, State};
 use tauri_plugin_log::LogTarget;
@@ -36,9 +47,18 @@ fn main() {
             }
         })
         .manage(SonarState(Arc::new(Mutex::new(Vec::new()))))
+        .manage(CaptureConfigState::default())
+        .manage(InterfaceStatsState::default())
+        .manage(DnsNamesState::default())
+        .manage(DhcpLeasesState::default())
         .invoke_handler(tauri::generate_handler![
             get_interfaces_tab,
+            get_capture_diagnostics,
             get_selected_interface,
+            stop_selected_interface,
+            get_capture_config,
+            set_capture_config,
+            get_interface_stats,
             save_packets_to_csv,
             save_packets_to_excel,
             get_hash_map_state,
@@ -70,15 +90,61 @@ fn get_interfaces_tab() -> Vec<String> {
     get_interfaces()
 }
 
+#[tauri::command(async, rename_all = "snake_case")]
+fn get_capture_diagnostics(interface_name: String) -> Vec<CaptureDiagnostic> {
+    check_capture_permissions(&interface_name)
+}
+
 #[tauri::command(async, rename_all = "snake_case")]
 fn get_selected_interface(
     window: tauri::Window,
     interface_name: String,
     state: tauri::State<SonarState>,
+    configs: tauri::State<CaptureConfigState>,
+    stats: tauri::State<InterfaceStatsState>,
+    dns_names: tauri::State<DnsNamesState>,
+    dhcp_leases: tauri::State<DhcpLeasesState>,
 ) {
     let app = window.app_handle();
     info!("Interface sélectionée: {}", interface_name);
-    scan_until_interrupt(app, &interface_name, state);
+    scan_until_interrupt(
+        app,
+        &interface_name,
+        state,
+        configs,
+        stats,
+        dns_names,
+        dhcp_leases,
+    );
+}
+
+#[tauri::command]
+fn stop_selected_interface() {
+    info!("Arrêt de la capture demandé");
+    stop_capture();
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_capture_config(interface_name: String, configs: State<CaptureConfigState>) -> CaptureConfig {
+    configs.get(&interface_name)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn set_capture_config(
+    interface_name: String,
+    config: CaptureConfig,
+    configs: State<CaptureConfigState>,
+) {
+    info!(
+        "Configuration de capture pour {}: {:?}",
+        interface_name, config
+    );
+    configs.set(&interface_name, config);
+}
+
+#[tauri::command]
+fn get_interface_stats(stats: State<InterfaceStatsState>) -> HashMap<String, InterfaceStats> {
+    stats.snapshot()
 }
 
 #[tauri::command(async, rename_all = "snake_case")]
@@ -88,9 +154,15 @@ fn save_packets_to_csv(file_path: String, state: State<SonarState>) -> Result<()
 }
 
 #[tauri::command(async, rename_all = "snake_case")]
-fn save_packets_to_excel(file_path: String, state: State<SonarState>) -> Result<(), MyError> {
+fn save_packets_to_excel(
+    file_path: String,
+    state: State<SonarState>,
+    stats: State<InterfaceStatsState>,
+    dns_names: State<DnsNamesState>,
+    dhcp_leases: State<DhcpLeasesState>,
+) -> Result<(), MyError> {
     info!("Chemin d'enregistrement du Excel: {}", &file_path);
-    cmd_save_packets_to_excel(file_path, state)
+    cmd_save_packets_to_excel(file_path, state, stats, dns_names, dhcp_leases)
 }
 
 #[tauri::command]
@@ -108,9 +180,19 @@ fn get_hash_map_state(shared_hash_map: State<SonarState>) -> Result<String, Stri
 }
 
 
-#[tauri::command]
-fn get_graph_state(shared_hash_map: State<SonarState>) -> Result<String, String> {
-    get_graph_data(shared_hash_map)
+#[tauri::command(rename_all = "snake_case")]
+fn get_graph_state(
+    shared_hash_map: State<SonarState>,
+    dns_names: State<DnsNamesState>,
+    dhcp_leases: State<DhcpLeasesState>,
+    edge_label: Option<EdgeLabelKind>,
+) -> Result<String, String> {
+    get_graph_data(
+        shared_hash_map,
+        dns_names,
+        dhcp_leases,
+        edge_label.unwrap_or_default(),
+    )
 }
 
 #[tauri::command]
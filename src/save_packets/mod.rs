@@ -1,4 +1,19 @@
-use crate::{sniff::capture_packet::layer_2_infos::PacketInfos, tauri_state::SonarState};
+use std::collections::{BTreeSet, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    sniff::capture_packet::{
+        interface_stats::InterfaceStats,
+        layer_2_infos::{
+            layer_3_infos::layer_4_infos::layer_7_infos::{
+                dhcp::DhcpLeaseInventory, dns::DnsNameMap, local_names::LocalNameInventory,
+                tls::certificate::CertificateInventory,
+            },
+            PacketInfos,
+        },
+    },
+    tauri_state::{DhcpLeasesState, DnsNamesState, InterfaceStatsState, SonarState},
+};
 use csv::Writer;
 use rust_xlsxwriter::*;
 use serde::Serialize;
@@ -50,11 +65,125 @@ struct PacketInfosCsv {
     packet_size: usize,
     /// Nombre de fois que ce paquet a été rencontré.
     count: u32,
+    /// Identifiant de châssis LLDP (optionnel).
+    lldp_chassis_id: Option<String>,
+    /// Identifiant de port LLDP (optionnel).
+    lldp_port_id: Option<String>,
+    /// TTL LLDP en secondes (optionnel).
+    lldp_ttl: Option<u16>,
+    /// Nom de l'équipement annoncé par LLDP (optionnel).
+    lldp_system_name: Option<String>,
+    /// Description de l'équipement annoncée par LLDP (optionnel).
+    lldp_system_description: Option<String>,
+    /// Capacités activées annoncées par LLDP (optionnel).
+    lldp_capabilities: Option<String>,
+    /// Adresse de gestion annoncée par LLDP (optionnel).
+    lldp_management_address: Option<String>,
+    /// Opération ARP (optionnel).
+    arp_operation: Option<String>,
+    /// Adresse matérielle de l'émetteur ARP (optionnel).
+    arp_sender_hw_address: Option<String>,
+    /// Adresse matérielle de la cible ARP (optionnel).
+    arp_target_hw_address: Option<String>,
+    /// Classification ARP : Probe, Announcement ou Gratuitous (optionnel).
+    arp_kind: Option<String>,
+    /// En-têtes d'extension IPv6 présents (optionnel).
+    ipv6_extension_headers: Option<String>,
+    /// Anomalies relevées sur le flux (optionnel).
+    anomalies: Option<String>,
+    /// Identifiant du VLAN externe (optionnel).
+    vlan_outer_id: Option<u16>,
+    /// Priorité (PCP) du VLAN externe (optionnel).
+    vlan_outer_priority: Option<u8>,
+    /// Bit DEI du VLAN externe (optionnel).
+    vlan_outer_dei: Option<bool>,
+    /// Identifiant du VLAN interne (optionnel).
+    vlan_inner_id: Option<u16>,
+    /// Priorité (PCP) du VLAN interne (optionnel).
+    vlan_inner_priority: Option<u8>,
+    /// Bit DEI du VLAN interne (optionnel).
+    vlan_inner_dei: Option<bool>,
+    /// Pile d'étiquettes MPLS, de l'étiquette externe au fond de pile (optionnel).
+    mpls_labels: Option<String>,
+    /// Type de tunnel dont le paquet a été extrait (optionnel).
+    tunnel_kind: Option<String>,
+    /// Identifiant du tunnel : clé GRE, session ERSPAN ou VNI (optionnel).
+    tunnel_id: Option<u32>,
+    /// Adresse IP source de l'en-tête externe du tunnel (optionnel).
+    tunnel_outer_ip_source: Option<String>,
+    /// Adresse IP destination de l'en-tête externe du tunnel (optionnel).
+    tunnel_outer_ip_destination: Option<String>,
+    /// Drapeaux TCP du dernier segment du flux (optionnel).
+    tcp_flags: Option<String>,
+    /// État de la connexion TCP (optionnel).
+    tcp_state: Option<String>,
+    /// Rôle de l'émetteur dans la connexion TCP : Client ou Server (optionnel).
+    tcp_role: Option<String>,
+    /// Protocole applicatif détecté (optionnel).
+    l_7_protocol: Option<String>,
+    /// Type du message DHCP ou DHCPv6 (optionnel).
+    dhcp_message_type: Option<String>,
+    /// Adresse attribuée, ou à défaut demandée, dans le message DHCP (optionnel).
+    dhcp_ip: Option<String>,
+    /// Nom d'hôte du client DHCP (optionnel).
+    dhcp_hostname: Option<String>,
+    /// Nom DNS de l'adresse IP source (optionnel).
+    hostname_source: Option<String>,
+    /// Nom DNS de l'adresse IP destination (optionnel).
+    hostname_destination: Option<String>,
+    /// Questions du dernier message DNS, avec leur type d'enregistrement (optionnel).
+    dns_query: Option<String>,
+    /// Code de retour de la dernière réponse DNS (optionnel).
+    dns_response_code: Option<String>,
+    /// Noms d'hôte annoncés par mDNS, LLMNR ou NBNS (optionnel).
+    local_names: Option<String>,
+    /// Services DNS-SD annoncés par mDNS (optionnel).
+    local_services: Option<String>,
+    /// Groupes de travail NetBIOS (optionnel).
+    workgroups: Option<String>,
+    /// Méthode de la dernière requête HTTP (optionnel).
+    http_method: Option<String>,
+    /// URI de la dernière requête HTTP (optionnel).
+    http_uri: Option<String>,
+    /// En-tête Host de la dernière requête HTTP (optionnel).
+    http_host: Option<String>,
+    /// En-tête User-Agent de la dernière requête HTTP (optionnel).
+    http_user_agent: Option<String>,
+    /// Code de statut de la dernière réponse HTTP (optionnel).
+    http_status: Option<u16>,
+    /// En-tête Server des réponses HTTP (optionnel).
+    http_server: Option<String>,
+    /// Nom de serveur demandé dans le ClientHello TLS (optionnel).
+    tls_server_name: Option<String>,
+    /// Version TLS proposée ou retenue (optionnel).
+    tls_version: Option<String>,
+    /// Protocoles ALPN proposés ou retenu (optionnel).
+    tls_alpn: Option<String>,
+    /// Suites de chiffrement proposées ou retenue (optionnel).
+    tls_cipher_suites: Option<String>,
+    /// Empreinte JA3 du client TLS (optionnel).
+    ja3: Option<String>,
+    /// Empreinte JA3S du serveur TLS (optionnel).
+    ja3s: Option<String>,
+    /// Empreinte JA4 du client TLS (optionnel).
+    ja4: Option<String>,
+    /// Sujet du certificat présenté par le serveur TLS (optionnel).
+    tls_certificate_subject: Option<String>,
 }
 
 impl PacketInfosCsv {
     /// Convertit les informations du paquet en une structure `PacketInfosCsv`.
     fn from_packet_infos(packet: &PacketInfos, count: u32) -> Self {
+        let arp = packet.layer_3_infos.arp.as_ref();
+        let lldp = packet.layer_3_infos.lldp.as_ref();
+        let tunnel = packet.tunnel.as_ref();
+        let tcp = packet.layer_3_infos.layer_4_infos.tcp.as_ref();
+        let layer_7 = packet.layer_3_infos.layer_4_infos.layer_7_infos.as_ref();
+        let dhcp = layer_7.and_then(|l| l.dhcp.as_ref());
+        let dns = layer_7.and_then(|l| l.dns.as_ref());
+        let local_names = layer_7.and_then(|l| l.local_names.as_ref());
+        let http = layer_7.and_then(|l| l.http.as_ref());
+        let tls = layer_7.and_then(|l| l.tls.as_ref());
         PacketInfosCsv {
             mac_address_source: packet.mac_address_source.clone(),
             mac_address_destination: packet.mac_address_destination.clone(),
@@ -67,6 +196,85 @@ impl PacketInfosCsv {
             port_destination: packet.layer_3_infos.layer_4_infos.port_destination.clone(),
             packet_size: packet.packet_size.clone(),
             count,
+            lldp_chassis_id: lldp.and_then(|l| l.chassis_id.clone()),
+            lldp_port_id: lldp.and_then(|l| l.port_id.clone()),
+            lldp_ttl: lldp.and_then(|l| l.ttl),
+            lldp_system_name: lldp.and_then(|l| l.system_name.clone()),
+            lldp_system_description: lldp.and_then(|l| l.system_description.clone()),
+            lldp_capabilities: lldp
+                .filter(|l| !l.enabled_capabilities.is_empty())
+                .map(|l| l.enabled_capabilities.join(", ")),
+            lldp_management_address: lldp.and_then(|l| l.management_address.clone()),
+            arp_operation: arp.map(|a| a.operation.clone()),
+            arp_sender_hw_address: arp.map(|a| a.sender_hw_address.clone()),
+            arp_target_hw_address: arp.map(|a| a.target_hw_address.clone()),
+            arp_kind: arp.and_then(|a| a.kind.clone()),
+            ipv6_extension_headers: (!packet.layer_3_infos.ipv6_extension_headers.is_empty())
+                .then(|| packet.layer_3_infos.ipv6_extension_headers.join(", ")),
+            anomalies: (!packet.layer_3_infos.anomalies.is_empty()).then(|| {
+                let anomalies: Vec<&str> = packet
+                    .layer_3_infos
+                    .anomalies
+                    .iter()
+                    .map(String::as_str)
+                    .collect();
+                anomalies.join(", ")
+            }),
+            vlan_outer_id: packet.vlan_outer.map(|v| v.vlan_id),
+            vlan_outer_priority: packet.vlan_outer.map(|v| v.priority),
+            vlan_outer_dei: packet.vlan_outer.map(|v| v.drop_eligible),
+            vlan_inner_id: packet.vlan_inner.map(|v| v.vlan_id),
+            vlan_inner_priority: packet.vlan_inner.map(|v| v.priority),
+            vlan_inner_dei: packet.vlan_inner.map(|v| v.drop_eligible),
+            mpls_labels: packet.layer_3_infos.mpls.as_ref().map(|m| {
+                let labels: Vec<String> = m.labels.iter().map(|l| l.label.to_string()).collect();
+                labels.join(", ")
+            }),
+            tunnel_kind: tunnel.map(|t| t.kind.clone()),
+            tunnel_id: tunnel.and_then(|t| t.tunnel_id),
+            tunnel_outer_ip_source: tunnel.map(|t| t.outer_ip_source.clone()),
+            tunnel_outer_ip_destination: tunnel.map(|t| t.outer_ip_destination.clone()),
+            tcp_flags: tcp.map(|t| t.flag_names.join(", ")),
+            tcp_state: tcp.and_then(|t| t.connection_state.map(|s| s.to_string())),
+            tcp_role: tcp.and_then(|t| t.role.map(|r| r.to_string())),
+            l_7_protocol: layer_7.and_then(|l| l.protocol.clone()),
+            dhcp_message_type: dhcp.map(|d| d.message_type.clone()),
+            dhcp_ip: dhcp.and_then(|d| d.offered_ip.clone().or(d.requested_ip.clone())),
+            dhcp_hostname: dhcp.and_then(|d| d.hostname.clone()),
+            hostname_source: packet.layer_3_infos.hostname_source.clone(),
+            hostname_destination: packet.layer_3_infos.hostname_destination.clone(),
+            dns_query: dns.filter(|d| !d.questions.is_empty()).map(|d| {
+                let questions: Vec<String> = d
+                    .questions
+                    .iter()
+                    .map(|q| format!("{} ({})", q.name, q.record_type))
+                    .collect();
+                questions.join(", ")
+            }),
+            dns_response_code: dns.and_then(|d| d.response_code.clone()),
+            local_names: local_names.and_then(|l| join_names(&l.hostnames)),
+            local_services: local_names.and_then(|l| join_names(&l.services)),
+            workgroups: local_names.and_then(|l| join_names(&l.workgroups)),
+            http_method: http.and_then(|h| h.method.clone()),
+            http_uri: http.and_then(|h| h.uri.clone()),
+            http_host: http.and_then(|h| h.host.clone()),
+            http_user_agent: http.and_then(|h| h.user_agent.clone()),
+            http_status: http.and_then(|h| h.status_code),
+            http_server: http.and_then(|h| h.server.clone()),
+            tls_server_name: tls.and_then(|t| t.server_name.clone()),
+            tls_version: tls.and_then(|t| t.version.clone()),
+            tls_alpn: tls
+                .filter(|t| !t.alpn.is_empty())
+                .map(|t| t.alpn.join(", ")),
+            tls_cipher_suites: tls
+                .filter(|t| !t.cipher_suites.is_empty())
+                .map(|t| t.cipher_suites.join(", ")),
+            ja3: tls.and_then(|t| t.ja3.clone()),
+            ja3s: tls.and_then(|t| t.ja3s.clone()),
+            ja4: tls.and_then(|t| t.ja4.clone()),
+            tls_certificate_subject: layer_7
+                .and_then(|l| l.certificates.first())
+                .map(|c| c.subject.clone()),
         }
     }
 }
@@ -114,19 +322,30 @@ pub fn cmd_save_packets_to_csv(file_path: String, state: State<SonarState>) -> R
 
 /// Fonction pour enregistrer les paquets vers un fichier Excel.
 ///
+/// Le classeur contient une feuille avec les paquets, une feuille "Statistiques" résumant la capture
+/// de chaque interface, une feuille "DNS" listant les noms appris pour chaque adresse IP, une feuille "DHCP"
+/// listant les baux observés et une feuille "Équipements" listant les noms, services et groupes de travail
+/// annoncés par chaque équipement.
+///
 /// # Arguments
 ///
 /// * `file_path` - Chemin du fichier Excel.
 /// * `state` - État contenant les données des paquets.
+/// * `stats` - État contenant les statistiques de capture par interface.
+/// * `dns_names` - État contenant la table des noms DNS.
+/// * `dhcp_leases` - État contenant l'inventaire des baux DHCP.
 ///
 /// # Exemple
 ///
 /// ```rust
-/// cmd_save_packets_to_excel(String::from("paquets.xlsx"), state);
+/// cmd_save_packets_to_excel(String::from("paquets.xlsx"), state, stats, dns_names, dhcp_leases);
 /// ```
 pub fn cmd_save_packets_to_excel(
     file_path: String,
     state: State<SonarState>,
+    stats: State<InterfaceStatsState>,
+    dns_names: State<DnsNamesState>,
+    dhcp_leases: State<DhcpLeasesState>,
 ) -> Result<(), MyError> {
     // Lock the state to access the data
     let data = state.0.lock().unwrap();
@@ -150,6 +369,58 @@ pub fn cmd_save_packets_to_excel(
         "Destination Port",
         "Taille des packets",
         "Count",
+        "LLDP Chassis ID",
+        "LLDP Port ID",
+        "LLDP TTL",
+        "LLDP System Name",
+        "LLDP System Description",
+        "LLDP Capabilities",
+        "LLDP Management Address",
+        "ARP Operation",
+        "ARP Sender MAC",
+        "ARP Target MAC",
+        "ARP Kind",
+        "IPv6 Extension Headers",
+        "Anomalies",
+        "Outer VLAN ID",
+        "Outer VLAN PCP",
+        "Outer VLAN DEI",
+        "Inner VLAN ID",
+        "Inner VLAN PCP",
+        "Inner VLAN DEI",
+        "MPLS Labels",
+        "Tunnel Type",
+        "Tunnel ID",
+        "Tunnel Outer IP Source",
+        "Tunnel Outer IP Destination",
+        "TCP Flags",
+        "TCP State",
+        "TCP Role",
+        "L7 Protocol",
+        "DHCP Message Type",
+        "DHCP IP",
+        "DHCP Hostname",
+        "Source Hostname",
+        "Destination Hostname",
+        "DNS Query",
+        "DNS Response Code",
+        "Local Names",
+        "Local Services",
+        "Workgroups",
+        "HTTP Method",
+        "HTTP URI",
+        "HTTP Host",
+        "HTTP User-Agent",
+        "HTTP Status",
+        "HTTP Server",
+        "TLS SNI",
+        "TLS Version",
+        "TLS ALPN",
+        "TLS Cipher Suites",
+        "JA3",
+        "JA3S",
+        "JA4",
+        "TLS Certificate Subject",
     ];
 
     for (i, header) in headers.iter().enumerate() {
@@ -212,8 +483,133 @@ pub fn cmd_save_packets_to_excel(
         sheet
             .write_number(i as u32 + 1, 10, packet_csv.count as f64)
             .map_err(|e| MyError::XlsxError(e.to_string()))?;
+
+        // Informations LLDP
+        write_optional_string(sheet, i as u32 + 1, 11, &packet_csv.lldp_chassis_id)?;
+        write_optional_string(sheet, i as u32 + 1, 12, &packet_csv.lldp_port_id)?;
+        if let Some(ttl) = packet_csv.lldp_ttl {
+            sheet
+                .write_number(i as u32 + 1, 13, ttl as f64)
+                .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        }
+        write_optional_string(sheet, i as u32 + 1, 14, &packet_csv.lldp_system_name)?;
+        write_optional_string(sheet, i as u32 + 1, 15, &packet_csv.lldp_system_description)?;
+        write_optional_string(sheet, i as u32 + 1, 16, &packet_csv.lldp_capabilities)?;
+        write_optional_string(sheet, i as u32 + 1, 17, &packet_csv.lldp_management_address)?;
+
+        // Détails ARP
+        write_optional_string(sheet, i as u32 + 1, 18, &packet_csv.arp_operation)?;
+        write_optional_string(sheet, i as u32 + 1, 19, &packet_csv.arp_sender_hw_address)?;
+        write_optional_string(sheet, i as u32 + 1, 20, &packet_csv.arp_target_hw_address)?;
+        write_optional_string(sheet, i as u32 + 1, 21, &packet_csv.arp_kind)?;
+
+        // En-têtes d'extension IPv6
+        write_optional_string(sheet, i as u32 + 1, 22, &packet_csv.ipv6_extension_headers)?;
+        write_optional_string(sheet, i as u32 + 1, 23, &packet_csv.anomalies)?;
+
+        // Étiquettes VLAN
+        let vlan_columns = [
+            (24, packet_csv.vlan_outer_id.map(f64::from)),
+            (25, packet_csv.vlan_outer_priority.map(f64::from)),
+            (27, packet_csv.vlan_inner_id.map(f64::from)),
+            (28, packet_csv.vlan_inner_priority.map(f64::from)),
+        ];
+        for (col, value) in vlan_columns {
+            if let Some(value) = value {
+                sheet
+                    .write_number(i as u32 + 1, col, value)
+                    .map_err(|e| MyError::XlsxError(e.to_string()))?;
+            }
+        }
+        for (col, value) in [
+            (26, packet_csv.vlan_outer_dei),
+            (29, packet_csv.vlan_inner_dei),
+        ] {
+            if let Some(value) = value {
+                sheet
+                    .write_boolean(i as u32 + 1, col, value)
+                    .map_err(|e| MyError::XlsxError(e.to_string()))?;
+            }
+        }
+
+        // Pile d'étiquettes MPLS
+        write_optional_string(sheet, i as u32 + 1, 30, &packet_csv.mpls_labels)?;
+
+        // Tunnel
+        write_optional_string(sheet, i as u32 + 1, 31, &packet_csv.tunnel_kind)?;
+        if let Some(tunnel_id) = packet_csv.tunnel_id {
+            sheet
+                .write_number(i as u32 + 1, 32, tunnel_id as f64)
+                .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        }
+        write_optional_string(sheet, i as u32 + 1, 33, &packet_csv.tunnel_outer_ip_source)?;
+        write_optional_string(
+            sheet,
+            i as u32 + 1,
+            34,
+            &packet_csv.tunnel_outer_ip_destination,
+        )?;
+
+        // Connexion TCP
+        write_optional_string(sheet, i as u32 + 1, 35, &packet_csv.tcp_flags)?;
+        write_optional_string(sheet, i as u32 + 1, 36, &packet_csv.tcp_state)?;
+        write_optional_string(sheet, i as u32 + 1, 37, &packet_csv.tcp_role)?;
+
+        // Couche 7
+        write_optional_string(sheet, i as u32 + 1, 38, &packet_csv.l_7_protocol)?;
+        write_optional_string(sheet, i as u32 + 1, 39, &packet_csv.dhcp_message_type)?;
+        write_optional_string(sheet, i as u32 + 1, 40, &packet_csv.dhcp_ip)?;
+        write_optional_string(sheet, i as u32 + 1, 41, &packet_csv.dhcp_hostname)?;
+
+        // Noms DNS
+        write_optional_string(sheet, i as u32 + 1, 42, &packet_csv.hostname_source)?;
+        write_optional_string(sheet, i as u32 + 1, 43, &packet_csv.hostname_destination)?;
+        write_optional_string(sheet, i as u32 + 1, 44, &packet_csv.dns_query)?;
+        write_optional_string(sheet, i as u32 + 1, 45, &packet_csv.dns_response_code)?;
+
+        // Noms annoncés par mDNS, LLMNR et NBNS
+        write_optional_string(sheet, i as u32 + 1, 46, &packet_csv.local_names)?;
+        write_optional_string(sheet, i as u32 + 1, 47, &packet_csv.local_services)?;
+        write_optional_string(sheet, i as u32 + 1, 48, &packet_csv.workgroups)?;
+
+        // Requête et réponse HTTP
+        write_optional_string(sheet, i as u32 + 1, 49, &packet_csv.http_method)?;
+        write_optional_string(sheet, i as u32 + 1, 50, &packet_csv.http_uri)?;
+        write_optional_string(sheet, i as u32 + 1, 51, &packet_csv.http_host)?;
+        write_optional_string(sheet, i as u32 + 1, 52, &packet_csv.http_user_agent)?;
+        if let Some(status) = packet_csv.http_status {
+            sheet
+                .write_number(i as u32 + 1, 53, status as f64)
+                .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        }
+        write_optional_string(sheet, i as u32 + 1, 54, &packet_csv.http_server)?;
+
+        // Handshake TLS
+        write_optional_string(sheet, i as u32 + 1, 55, &packet_csv.tls_server_name)?;
+        write_optional_string(sheet, i as u32 + 1, 56, &packet_csv.tls_version)?;
+        write_optional_string(sheet, i as u32 + 1, 57, &packet_csv.tls_alpn)?;
+        write_optional_string(sheet, i as u32 + 1, 58, &packet_csv.tls_cipher_suites)?;
+        write_optional_string(sheet, i as u32 + 1, 59, &packet_csv.ja3)?;
+        write_optional_string(sheet, i as u32 + 1, 60, &packet_csv.ja3s)?;
+        write_optional_string(sheet, i as u32 + 1, 61, &packet_csv.ja4)?;
+        write_optional_string(sheet, i as u32 + 1, 62, &packet_csv.tls_certificate_subject)?;
     }
 
+    // Add the per-interface statistics summary
+    write_interface_stats_sheet(&mut workbook, &stats.snapshot())?;
+
+    // Add the names learned from DNS responses
+    write_dns_names_sheet(&mut workbook, &dns_names.snapshot())?;
+
+    // Add the DHCP leases recorded during the capture
+    write_dhcp_leases_sheet(&mut workbook, &dhcp_leases.snapshot())?;
+
+    // Add the names announced by local hosts
+    write_local_names_sheet(&mut workbook, &LocalNameInventory::from_packets(&data))?;
+
+    // Add the certificates presented by TLS servers
+    write_certificates_sheet(&mut workbook, &CertificateInventory::from_packets(&data))?;
+
     // Close the workbook
     workbook
         .save(file_path)
@@ -222,3 +618,295 @@ pub fn cmd_save_packets_to_excel(
     Ok(())
 }
 
+/// Joint les valeurs d'un ensemble, ou retourne `None` s'il est vide.
+fn join_names(names: &BTreeSet<String>) -> Option<String> {
+    if names.is_empty() {
+        return None;
+    }
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    Some(names.join(", "))
+}
+
+/// Écrit une valeur optionnelle dans une cellule, qui reste vide si la valeur est absente.
+fn write_optional_string(
+    sheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    value: &Option<String>,
+) -> Result<(), MyError> {
+    if let Some(value) = value {
+        sheet
+            .write_string(row, col, value)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Ajoute au classeur une feuille "Statistiques" résumant la capture de chaque interface.
+///
+/// Chaque ligne correspond à une interface ; les colonnes de fin donnent le nombre de trames
+/// par EtherType de l'en-tête de liaison, observé sur l'ensemble des interfaces.
+///
+/// # Arguments
+///
+/// * `workbook` - Le classeur Excel à compléter.
+/// * `stats` - Les statistiques de capture, indexées par nom d'interface.
+fn write_interface_stats_sheet(
+    workbook: &mut Workbook,
+    stats: &HashMap<String, InterfaceStats>,
+) -> Result<(), MyError> {
+    let sheet = workbook.add_worksheet();
+    sheet
+        .set_name("Statistiques")
+        .map_err(|e| MyError::XlsxError(e.to_string()))?;
+
+    let ethertypes: BTreeSet<&String> = stats
+        .values()
+        .flat_map(|s| s.frames_by_ethertype.keys())
+        .collect();
+
+    let headers = [
+        "Interface",
+        "Trames reçues",
+        "Octets",
+        "Échecs d'analyse",
+        "Erreurs de lecture",
+    ];
+    for (i, header) in headers
+        .iter()
+        .copied()
+        .chain(ethertypes.iter().map(|e| e.as_str()))
+        .enumerate()
+    {
+        sheet
+            .write_string(0, i as u16, header)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+    }
+
+    let mut interfaces: Vec<&String> = stats.keys().collect();
+    interfaces.sort();
+
+    for (i, interface) in interfaces.into_iter().enumerate() {
+        let row = i as u32 + 1;
+        let interface_stats = &stats[interface];
+
+        sheet
+            .write_string(row, 0, interface)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+
+        let counters = [
+            interface_stats.frames_received,
+            interface_stats.bytes,
+            interface_stats.parse_failures,
+            interface_stats.read_errors,
+        ]
+        .into_iter()
+        .chain(ethertypes.iter().map(|ethertype| {
+            interface_stats
+                .frames_by_ethertype
+                .get(*ethertype)
+                .copied()
+                .unwrap_or(0)
+        }));
+
+        for (j, counter) in counters.enumerate() {
+            sheet
+                .write_number(row, j as u16 + 1, counter as f64)
+                .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ajoute au classeur une feuille "DNS" listant les noms appris pour chaque adresse IP.
+///
+/// # Arguments
+///
+/// * `workbook` - Le classeur Excel à compléter.
+/// * `dns_names` - La table des noms DNS.
+fn write_dns_names_sheet(workbook: &mut Workbook, dns_names: &DnsNameMap) -> Result<(), MyError> {
+    let sheet = workbook.add_worksheet();
+    sheet
+        .set_name("DNS")
+        .map_err(|e| MyError::XlsxError(e.to_string()))?;
+
+    for (i, header) in ["Adresse IP", "Nom", "Noms observés"].iter().enumerate() {
+        sheet
+            .write_string(0, i as u16, *header)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+    }
+
+    for (i, (address, names)) in dns_names.entries().enumerate() {
+        let row = i as u32 + 1;
+        let latest = names.last().map(String::as_str).unwrap_or_default();
+        for (col, value) in [address.as_str(), latest, &names.join(", ")]
+            .into_iter()
+            .enumerate()
+        {
+            sheet
+                .write_string(row, col as u16, value)
+                .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ajoute au classeur une feuille "DHCP" listant, pour chaque adresse MAC, l'adresse IP attribuée, le nom
+/// d'hôte et la classe du fabricant annoncés lors des échanges DHCP.
+///
+/// # Arguments
+///
+/// * `workbook` - Le classeur Excel à compléter.
+/// * `inventory` - L'inventaire des baux DHCP.
+fn write_dhcp_leases_sheet(
+    workbook: &mut Workbook,
+    inventory: &DhcpLeaseInventory,
+) -> Result<(), MyError> {
+    let sheet = workbook.add_worksheet();
+    sheet
+        .set_name("DHCP")
+        .map_err(|e| MyError::XlsxError(e.to_string()))?;
+
+    let headers = [
+        "Adresse MAC",
+        "Adresse IP",
+        "Nom d'hôte",
+        "Classe du fabricant",
+    ];
+    for (i, header) in headers.iter().enumerate() {
+        sheet
+            .write_string(0, i as u16, *header)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+    }
+
+    for (i, lease) in inventory.leases().enumerate() {
+        let row = i as u32 + 1;
+        sheet
+            .write_string(row, 0, &lease.mac_address)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        write_optional_string(sheet, row, 1, &lease.ip_address)?;
+        write_optional_string(sheet, row, 2, &lease.hostname)?;
+        write_optional_string(sheet, row, 3, &lease.vendor_class)?;
+    }
+
+    Ok(())
+}
+
+/// Ajoute au classeur une feuille "Équipements" listant, pour chaque adresse MAC, les noms, services
+/// et groupes de travail annoncés par mDNS, LLMNR et NBNS.
+///
+/// # Arguments
+///
+/// * `workbook` - Le classeur Excel à compléter.
+/// * `inventory` - L'inventaire des noms locaux.
+fn write_local_names_sheet(
+    workbook: &mut Workbook,
+    inventory: &LocalNameInventory,
+) -> Result<(), MyError> {
+    let sheet = workbook.add_worksheet();
+    sheet
+        .set_name("Équipements")
+        .map_err(|e| MyError::XlsxError(e.to_string()))?;
+
+    let headers = [
+        "Adresse MAC",
+        "Adresses IP",
+        "Noms",
+        "Services",
+        "Groupes de travail",
+    ];
+    for (i, header) in headers.iter().enumerate() {
+        sheet
+            .write_string(0, i as u16, *header)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+    }
+
+    for (i, host) in inventory.hosts().enumerate() {
+        let row = i as u32 + 1;
+        sheet
+            .write_string(row, 0, &host.mac_address)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        let columns = [
+            &host.ip_addresses,
+            &host.names.hostnames,
+            &host.names.services,
+            &host.names.workgroups,
+        ];
+        for (j, values) in columns.into_iter().enumerate() {
+            write_optional_string(sheet, row, j as u16 + 1, &join_names(values))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ajoute une feuille listant les certificats présentés par chaque serveur TLS, avec les avertissements
+/// d'expiration et de certificat auto-signé.
+fn write_certificates_sheet(
+    workbook: &mut Workbook,
+    inventory: &CertificateInventory,
+) -> Result<(), MyError> {
+    let sheet = workbook.add_worksheet();
+    sheet
+        .set_name("Certificats")
+        .map_err(|e| MyError::XlsxError(e.to_string()))?;
+
+    let headers = [
+        "Serveur",
+        "Position",
+        "Sujet",
+        "Émetteur",
+        "SAN",
+        "Valide à partir du",
+        "Expire le",
+        "Type de clé",
+        "Taille de clé",
+        "Empreinte SHA-256",
+        "Avertissements",
+    ];
+    for (i, header) in headers.iter().enumerate() {
+        sheet
+            .write_string(0, i as u16, *header)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+
+    for (i, entry) in inventory.certificates().enumerate() {
+        let row = i as u32 + 1;
+        let certificate = &entry.certificate;
+        sheet
+            .write_string(row, 0, &entry.server)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        sheet
+            .write_number(row, 1, entry.position as f64)
+            .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        let columns = [
+            (2, &certificate.subject),
+            (3, &certificate.issuer),
+            (4, &certificate.subject_alternative_names.join(", ")),
+            (5, &certificate.not_before),
+            (6, &certificate.not_after),
+            (7, &certificate.key_type),
+            (9, &certificate.fingerprint_sha256),
+            (10, &certificate.warnings(now).join(", ")),
+        ];
+        for (col, value) in columns {
+            sheet
+                .write_string(row, col, value)
+                .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        }
+        if let Some(key_size) = certificate.key_size {
+            sheet
+                .write_number(row, 8, key_size as f64)
+                .map_err(|e| MyError::XlsxError(e.to_string()))?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! # Configuration de la capture
+//!
+//! Ce module définit [`CaptureConfig`], la configuration appliquée au canal de capture d'une interface
+//! (mode promiscuité, tailles des tampons, délai de lecture). Elle est convertie en
+//! [`datalink::Config`](pnet::datalink::Config) lors de l'ouverture du canal.
+
+use std::time::Duration;
+
+use pnet::datalink;
+use serde::{Deserialize, Serialize};
+
+/// Configuration du canal de capture d'une interface réseau.
+///
+/// Les champs absents lors de la désérialisation prennent leur valeur par défaut, ce qui permet à
+/// l'interface utilisateur de n'envoyer que les paramètres modifiés.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// Active le mode promiscuité de l'interface pour recevoir les trames destinées aux autres hôtes.
+    pub promiscuous: bool,
+    /// Taille du tampon de lecture, en octets.
+    pub read_buffer_size: usize,
+    /// Taille du tampon d'écriture, en octets.
+    pub write_buffer_size: usize,
+    /// Délai maximal d'attente d'une trame, en millisecondes (`None` pour une lecture bloquante).
+    ///
+    /// Sans délai, la boucle de capture reste bloquée tant qu'aucune trame n'arrive et ne peut pas
+    /// réagir à une demande d'arrêt ([`stop_capture`](super::stop_capture)).
+    pub read_timeout_ms: Option<u64>,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        CaptureConfig {
+            promiscuous: true,
+            read_buffer_size: 65536,
+            write_buffer_size: 4096,
+            read_timeout_ms: Some(1000),
+        }
+    }
+}
+
+impl From<&CaptureConfig> for datalink::Config {
+    fn from(config: &CaptureConfig) -> Self {
+        datalink::Config {
+            promiscuous: config.promiscuous,
+            read_buffer_size: config.read_buffer_size,
+            write_buffer_size: config.write_buffer_size,
+            read_timeout: config.read_timeout_ms.map(Duration::from_millis),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_config_to_datalink_config() {
+        let config = CaptureConfig {
+            promiscuous: false,
+            read_buffer_size: 1 << 20,
+            write_buffer_size: 2048,
+            read_timeout_ms: Some(250),
+        };
+
+        let datalink_config = datalink::Config::from(&config);
+
+        assert!(!datalink_config.promiscuous);
+        assert_eq!(datalink_config.read_buffer_size, 1 << 20);
+        assert_eq!(datalink_config.write_buffer_size, 2048);
+        assert_eq!(
+            datalink_config.read_timeout,
+            Some(Duration::from_millis(250))
+        );
+    }
+
+    #[test]
+    fn test_capture_config_partial_deserialization() {
+        let config: CaptureConfig = serde_json::from_str(r#"{"promiscuous": false}"#).unwrap();
+
+        assert!(!config.promiscuous);
+        assert_eq!(
+            config.read_timeout_ms,
+            CaptureConfig::default().read_timeout_ms
+        );
+    }
+}
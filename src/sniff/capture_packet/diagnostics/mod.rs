@@ -0,0 +1,193 @@
+//! # Diagnostic des droits de capture
+//!
+//! Avant de démarrer une capture, ce module vérifie pour chaque interface qu'un canal de capture peut
+//! effectivement être ouvert. Les échecs sont classés en causes actionnables (droits insuffisants,
+//! interface désactivée, interface inexistante) accompagnées d'un conseil pour l'utilisateur, au lieu
+//! de l'erreur générique de création du canal.
+
+use std::io;
+
+use pnet::datalink::{self, NetworkInterface};
+use serde::Serialize;
+
+use super::capture_config::CaptureConfig;
+
+/// Code d'erreur `ENXIO` : le périphérique n'existe pas ou n'est pas configuré.
+const ENXIO: i32 = 6;
+/// Code d'erreur `ENODEV` (Linux) : aucun périphérique de ce nom.
+const ENODEV: i32 = 19;
+
+/// Résultat du diagnostic de capture d'une interface.
+#[derive(Debug, Serialize, Clone, Eq, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CaptureStatus {
+    /// Un canal de capture a pu être ouvert sur l'interface.
+    Ready,
+    /// Le processus n'a pas les droits de capture (CAP_NET_RAW sous Linux, administrateur ailleurs).
+    MissingPermission,
+    /// L'interface existe mais n'est pas activée.
+    InterfaceDown,
+    /// Aucune interface de ce nom n'existe sur le système.
+    NoSuchDevice,
+    /// L'ouverture du canal a échoué pour une autre raison.
+    Error { message: String },
+}
+
+/// Diagnostic de capture d'une interface, destiné à l'interface utilisateur.
+#[derive(Debug, Serialize, Clone, Eq, PartialEq)]
+pub struct CaptureDiagnostic {
+    /// Nom de l'interface diagnostiquée.
+    pub interface: String,
+    /// Résultat du diagnostic.
+    #[serde(flatten)]
+    pub status: CaptureStatus,
+    /// Action conseillée pour corriger le problème, le cas échéant.
+    pub advice: Option<String>,
+}
+
+impl CaptureDiagnostic {
+    /// Construit un diagnostic et y associe le conseil correspondant au résultat.
+    pub fn new(interface: &str, status: CaptureStatus) -> CaptureDiagnostic {
+        let advice = match &status {
+            CaptureStatus::Ready => None,
+            CaptureStatus::MissingPermission => Some(permission_advice()),
+            CaptureStatus::InterfaceDown => Some(format!(
+                "Activez l'interface {} avant de lancer la capture.",
+                interface
+            )),
+            CaptureStatus::NoSuchDevice => Some(
+                "Vérifiez que l'interface est branchée puis actualisez la liste des interfaces."
+                    .to_string(),
+            ),
+            CaptureStatus::Error { .. } => None,
+        };
+
+        CaptureDiagnostic {
+            interface: interface.to_string(),
+            status,
+            advice,
+        }
+    }
+
+    /// Indique si la capture peut démarrer sur cette interface.
+    pub fn is_ready(&self) -> bool {
+        self.status == CaptureStatus::Ready
+    }
+}
+
+/// Conseil affiché lorsque les droits de capture sont insuffisants, selon le système.
+fn permission_advice() -> String {
+    #[cfg(target_os = "linux")]
+    {
+        "Sonar n'a pas la capacité CAP_NET_RAW : lancez-le en root ou exécutez \
+         `sudo setcap cap_net_raw,cap_net_admin=eip` sur l'exécutable."
+            .to_string()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        "Lancez Sonar en tant qu'administrateur et vérifiez que Npcap est installé.".to_string()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        "Lancez Sonar avec les droits administrateur ou donnez accès aux périphériques /dev/bpf*."
+            .to_string()
+    }
+}
+
+/// Classe l'erreur retournée par l'ouverture d'un canal de capture.
+fn classify_channel_error(error: &io::Error) -> CaptureStatus {
+    match (error.kind(), error.raw_os_error()) {
+        (io::ErrorKind::PermissionDenied, _) => CaptureStatus::MissingPermission,
+        (io::ErrorKind::NotFound, _) | (_, Some(ENODEV)) | (_, Some(ENXIO)) => {
+            CaptureStatus::NoSuchDevice
+        }
+        _ => CaptureStatus::Error {
+            message: error.to_string(),
+        },
+    }
+}
+
+/// Diagnostique la capture sur une interface en tentant d'ouvrir un canal, immédiatement refermé.
+///
+/// Le canal de test est ouvert sans mode promiscuité pour ne pas modifier l'état de l'interface.
+pub fn diagnose_interface(interface: &NetworkInterface) -> CaptureDiagnostic {
+    if !interface.is_up() {
+        return CaptureDiagnostic::new(&interface.name, CaptureStatus::InterfaceDown);
+    }
+
+    let config = CaptureConfig {
+        promiscuous: false,
+        ..Default::default()
+    };
+    let status = match datalink::channel(interface, (&config).into()) {
+        Ok(_) => CaptureStatus::Ready,
+        Err(e) => classify_channel_error(&e),
+    };
+
+    CaptureDiagnostic::new(&interface.name, status)
+}
+
+/// Diagnostique la capture sur les interfaces demandées, ou sur toutes les interfaces si aucune n'est précisée.
+///
+/// # Arguments
+///
+/// * `interface_name` - Le nom de l'interface à vérifier, ou `None` pour toutes les interfaces.
+pub fn diagnose_interfaces(interface_name: Option<&str>) -> Vec<CaptureDiagnostic> {
+    let interfaces = datalink::interfaces();
+
+    match interface_name {
+        Some(name) => match interfaces.iter().find(|iface| iface.name == name) {
+            Some(interface) => vec![diagnose_interface(interface)],
+            None => vec![CaptureDiagnostic::new(name, CaptureStatus::NoSuchDevice)],
+        },
+        None => interfaces.iter().map(diagnose_interface).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_channel_error() {
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            classify_channel_error(&denied),
+            CaptureStatus::MissingPermission
+        );
+
+        let no_device = io::Error::from_raw_os_error(ENODEV);
+        assert_eq!(
+            classify_channel_error(&no_device),
+            CaptureStatus::NoSuchDevice
+        );
+
+        let other = io::Error::other("boom");
+        assert_eq!(
+            classify_channel_error(&other),
+            CaptureStatus::Error {
+                message: "boom".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_diagnose_unknown_interface() {
+        let diagnostics = diagnose_interfaces(Some("sonar-does-not-exist0"));
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].status, CaptureStatus::NoSuchDevice);
+        assert!(!diagnostics[0].is_ready());
+        assert!(diagnostics[0].advice.is_some());
+    }
+
+    #[test]
+    fn test_diagnostic_serialization() {
+        let diagnostic = CaptureDiagnostic::new("eth0", CaptureStatus::InterfaceDown);
+        let json = serde_json::to_value(&diagnostic).unwrap();
+
+        assert_eq!(json["interface"], "eth0");
+        assert_eq!(json["status"], "interface_down");
+        assert!(json["advice"].as_str().unwrap().contains("eth0"));
+    }
+}
@@ -0,0 +1,94 @@
+//! # Statistiques de capture par interface
+//!
+//! Ce module comptabilise, pour chaque interface capturée, les trames reçues, les octets lus, les trames
+//! qui n'ont pas pu être analysées, les erreurs de lecture et la répartition des trames par EtherType.
+//! Ces compteurs permettent de distinguer une interface silencieuse d'une interface en erreur.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+/// Compteurs de capture d'une interface réseau.
+#[derive(Debug, Default, Serialize, Clone, Eq, PartialEq)]
+pub struct InterfaceStats {
+    /// Nombre de trames lues sur l'interface, y compris celles qui n'ont pas pu être analysées.
+    pub frames_received: u64,
+    /// Nombre total d'octets lus.
+    pub bytes: u64,
+    /// Nombre de trames dont l'en-tête de liaison n'a pas pu être analysé.
+    pub parse_failures: u64,
+    /// Nombre d'erreurs retournées par le canal de capture.
+    pub read_errors: u64,
+    /// Nombre de trames analysées, par EtherType de l'en-tête de liaison (avant retrait des étiquettes VLAN
+    /// et décapsulation des tunnels).
+    pub frames_by_ethertype: BTreeMap<String, u64>,
+}
+
+impl InterfaceStats {
+    /// Comptabilise une trame lue sur l'interface.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_len` - La taille de la trame en octets.
+    /// * `ethertype` - L'EtherType de l'en-tête de liaison de la trame, ou `None` si elle n'a pas pu être analysée.
+    pub fn record_frame(&mut self, frame_len: usize, ethertype: Option<&str>) {
+        self.frames_received += 1;
+        self.bytes += frame_len as u64;
+        match ethertype {
+            Some(ethertype) => {
+                *self
+                    .frames_by_ethertype
+                    .entry(ethertype.to_string())
+                    .or_insert(0) += 1;
+            }
+            None => self.parse_failures += 1,
+        }
+    }
+
+    /// Comptabilise une erreur de lecture du canal de capture.
+    pub fn record_read_error(&mut self) {
+        self.read_errors += 1;
+    }
+}
+
+/// Met à jour les statistiques d'une interface dans la table partagée.
+///
+/// # Arguments
+///
+/// * `stats` - La table partagée des statistiques, indexée par nom d'interface.
+/// * `interface` - Le nom de l'interface concernée.
+/// * `update` - La mise à jour à appliquer aux compteurs de l'interface.
+pub fn update_interface_stats(
+    stats: &Mutex<HashMap<String, InterfaceStats>>,
+    interface: &str,
+    update: impl FnOnce(&mut InterfaceStats),
+) {
+    let mut stats_locked = stats.lock().expect("Failed to lock the mutex");
+    update(stats_locked.entry(interface.to_string()).or_default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_interface_stats() {
+        let stats = Mutex::new(HashMap::new());
+
+        update_interface_stats(&stats, "eth0", |s| s.record_frame(60, Some("Ipv4")));
+        update_interface_stats(&stats, "eth0", |s| s.record_frame(42, Some("Arp")));
+        update_interface_stats(&stats, "eth0", |s| s.record_frame(1514, Some("Ipv4")));
+        update_interface_stats(&stats, "eth0", |s| s.record_frame(8, None));
+        update_interface_stats(&stats, "eth0", |s| s.record_read_error());
+
+        let stats = stats.lock().unwrap();
+        let eth0 = &stats["eth0"];
+        assert_eq!(eth0.frames_received, 4);
+        assert_eq!(eth0.bytes, 1624);
+        assert_eq!(eth0.parse_failures, 1);
+        assert_eq!(eth0.read_errors, 1);
+        assert_eq!(eth0.frames_by_ethertype["Ipv4"], 2);
+        assert_eq!(eth0.frames_by_ethertype["Arp"], 1);
+    }
+}
@@ -0,0 +1,145 @@
+//! # Détails ARP
+//!
+//! Ce module conserve l'opération et les adresses matérielles d'un paquet ARP, et classe les paquets
+//! particuliers décrits par la RFC 5227 : sondes (probes), annonces et ARP gratuits.
+
+use std::net::Ipv4Addr;
+
+use pnet::packet::arp::{ArpOperations, ArpPacket};
+use pnet::util::MacAddr;
+use serde::Serialize;
+
+/// Informations extraites d'un paquet ARP, en complément des adresses IP de `Layer3Infos`.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct ArpInfos {
+    /// Opération ARP : Request, Reply, ou le code numérique pour les autres opérations.
+    pub operation: String,
+    /// Adresse matérielle de l'émetteur.
+    pub sender_hw_address: String,
+    /// Adresse matérielle de la cible (nulle dans une requête ordinaire).
+    pub target_hw_address: String,
+    /// Classification RFC 5227 : Probe, Announcement ou Gratuitous ; `None` pour un échange ordinaire.
+    pub kind: Option<String>,
+}
+
+impl ArpInfos {
+    /// Construit les informations ARP d'un paquet et le classe selon la RFC 5227.
+    pub fn from_packet(arp_packet: &ArpPacket<'_>) -> ArpInfos {
+        let operation = arp_packet.get_operation();
+        let sender_proto_addr = arp_packet.get_sender_proto_addr();
+        let target_proto_addr = arp_packet.get_target_proto_addr();
+
+        let kind = if operation == ArpOperations::Request
+            && sender_proto_addr == Ipv4Addr::UNSPECIFIED
+            && arp_packet.get_target_hw_addr() == MacAddr::zero()
+        {
+            // Sonde : vérification qu'une adresse n'est pas déjà utilisée avant de se l'attribuer
+            Some("Probe")
+        } else if sender_proto_addr == target_proto_addr && operation == ArpOperations::Request {
+            // Annonce : l'hôte revendique son adresse auprès du réseau
+            Some("Announcement")
+        } else if sender_proto_addr == target_proto_addr && operation == ArpOperations::Reply {
+            // Réponse non sollicitée mettant à jour les caches ARP voisins
+            Some("Gratuitous")
+        } else {
+            None
+        };
+
+        ArpInfos {
+            operation: match operation {
+                ArpOperations::Request => "Request".to_string(),
+                ArpOperations::Reply => "Reply".to_string(),
+                other => other.0.to_string(),
+            },
+            sender_hw_address: arp_packet.get_sender_hw_addr().to_string(),
+            target_hw_address: arp_packet.get_target_hw_addr().to_string(),
+            kind: kind.map(str::to_string),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pnet::packet::arp::{ArpHardwareTypes, ArpOperation, MutableArpPacket};
+    use pnet::packet::ethernet::EtherTypes;
+
+    fn arp_packet(
+        operation: ArpOperation,
+        sender_ip: Ipv4Addr,
+        target_mac: MacAddr,
+        target_ip: Ipv4Addr,
+    ) -> Vec<u8> {
+        let mut buf = vec![0u8; 28];
+        let mut arp = MutableArpPacket::new(&mut buf).unwrap();
+        arp.set_hardware_type(ArpHardwareTypes::Ethernet);
+        arp.set_protocol_type(EtherTypes::Ipv4);
+        arp.set_hw_addr_len(6);
+        arp.set_proto_addr_len(4);
+        arp.set_operation(operation);
+        arp.set_sender_hw_addr(MacAddr::new(0x02, 0, 0, 0, 0, 0x01));
+        arp.set_sender_proto_addr(sender_ip);
+        arp.set_target_hw_addr(target_mac);
+        arp.set_target_proto_addr(target_ip);
+        buf
+    }
+
+    fn classify(buf: &[u8]) -> ArpInfos {
+        ArpInfos::from_packet(&ArpPacket::new(buf).unwrap())
+    }
+
+    #[test]
+    fn test_arp_request_and_reply() {
+        let host = Ipv4Addr::new(192, 168, 1, 10);
+        let gateway = Ipv4Addr::new(192, 168, 1, 1);
+
+        let request = classify(&arp_packet(
+            ArpOperations::Request,
+            host,
+            MacAddr::zero(),
+            gateway,
+        ));
+        assert_eq!(request.operation, "Request");
+        assert_eq!(request.sender_hw_address, "02:00:00:00:00:01");
+        assert_eq!(request.target_hw_address, "00:00:00:00:00:00");
+        assert_eq!(request.kind, None);
+
+        let reply = classify(&arp_packet(
+            ArpOperations::Reply,
+            gateway,
+            MacAddr::new(0x02, 0, 0, 0, 0, 0x02),
+            host,
+        ));
+        assert_eq!(reply.operation, "Reply");
+        assert_eq!(reply.kind, None);
+    }
+
+    #[test]
+    fn test_arp_rfc5227_classification() {
+        let address = Ipv4Addr::new(10, 0, 0, 42);
+
+        let probe = classify(&arp_packet(
+            ArpOperations::Request,
+            Ipv4Addr::UNSPECIFIED,
+            MacAddr::zero(),
+            address,
+        ));
+        assert_eq!(probe.kind.as_deref(), Some("Probe"));
+
+        let announcement = classify(&arp_packet(
+            ArpOperations::Request,
+            address,
+            MacAddr::zero(),
+            address,
+        ));
+        assert_eq!(announcement.kind.as_deref(), Some("Announcement"));
+
+        let gratuitous = classify(&arp_packet(
+            ArpOperations::Reply,
+            address,
+            MacAddr::broadcast(),
+            address,
+        ));
+        assert_eq!(gratuitous.kind.as_deref(), Some("Gratuitous"));
+    }
+}
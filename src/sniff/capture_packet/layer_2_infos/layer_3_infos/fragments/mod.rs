@@ -0,0 +1,358 @@
+//! # Réassemblage des fragments IPv4 et IPv6
+//!
+//! Seul le premier fragment d'un datagramme fragmenté contient l'en-tête de la couche 4 : sans réassemblage,
+//! les fragments suivants ne peuvent pas être rattachés à leur flux (ports inconnus).
+//!
+//! Ce module conserve les fragments de chaque datagramme, identifié par ses adresses, son protocole et son
+//! identifiant, jusqu'à ce qu'il soit complet. Les datagrammes incomplets sont abandonnés après un délai,
+//! et la mémoire occupée par les fragments en attente est bornée.
+//!
+//! Les fragments qui se chevauchent, les premiers fragments trop courts pour contenir l'en-tête de la couche 4
+//! et les datagrammes dépassant la taille maximale sont signalés comme anomalies.
+//!
+//! Chaque thread de capture possède son propre réassembleur, qu'il transmet à la dissection de chaque trame
+//! (voir [`Reassemblers`](super::Reassemblers)).
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+/// Délai au-delà duquel un datagramme incomplet est abandonné.
+pub const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Mémoire maximale occupée par les fragments en attente, par thread de capture.
+pub const MAX_PENDING_BYTES: usize = 4 * 1024 * 1024;
+/// Taille maximale d'un datagramme réassemblé.
+const MAX_DATAGRAM_SIZE: usize = 65535;
+/// Intervalle minimal entre deux purges des datagrammes expirés.
+const EXPIRATION_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+pub const ANOMALY_OVERLAPPING_FRAGMENT: &str = "Overlapping fragment";
+pub const ANOMALY_TINY_FRAGMENT: &str = "Tiny fragment";
+pub const ANOMALY_OVERSIZED_DATAGRAM: &str = "Oversized datagram";
+
+/// Identifie les fragments d'un même datagramme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FragmentKey {
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub protocol: u8,
+    pub identification: u32,
+}
+
+/// Un fragment reçu.
+#[derive(Debug, Clone, Copy)]
+pub struct Fragment<'a> {
+    /// Décalage des données du fragment dans le datagramme, en octets.
+    pub offset: usize,
+    /// Drapeau MF : d'autres fragments suivent.
+    pub more_fragments: bool,
+    /// Données du fragment.
+    pub payload: &'a [u8],
+    /// Taille minimale de l'en-tête de la couche 4 attendu dans le premier fragment.
+    pub min_header_len: usize,
+}
+
+/// Résultat de l'ajout d'un fragment.
+#[derive(Debug, Default, PartialEq)]
+pub struct Reassembly {
+    /// Données de la couche 4 disponibles : le datagramme complet une fois réassemblé,
+    /// ou à défaut le premier fragment s'il a été reçu, pour retrouver l'en-tête de la couche 4.
+    pub payload: Option<Vec<u8>>,
+    /// Indique si le datagramme est complet : seul le datagramme complet est disséqué jusqu'à la couche 7,
+    /// les autres fragments sont seulement rattachés à leur flux.
+    pub complete: bool,
+    /// Anomalies détectées sur ce fragment.
+    pub anomalies: Vec<String>,
+}
+
+/// Datagramme en cours de réassemblage.
+#[derive(Debug)]
+struct PendingDatagram {
+    first_seen: Instant,
+    /// Fragments reçus, sous forme (décalage, données).
+    fragments: Vec<(usize, Vec<u8>)>,
+    /// Taille totale, connue à la réception du dernier fragment.
+    total_len: Option<usize>,
+    bytes: usize,
+}
+
+impl PendingDatagram {
+    /// Indique si un fragment identique (même décalage, mêmes données) a déjà été reçu.
+    fn contains(&self, start: usize, payload: &[u8]) -> bool {
+        self.fragments
+            .iter()
+            .any(|(offset, data)| *offset == start && data == payload)
+    }
+
+    fn overlaps(&self, start: usize, end: usize) -> bool {
+        self.fragments
+            .iter()
+            .any(|(offset, data)| start < offset + data.len() && *offset < end)
+    }
+
+    fn first_fragment(&self) -> Option<&Vec<u8>> {
+        self.fragments
+            .iter()
+            .find(|(offset, _)| *offset == 0)
+            .map(|(_, data)| data)
+    }
+
+    /// Assemble le datagramme si tous les fragments ont été reçus.
+    fn assemble(&mut self) -> Option<Vec<u8>> {
+        let total_len = self.total_len?;
+        self.fragments.sort_by_key(|(offset, _)| *offset);
+        let mut datagram = Vec::with_capacity(total_len);
+        for (offset, data) in &self.fragments {
+            if *offset != datagram.len() {
+                return None;
+            }
+            datagram.extend_from_slice(data);
+        }
+        (datagram.len() == total_len).then_some(datagram)
+    }
+}
+
+/// Réassembleur de fragments IP, avec délai d'expiration et limite mémoire.
+#[derive(Debug)]
+pub struct FragmentReassembler {
+    pending: HashMap<FragmentKey, PendingDatagram>,
+    pending_bytes: usize,
+    timeout: Duration,
+    max_pending_bytes: usize,
+    last_sweep: Option<Instant>,
+}
+
+impl Default for FragmentReassembler {
+    fn default() -> Self {
+        FragmentReassembler::new(REASSEMBLY_TIMEOUT, MAX_PENDING_BYTES)
+    }
+}
+
+impl FragmentReassembler {
+    pub fn new(timeout: Duration, max_pending_bytes: usize) -> Self {
+        FragmentReassembler {
+            pending: HashMap::new(),
+            pending_bytes: 0,
+            timeout,
+            max_pending_bytes,
+            last_sweep: None,
+        }
+    }
+
+    /// Ajoute un fragment au datagramme correspondant et retourne les données disponibles.
+    pub fn add(&mut self, key: FragmentKey, fragment: Fragment<'_>, now: Instant) -> Reassembly {
+        self.expire(now);
+
+        let mut anomalies = Vec::new();
+        let start = fragment.offset;
+        let end = start + fragment.payload.len();
+
+        if start == 0 && fragment.more_fragments && fragment.payload.len() < fragment.min_header_len
+        {
+            anomalies.push(ANOMALY_TINY_FRAGMENT.to_string());
+        }
+        if end > MAX_DATAGRAM_SIZE {
+            // Le datagramme ne pourra jamais être réassemblé (ping of death)
+            anomalies.push(ANOMALY_OVERSIZED_DATAGRAM.to_string());
+            self.remove(&key);
+            return Reassembly {
+                anomalies,
+                ..Default::default()
+            };
+        }
+
+        let datagram = self.pending.entry(key).or_insert_with(|| PendingDatagram {
+            first_seen: now,
+            fragments: Vec::new(),
+            total_len: None,
+            bytes: 0,
+        });
+
+        if datagram.contains(start, fragment.payload) {
+            // Fragment dupliqué (retransmission, trame vue deux fois par un port miroir) : déjà reçu
+        } else if datagram.overlaps(start, end) {
+            // Les données déjà reçues sont conservées
+            anomalies.push(ANOMALY_OVERLAPPING_FRAGMENT.to_string());
+        } else if !fragment.payload.is_empty() {
+            datagram.fragments.push((start, fragment.payload.to_vec()));
+            datagram.bytes += fragment.payload.len();
+            self.pending_bytes += fragment.payload.len();
+        }
+        if !fragment.more_fragments {
+            datagram.total_len = Some(end);
+        }
+
+        let reassembly = match datagram.assemble() {
+            Some(payload) => {
+                self.remove(&key);
+                Reassembly {
+                    payload: Some(payload),
+                    complete: true,
+                    anomalies,
+                }
+            }
+            None => Reassembly {
+                payload: datagram.first_fragment().cloned(),
+                complete: false,
+                anomalies,
+            },
+        };
+
+        self.enforce_memory_limit();
+        reassembly
+    }
+
+    fn remove(&mut self, key: &FragmentKey) {
+        if let Some(datagram) = self.pending.remove(key) {
+            self.pending_bytes -= datagram.bytes;
+        }
+    }
+
+    /// Abandonne les datagrammes incomplets dont le premier fragment est trop ancien.
+    fn expire(&mut self, now: Instant) {
+        if self
+            .last_sweep
+            .is_some_and(|last_sweep| now.duration_since(last_sweep) < EXPIRATION_SWEEP_INTERVAL)
+        {
+            return;
+        }
+        self.last_sweep = Some(now);
+
+        let timeout = self.timeout;
+        let mut expired_bytes = 0;
+        self.pending.retain(|_, datagram| {
+            let alive = now.duration_since(datagram.first_seen) < timeout;
+            if !alive {
+                expired_bytes += datagram.bytes;
+            }
+            alive
+        });
+        self.pending_bytes -= expired_bytes;
+    }
+
+    /// Abandonne les datagrammes les plus anciens tant que la limite mémoire est dépassée.
+    fn enforce_memory_limit(&mut self) {
+        while self.pending_bytes > self.max_pending_bytes {
+            let oldest = self
+                .pending
+                .iter()
+                .min_by_key(|(_, datagram)| datagram.first_seen)
+                .map(|(key, _)| *key);
+            match oldest {
+                Some(key) => self.remove(&key),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> FragmentKey {
+        FragmentKey {
+            source: "10.0.0.1".parse().unwrap(),
+            destination: "10.0.0.2".parse().unwrap(),
+            protocol: 17,
+            identification: 0x1234,
+        }
+    }
+
+    fn fragment(offset: usize, more_fragments: bool, payload: &[u8]) -> Fragment<'_> {
+        Fragment {
+            offset,
+            more_fragments,
+            payload,
+            min_header_len: 8,
+        }
+    }
+
+    #[test]
+    fn test_reassemble_out_of_order() {
+        let mut reassembler = FragmentReassembler::default();
+        let now = Instant::now();
+        let first = [1u8; 16];
+        let last = [2u8; 4];
+
+        let reassembly = reassembler.add(key(), fragment(16, false, &last), now);
+        assert_eq!(reassembly.payload, None);
+        assert!(!reassembly.complete);
+
+        let reassembly = reassembler.add(key(), fragment(0, true, &first), now);
+        assert!(reassembly.complete);
+        assert_eq!(
+            reassembly.payload.unwrap(),
+            [&first[..], &last[..]].concat()
+        );
+        assert!(reassembly.anomalies.is_empty());
+        assert_eq!(reassembler.pending.len(), 0);
+    }
+
+    #[test]
+    fn test_pending_datagram_exposes_first_fragment() {
+        let mut reassembler = FragmentReassembler::default();
+        let now = Instant::now();
+        let first = [1u8; 16];
+
+        reassembler.add(key(), fragment(0, true, &first), now);
+        let reassembly = reassembler.add(key(), fragment(24, true, &[3u8; 8]), now);
+
+        assert!(!reassembly.complete);
+        assert_eq!(reassembly.payload, Some(first.to_vec()));
+    }
+
+    #[test]
+    fn test_anomalies() {
+        let mut reassembler = FragmentReassembler::default();
+        let now = Instant::now();
+
+        let reassembly = reassembler.add(key(), fragment(0, true, &[0u8; 4]), now);
+        assert_eq!(reassembly.anomalies, vec![ANOMALY_TINY_FRAGMENT]);
+
+        let reassembly = reassembler.add(key(), fragment(0, true, &[0u8; 16]), now);
+        assert_eq!(reassembly.anomalies, vec![ANOMALY_OVERLAPPING_FRAGMENT]);
+
+        // Un fragment identique à un fragment déjà reçu n'est pas un chevauchement
+        reassembler.add(key(), fragment(16, true, &[1u8; 8]), now);
+        let reassembly = reassembler.add(key(), fragment(16, true, &[1u8; 8]), now);
+        assert!(reassembly.anomalies.is_empty());
+        let reassembly = reassembler.add(key(), fragment(16, true, &[2u8; 8]), now);
+        assert_eq!(reassembly.anomalies, vec![ANOMALY_OVERLAPPING_FRAGMENT]);
+
+        let reassembly = reassembler.add(key(), fragment(65528, true, &[0u8; 16]), now);
+        assert_eq!(reassembly.anomalies, vec![ANOMALY_OVERSIZED_DATAGRAM]);
+        assert_eq!(reassembler.pending.len(), 0);
+    }
+
+    #[test]
+    fn test_timeout_and_memory_limit() {
+        let mut reassembler = FragmentReassembler::new(Duration::from_secs(30), 32);
+        let now = Instant::now();
+
+        reassembler.add(key(), fragment(0, true, &[0u8; 16]), now);
+        assert_eq!(reassembler.pending.len(), 1);
+        reassembler.add(
+            FragmentKey {
+                identification: 1,
+                ..key()
+            },
+            fragment(0, true, &[0u8; 24]),
+            now + Duration::from_secs(1),
+        );
+        // La limite de 32 octets est dépassée : le datagramme le plus ancien est abandonné
+        assert_eq!(reassembler.pending.len(), 1);
+
+        reassembler.add(
+            FragmentKey {
+                identification: 2,
+                ..key()
+            },
+            fragment(0, true, &[0u8; 8]),
+            now + Duration::from_secs(40),
+        );
+        assert_eq!(reassembler.pending.len(), 1);
+    }
+}
@@ -0,0 +1,146 @@
+//! # Chaîne des en-têtes d'extension IPv6
+//!
+//! Le champ `next_header` de l'en-tête IPv6 ne désigne pas forcément le protocole de la couche 4 :
+//! il peut annoncer un en-tête d'extension (Hop-by-Hop, Routing, Fragment, Destination Options...),
+//! lui-même suivi d'autres en-têtes. Ce module parcourt cette chaîne pour retrouver le protocole
+//! de couche supérieure et le début de ses données.
+
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+
+/// Résultat du parcours de la chaîne des en-têtes d'extension.
+///
+/// Si un en-tête Fragment est présent, `protocol` et `payload` désignent la partie fragmentable qui le suit.
+#[derive(Debug, PartialEq)]
+pub struct Ipv6UpperLayer<'a> {
+    /// Protocole de couche supérieure (TCP, UDP, ICMPv6...).
+    pub protocol: IpNextHeaderProtocol,
+    /// Données de la couche supérieure, après le dernier en-tête d'extension.
+    pub payload: &'a [u8],
+    /// En-têtes d'extension rencontrés, dans l'ordre.
+    pub extension_headers: Vec<String>,
+    /// Contenu de l'en-tête Fragment, s'il est présent.
+    pub fragment: Option<Ipv6Fragment>,
+}
+
+/// Champs de l'en-tête d'extension Fragment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv6Fragment {
+    /// Décalage du fragment, en octets.
+    pub offset: u16,
+    /// Drapeau M : d'autres fragments suivent.
+    pub more_fragments: bool,
+    /// Identifiant commun aux fragments d'un même paquet.
+    pub identification: u32,
+}
+
+/// Parcourt les en-têtes d'extension à partir du `next_header` et de la charge utile d'un paquet IPv6.
+///
+/// Le parcours s'arrête au premier protocole qui n'est pas un en-tête d'extension, ou sur un en-tête tronqué,
+/// auquel cas la charge utile retournée est vide. Il s'arrête aussi après un en-tête Fragment : les données qui
+/// suivent ne peuvent être analysées qu'une fois le datagramme réassemblé.
+pub fn walk_extension_headers(
+    next_header: IpNextHeaderProtocol,
+    payload: &[u8],
+) -> Ipv6UpperLayer<'_> {
+    let mut upper_layer = Ipv6UpperLayer {
+        protocol: next_header,
+        payload,
+        extension_headers: Vec::new(),
+        fragment: None,
+    };
+
+    while let Some((name, header_len)) = extension_header(upper_layer.protocol, upper_layer.payload)
+    {
+        let data = upper_layer.payload;
+        if data.len() < header_len {
+            upper_layer.payload = &[];
+            break;
+        }
+
+        if upper_layer.protocol == IpNextHeaderProtocols::Ipv6Frag {
+            upper_layer.fragment = Some(Ipv6Fragment {
+                offset: u16::from_be_bytes([data[2], data[3]]) & 0xfff8,
+                more_fragments: data[3] & 0x01 != 0,
+                identification: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            });
+        }
+
+        upper_layer.extension_headers.push(name.to_string());
+        upper_layer.protocol = IpNextHeaderProtocol(data[0]);
+        upper_layer.payload = &data[header_len..];
+        if upper_layer.fragment.is_some() {
+            break;
+        }
+    }
+    upper_layer
+}
+
+/// Retourne le nom et la longueur de l'en-tête d'extension, ou `None` s'il s'agit d'un protocole de couche supérieure.
+fn extension_header(protocol: IpNextHeaderProtocol, data: &[u8]) -> Option<(&'static str, usize)> {
+    // La longueur des en-têtes est codée dans le deuxième octet
+    let length_field = data.get(1).map_or(0, |len| *len as usize);
+    match protocol {
+        IpNextHeaderProtocols::Hopopt => Some(("Hop-by-Hop", (length_field + 1) * 8)),
+        IpNextHeaderProtocols::Ipv6Route => Some(("Routing", (length_field + 1) * 8)),
+        IpNextHeaderProtocols::Ipv6Frag => Some(("Fragment", 8)),
+        IpNextHeaderProtocols::Ipv6Opts => Some(("Destination Options", (length_field + 1) * 8)),
+        IpNextHeaderProtocols::Ah => Some(("Authentication", (length_field + 2) * 4)),
+        IpNextHeaderProtocols::MobilityHeader => Some(("Mobility", (length_field + 1) * 8)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_hop_by_hop_and_fragment() {
+        // Hop-by-Hop (8 octets) -> Fragment (8 octets) -> UDP
+        let payload = [
+            44, 0, 5, 2, 0, 0, 1, 0, // Hop-by-Hop avec option Router Alert
+            17, 0, 0, 0, 0, 0, 0x12, 0x34, // Fragment : offset 0, M = 0
+            0x30, 0x39, 0x00, 0x35, 0, 8, 0, 0, // UDP 12345 -> 53
+        ];
+
+        let upper_layer = walk_extension_headers(IpNextHeaderProtocols::Hopopt, &payload);
+
+        assert_eq!(upper_layer.protocol, IpNextHeaderProtocols::Udp);
+        assert_eq!(upper_layer.payload, &payload[16..]);
+        assert_eq!(
+            upper_layer.extension_headers,
+            vec!["Hop-by-Hop", "Fragment"]
+        );
+        assert_eq!(
+            upper_layer.fragment,
+            Some(Ipv6Fragment {
+                offset: 0,
+                more_fragments: false,
+                identification: 0x1234,
+            })
+        );
+    }
+
+    #[test]
+    fn test_walk_non_first_fragment() {
+        let payload = [6, 0, 0x05, 0xa9, 0, 0, 0, 1, 0xde, 0xad];
+
+        let upper_layer = walk_extension_headers(IpNextHeaderProtocols::Ipv6Frag, &payload);
+
+        assert_eq!(upper_layer.protocol, IpNextHeaderProtocols::Tcp);
+        assert_eq!(upper_layer.fragment.unwrap().offset, 1448);
+        assert!(upper_layer.fragment.unwrap().more_fragments);
+        assert_eq!(upper_layer.payload, &payload[8..]);
+    }
+
+    #[test]
+    fn test_walk_truncated_header() {
+        let payload = [6, 3, 0, 0];
+
+        let upper_layer = walk_extension_headers(IpNextHeaderProtocols::Ipv6Opts, &payload);
+
+        assert_eq!(upper_layer.protocol, IpNextHeaderProtocols::Ipv6Opts);
+        assert!(upper_layer.payload.is_empty());
+        assert!(upper_layer.extension_headers.is_empty());
+    }
+}
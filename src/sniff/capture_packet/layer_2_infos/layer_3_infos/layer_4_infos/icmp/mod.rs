@@ -0,0 +1,272 @@
+//! # Dissection ICMP et ICMPv6
+//!
+//! Ce module extrait le type et le code des messages ICMP et ICMPv6, l'identifiant et le numéro de séquence
+//! des messages d'écho (ping), et, pour les messages d'erreur (destination injoignable, TTL dépassé...),
+//! l'en-tête du datagramme d'origine afin de relier l'erreur au flux qui l'a provoquée.
+
+use std::net::Ipv4Addr;
+
+use pnet::packet::icmp::IcmpPacket;
+use pnet::packet::icmpv6::Icmpv6Packet;
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::Packet;
+use serde::Serialize;
+
+use super::ndp::NdpInfos;
+
+/// Informations extraites d'un message ICMP ou ICMPv6.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct IcmpInfos {
+    /// Type du message.
+    pub icmp_type: u8,
+    /// Code du message, qui précise le type.
+    pub icmp_code: u8,
+    /// Nom du type de message (Echo Request, Destination Unreachable...).
+    pub message: String,
+    /// Identifiant des messages d'écho.
+    pub identifier: Option<u16>,
+    /// Numéro de séquence des messages d'écho.
+    pub sequence: Option<u16>,
+    /// Passerelle proposée par un message ICMP Redirect.
+    pub gateway: Option<String>,
+    /// En-tête du datagramme ayant provoqué un message d'erreur.
+    pub original_datagram: Option<OriginalDatagram>,
+    /// Détails des messages Neighbor Discovery (ICMPv6 133 à 137).
+    pub ndp: Option<NdpInfos>,
+}
+
+/// En-tête du datagramme d'origine embarqué dans un message d'erreur ICMP.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct OriginalDatagram {
+    pub ip_source: String,
+    pub ip_destination: String,
+    pub protocol: String,
+    pub port_source: Option<String>,
+    pub port_destination: Option<String>,
+}
+
+impl IcmpInfos {
+    /// Décode un message ICMP (IPv4).
+    pub fn from_icmp(icmp_packet: &IcmpPacket<'_>) -> IcmpInfos {
+        let icmp_type = icmp_packet.get_icmp_type().0;
+        // Les 4 octets qui suivent l'en-tête dépendent du type de message
+        let rest = icmp_packet.payload();
+        let mut infos = IcmpInfos {
+            icmp_type,
+            icmp_code: icmp_packet.get_icmp_code().0,
+            message: icmp_message(icmp_type),
+            ..Default::default()
+        };
+
+        match icmp_type {
+            // Echo Reply, Echo Request, Timestamp, Timestamp Reply
+            0 | 8 | 13 | 14 => infos.set_echo_fields(rest),
+            // Destination Unreachable, Source Quench, Redirect, Time Exceeded, Parameter Problem
+            3 | 4 | 5 | 11 | 12 => {
+                if icmp_type == 5 && rest.len() >= 4 {
+                    infos.gateway =
+                        Some(Ipv4Addr::new(rest[0], rest[1], rest[2], rest[3]).to_string());
+                }
+                infos.original_datagram = rest.get(4..).and_then(OriginalDatagram::from_ipv4);
+            }
+            _ => {}
+        }
+        infos
+    }
+
+    /// Décode un message ICMPv6.
+    pub fn from_icmpv6(icmpv6_packet: &Icmpv6Packet<'_>) -> IcmpInfos {
+        let icmp_type = icmpv6_packet.get_icmpv6_type().0;
+        let rest = icmpv6_packet.payload();
+        let mut infos = IcmpInfos {
+            icmp_type,
+            icmp_code: icmpv6_packet.get_icmpv6_code().0,
+            message: icmpv6_message(icmp_type),
+            ..Default::default()
+        };
+
+        match icmp_type {
+            // Echo Request, Echo Reply
+            128 | 129 => infos.set_echo_fields(rest),
+            // Destination Unreachable, Packet Too Big, Time Exceeded, Parameter Problem
+            1..=4 => infos.original_datagram = rest.get(4..).and_then(OriginalDatagram::from_ipv6),
+            // Router Solicitation, Router Advertisement, Neighbor Solicitation, Neighbor Advertisement, Redirect
+            133..=137 => infos.ndp = NdpInfos::parse(icmp_type, rest),
+            _ => {}
+        }
+        infos
+    }
+
+    fn set_echo_fields(&mut self, rest: &[u8]) {
+        if rest.len() >= 4 {
+            self.identifier = Some(u16::from_be_bytes([rest[0], rest[1]]));
+            self.sequence = Some(u16::from_be_bytes([rest[2], rest[3]]));
+        }
+    }
+}
+
+impl OriginalDatagram {
+    /// Décode l'en-tête IPv4 et les 8 premiers octets de la couche 4 embarqués dans une erreur ICMP.
+    fn from_ipv4(data: &[u8]) -> Option<OriginalDatagram> {
+        let ipv4_packet = Ipv4Packet::new(data)?;
+        let header_len = ipv4_packet.get_header_length() as usize * 4;
+        let protocol = ipv4_packet.get_next_level_protocol();
+        let (port_source, port_destination) = embedded_ports(protocol, data.get(header_len..));
+
+        Some(OriginalDatagram {
+            ip_source: ipv4_packet.get_source().to_string(),
+            ip_destination: ipv4_packet.get_destination().to_string(),
+            protocol: protocol.to_string(),
+            port_source,
+            port_destination,
+        })
+    }
+
+    /// Décode l'en-tête IPv6 et le début de la couche 4 embarqués dans une erreur ICMPv6.
+    fn from_ipv6(data: &[u8]) -> Option<OriginalDatagram> {
+        let ipv6_packet = Ipv6Packet::new(data)?;
+        let protocol = ipv6_packet.get_next_header();
+        let (port_source, port_destination) = embedded_ports(protocol, data.get(40..));
+
+        Some(OriginalDatagram {
+            ip_source: ipv6_packet.get_source().to_string(),
+            ip_destination: ipv6_packet.get_destination().to_string(),
+            protocol: protocol.to_string(),
+            port_source,
+            port_destination,
+        })
+    }
+}
+
+/// Lit les ports TCP ou UDP du segment tronqué embarqué dans une erreur ICMP.
+///
+/// Seuls les 8 premiers octets du segment sont garantis : les ports sont lus directement,
+/// `TcpPacket::new` exigeant un en-tête complet.
+fn embedded_ports(
+    protocol: IpNextHeaderProtocol,
+    segment: Option<&[u8]>,
+) -> (Option<String>, Option<String>) {
+    match (protocol, segment) {
+        (IpNextHeaderProtocols::Tcp | IpNextHeaderProtocols::Udp, Some(segment))
+            if segment.len() >= 4 =>
+        {
+            (
+                Some(u16::from_be_bytes([segment[0], segment[1]]).to_string()),
+                Some(u16::from_be_bytes([segment[2], segment[3]]).to_string()),
+            )
+        }
+        _ => (None, None),
+    }
+}
+
+fn icmp_message(icmp_type: u8) -> String {
+    match icmp_type {
+        0 => "Echo Reply",
+        3 => "Destination Unreachable",
+        4 => "Source Quench",
+        5 => "Redirect",
+        8 => "Echo Request",
+        9 => "Router Advertisement",
+        10 => "Router Solicitation",
+        11 => "Time Exceeded",
+        12 => "Parameter Problem",
+        13 => "Timestamp",
+        14 => "Timestamp Reply",
+        _ => "Unknown",
+    }
+    .to_string()
+}
+
+fn icmpv6_message(icmp_type: u8) -> String {
+    match icmp_type {
+        1 => "Destination Unreachable",
+        2 => "Packet Too Big",
+        3 => "Time Exceeded",
+        4 => "Parameter Problem",
+        128 => "Echo Request",
+        129 => "Echo Reply",
+        130 => "Multicast Listener Query",
+        131 => "Multicast Listener Report",
+        132 => "Multicast Listener Done",
+        133 => "Router Solicitation",
+        134 => "Router Advertisement",
+        135 => "Neighbor Solicitation",
+        136 => "Neighbor Advertisement",
+        137 => "Redirect",
+        143 => "Multicast Listener Report v2",
+        _ => "Unknown",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pnet::packet::icmp::{IcmpCode, IcmpTypes, MutableIcmpPacket};
+    use pnet::packet::ipv4::MutableIpv4Packet;
+
+    #[test]
+    fn test_icmp_echo_request() {
+        let mut buf = [0u8; 8];
+        let mut icmp = MutableIcmpPacket::new(&mut buf).unwrap();
+        icmp.set_icmp_type(IcmpTypes::EchoRequest);
+        icmp.set_payload(&[0x12, 0x34, 0x00, 0x07]);
+
+        let infos = IcmpInfos::from_icmp(&IcmpPacket::new(&buf).unwrap());
+
+        assert_eq!(infos.icmp_type, 8);
+        assert_eq!(infos.message, "Echo Request");
+        assert_eq!(infos.identifier, Some(0x1234));
+        assert_eq!(infos.sequence, Some(7));
+        assert_eq!(infos.original_datagram, None);
+    }
+
+    #[test]
+    fn test_icmp_port_unreachable_original_datagram() {
+        // Datagramme d'origine : en-tête IPv4 + 8 premiers octets UDP (53000 -> 161)
+        let mut original = vec![0u8; 28];
+        let mut ipv4 = MutableIpv4Packet::new(&mut original).unwrap();
+        ipv4.set_version(4);
+        ipv4.set_header_length(5);
+        ipv4.set_total_length(28);
+        ipv4.set_next_level_protocol(IpNextHeaderProtocols::Udp);
+        ipv4.set_source(Ipv4Addr::new(10, 0, 0, 5));
+        ipv4.set_destination(Ipv4Addr::new(10, 0, 0, 9));
+        original[20..24].copy_from_slice(&[0xcf, 0x08, 0x00, 0xa1]);
+
+        let mut buf = vec![0u8; 8 + original.len()];
+        let mut icmp = MutableIcmpPacket::new(&mut buf).unwrap();
+        icmp.set_icmp_type(IcmpTypes::DestinationUnreachable);
+        icmp.set_icmp_code(IcmpCode::new(3));
+        buf[8..].copy_from_slice(&original);
+
+        let infos = IcmpInfos::from_icmp(&IcmpPacket::new(&buf).unwrap());
+
+        assert_eq!(infos.message, "Destination Unreachable");
+        assert_eq!(infos.icmp_code, 3);
+        assert_eq!(infos.identifier, None);
+        assert_eq!(
+            infos.original_datagram,
+            Some(OriginalDatagram {
+                ip_source: "10.0.0.5".to_string(),
+                ip_destination: "10.0.0.9".to_string(),
+                protocol: "Udp".to_string(),
+                port_source: Some("53000".to_string()),
+                port_destination: Some("161".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_icmpv6_echo_reply() {
+        let buf = [129, 0, 0, 0, 0x00, 0x01, 0x00, 0x02];
+
+        let infos = IcmpInfos::from_icmpv6(&Icmpv6Packet::new(&buf).unwrap());
+
+        assert_eq!(infos.message, "Echo Reply");
+        assert_eq!(infos.identifier, Some(1));
+        assert_eq!(infos.sequence, Some(2));
+    }
+}
@@ -0,0 +1,540 @@
+//! # Dissection DHCP et DHCPv6
+//!
+//! Les échanges DHCP associent l'adresse MAC d'un client à l'adresse IP qui lui est attribuée, et portent
+//! souvent son nom d'hôte (option 12) et le type d'équipement (Vendor Class, option 60).
+//!
+//! Ce module décode les messages DHCPv4 (RFC 2131/2132) et DHCPv6 (RFC 8415), et construit au fil de la
+//! capture un inventaire des baux : adresse MAC → adresse IP → nom d'hôte ([`DhcpLeaseInventory`]).
+//!
+//! Les messages d'un même échange (Discover, Request, Ack...) appartiennent au même flux : l'inventaire est
+//! alimenté paquet par paquet (voir [`record_leases`]), avant que les flux ne soient fusionnés.
+
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+
+use pnet::packet::dhcp::{DhcpOperations, DhcpPacket};
+use pnet::packet::Packet;
+use pnet::util::MacAddr;
+use serde::Serialize;
+
+use crate::sniff::capture_packet::layer_2_infos::PacketInfos;
+
+/// Cookie magique précédant les options DHCPv4.
+const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+
+/// Options DHCPv4.
+const OPTION_PAD: u8 = 0;
+const OPTION_HOSTNAME: u8 = 12;
+const OPTION_REQUESTED_IP: u8 = 50;
+const OPTION_LEASE_TIME: u8 = 51;
+const OPTION_MESSAGE_TYPE: u8 = 53;
+const OPTION_SERVER_IDENTIFIER: u8 = 54;
+const OPTION_PARAMETER_REQUEST_LIST: u8 = 55;
+const OPTION_VENDOR_CLASS: u8 = 60;
+const OPTION_END: u8 = 255;
+
+/// Options DHCPv6.
+const OPTION6_CLIENT_ID: u16 = 1;
+const OPTION6_SERVER_ID: u16 = 2;
+const OPTION6_IA_NA: u16 = 3;
+const OPTION6_IA_ADDRESS: u16 = 5;
+const OPTION6_ORO: u16 = 6;
+const OPTION6_RELAY_MESSAGE: u16 = 9;
+const OPTION6_VENDOR_CLASS: u16 = 16;
+const OPTION6_CLIENT_FQDN: u16 = 39;
+
+/// Messages de relais DHCPv6, qui encapsulent le message du client ou du serveur.
+const RELAY_FORWARD: u8 = 12;
+const RELAY_REPLY: u8 = 13;
+
+/// Ports des clients DHCPv4 et DHCPv6.
+pub const DHCP_CLIENT_PORT: u16 = 68;
+pub const DHCPV6_CLIENT_PORT: u16 = 546;
+
+/// Informations extraites d'un message DHCP ou DHCPv6.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct DhcpInfos {
+    /// Type du message (Discover, Offer, Request, Ack... ou Solicit, Advertise, Reply... en DHCPv6).
+    pub message_type: String,
+    /// Identifiant de la transaction.
+    pub transaction_id: u32,
+    /// Adresse MAC du client (champ chaddr, ou DUID basé sur l'adresse de liaison en DHCPv6).
+    pub client_mac: Option<String>,
+    /// Adresse actuelle du client (champ ciaddr).
+    pub client_ip: Option<String>,
+    /// Adresse demandée par le client (option 50).
+    pub requested_ip: Option<String>,
+    /// Adresse proposée ou attribuée par le serveur (champ yiaddr, ou adresse IA_NA en DHCPv6).
+    pub offered_ip: Option<String>,
+    /// Identifiant du serveur (option 54, ou DUID du serveur en DHCPv6).
+    pub server_identifier: Option<String>,
+    /// Nom d'hôte du client (option 12, ou Client FQDN en DHCPv6).
+    pub hostname: Option<String>,
+    /// Classe du fabricant du client (option 60, ou Vendor Class en DHCPv6).
+    pub vendor_class: Option<String>,
+    /// Options demandées par le client (option 55, ou Option Request en DHCPv6).
+    pub parameter_request_list: Vec<u16>,
+    /// Durée du bail en secondes.
+    pub lease_time: Option<u32>,
+    /// DUID du client en hexadécimal, en DHCPv6.
+    pub client_duid: Option<String>,
+}
+
+impl DhcpInfos {
+    /// Décode un message DHCPv4 (charge utile d'un datagramme UDP).
+    pub fn parse_v4(data: &[u8]) -> Option<DhcpInfos> {
+        let dhcp_packet = DhcpPacket::new(data)?;
+        let options = dhcp_packet.payload();
+        if !options.starts_with(&MAGIC_COOKIE) {
+            return None;
+        }
+
+        let mut infos = DhcpInfos {
+            message_type: match dhcp_packet.get_op() {
+                DhcpOperations::Request => "Request".to_string(),
+                _ => "Reply".to_string(),
+            },
+            transaction_id: dhcp_packet.get_xid(),
+            client_mac: Some(dhcp_packet.get_chaddr())
+                .filter(|mac| *mac != MacAddr::zero())
+                .map(|mac| mac.to_string()),
+            client_ip: non_zero_ipv4(dhcp_packet.get_ciaddr()),
+            offered_ip: non_zero_ipv4(dhcp_packet.get_yiaddr()),
+            ..Default::default()
+        };
+
+        let mut options = &options[MAGIC_COOKIE.len()..];
+        while let Some(&code) = options.first() {
+            match code {
+                OPTION_END => break,
+                OPTION_PAD => {
+                    options = &options[1..];
+                    continue;
+                }
+                _ => {}
+            }
+            let len = *options.get(1)? as usize;
+            let value = options.get(2..2 + len)?;
+            options = &options[2 + len..];
+
+            match code {
+                OPTION_MESSAGE_TYPE => {
+                    if let Some(&message_type) = value.first() {
+                        infos.message_type = message_type_name(message_type);
+                    }
+                }
+                OPTION_HOSTNAME => infos.hostname = Some(printable(value)),
+                OPTION_REQUESTED_IP => infos.requested_ip = ipv4_option(value),
+                OPTION_SERVER_IDENTIFIER => infos.server_identifier = ipv4_option(value),
+                OPTION_LEASE_TIME => {
+                    infos.lease_time = value.try_into().ok().map(u32::from_be_bytes)
+                }
+                OPTION_PARAMETER_REQUEST_LIST => {
+                    infos.parameter_request_list = value.iter().map(|&code| code as u16).collect()
+                }
+                OPTION_VENDOR_CLASS => infos.vendor_class = Some(printable(value)),
+                _ => {}
+            }
+        }
+
+        Some(infos)
+    }
+
+    /// Décode un message DHCPv6 (charge utile d'un datagramme UDP).
+    ///
+    /// Les messages de relais sont décodés jusqu'au message du client ou du serveur qu'ils encapsulent.
+    pub fn parse_v6(data: &[u8]) -> Option<DhcpInfos> {
+        let message_type = *data.first()?;
+        if message_type == RELAY_FORWARD || message_type == RELAY_REPLY {
+            // nombre de sauts (1), adresse du lien (16), adresse du pair (16)
+            let options = data.get(34..)?;
+            return dhcpv6_options(options)
+                .find(|(code, _)| *code == OPTION6_RELAY_MESSAGE)
+                .and_then(|(_, relayed)| DhcpInfos::parse_v6(relayed));
+        }
+        let header = data.get(..4)?;
+
+        let mut infos = DhcpInfos {
+            message_type: message_type_v6_name(message_type),
+            transaction_id: u32::from_be_bytes([0, header[1], header[2], header[3]]),
+            ..Default::default()
+        };
+
+        for (code, value) in dhcpv6_options(&data[4..]) {
+            match code {
+                OPTION6_CLIENT_ID => {
+                    infos.client_duid = Some(hex(value));
+                    infos.client_mac = duid_mac_address(value);
+                }
+                OPTION6_SERVER_ID => infos.server_identifier = Some(hex(value)),
+                OPTION6_IA_NA => {
+                    // IAID (4), T1 (4), T2 (4), puis les adresses attribuées
+                    let addresses = value.get(12..).unwrap_or_default();
+                    if let Some((_, address)) =
+                        dhcpv6_options(addresses).find(|(code, _)| *code == OPTION6_IA_ADDRESS)
+                    {
+                        if let Some(ip) = address.get(..16) {
+                            let ip: [u8; 16] = ip.try_into().unwrap_or_default();
+                            infos.offered_ip = Some(Ipv6Addr::from(ip).to_string());
+                        }
+                        infos.lease_time = address
+                            .get(20..24)
+                            .and_then(|valid| valid.try_into().ok())
+                            .map(u32::from_be_bytes);
+                    }
+                }
+                OPTION6_ORO => {
+                    infos.parameter_request_list = value
+                        .chunks_exact(2)
+                        .map(|code| u16::from_be_bytes([code[0], code[1]]))
+                        .collect()
+                }
+                OPTION6_VENDOR_CLASS => {
+                    // Numéro d'entreprise (4), puis des chaînes précédées de leur longueur
+                    let mut classes = Vec::new();
+                    let mut rest = value.get(4..).unwrap_or_default();
+                    while rest.len() >= 2 {
+                        let len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+                        match rest.get(2..2 + len) {
+                            Some(class) => classes.push(printable(class)),
+                            None => break,
+                        }
+                        rest = &rest[2 + len..];
+                    }
+                    infos.vendor_class = Some(classes.join(", ")).filter(|c| !c.is_empty());
+                }
+                OPTION6_CLIENT_FQDN => {
+                    // Drapeaux (1), puis le nom au format DNS
+                    infos.hostname = value.get(1..).and_then(dns_name);
+                }
+                _ => {}
+            }
+        }
+
+        Some(infos)
+    }
+}
+
+/// Bail DHCP d'un client, reconstitué à partir des messages échangés.
+#[derive(Debug, Default, Serialize, Clone, Eq, PartialEq)]
+pub struct DhcpLease {
+    /// Adresse MAC du client.
+    pub mac_address: String,
+    /// Adresse IP attribuée, ou à défaut demandée par le client.
+    pub ip_address: Option<String>,
+    /// Nom d'hôte annoncé par le client.
+    pub hostname: Option<String>,
+    /// Classe du fabricant annoncée par le client.
+    pub vendor_class: Option<String>,
+}
+
+/// Inventaire des baux DHCP, indexé par adresse MAC du client.
+#[derive(Debug, Default, Clone)]
+pub struct DhcpLeaseInventory {
+    leases: BTreeMap<String, DhcpLease>,
+}
+
+impl DhcpLeaseInventory {
+    /// Complète l'inventaire avec le message DHCP d'un paquet, s'il en porte un.
+    pub fn record(&mut self, packet: &PacketInfos) {
+        let layer_4_infos = &packet.layer_3_infos.layer_4_infos;
+        let dhcp = match layer_4_infos
+            .layer_7_infos
+            .as_ref()
+            .and_then(|l| l.dhcp.as_ref())
+        {
+            Some(dhcp) => dhcp,
+            None => return,
+        };
+
+        // À défaut d'adresse MAC dans le message, le client est l'extrémité qui utilise le port client
+        let from_client = layer_4_infos.port_source.as_deref().is_some_and(|port| {
+            port == DHCP_CLIENT_PORT.to_string() || port == DHCPV6_CLIENT_PORT.to_string()
+        });
+        let mac_address = match &dhcp.client_mac {
+            Some(mac) => mac.clone(),
+            None if from_client => packet.mac_address_source.clone(),
+            None => packet.mac_address_destination.clone(),
+        };
+        if mac_address.is_empty() {
+            return;
+        }
+
+        let lease = self
+            .leases
+            .entry(mac_address.clone())
+            .or_insert_with(|| DhcpLease {
+                mac_address,
+                ..Default::default()
+            });
+        // L'adresse attribuée par le serveur prime sur l'adresse demandée par le client
+        if dhcp.offered_ip.is_some() {
+            lease.ip_address = dhcp.offered_ip.clone();
+        } else if lease.ip_address.is_none() {
+            lease.ip_address = dhcp.requested_ip.clone().or(dhcp.client_ip.clone());
+        }
+        if dhcp.hostname.is_some() {
+            lease.hostname = dhcp.hostname.clone();
+        }
+        if dhcp.vendor_class.is_some() {
+            lease.vendor_class = dhcp.vendor_class.clone();
+        }
+    }
+
+    /// Retourne les baux connus, par adresse MAC.
+    pub fn leases(&self) -> impl Iterator<Item = &DhcpLease> {
+        self.leases.values()
+    }
+}
+
+/// Complète l'inventaire des baux avec le message DHCP d'un paquet capturé, s'il en porte un.
+pub fn record_leases(leases: &Mutex<DhcpLeaseInventory>, packet: &PacketInfos) {
+    leases
+        .lock()
+        .expect("Failed to lock the mutex")
+        .record(packet);
+}
+
+fn message_type_name(message_type: u8) -> String {
+    match message_type {
+        1 => "Discover".to_string(),
+        2 => "Offer".to_string(),
+        3 => "Request".to_string(),
+        4 => "Decline".to_string(),
+        5 => "Ack".to_string(),
+        6 => "Nak".to_string(),
+        7 => "Release".to_string(),
+        8 => "Inform".to_string(),
+        other => format!("Type {}", other),
+    }
+}
+
+fn message_type_v6_name(message_type: u8) -> String {
+    match message_type {
+        1 => "Solicit".to_string(),
+        2 => "Advertise".to_string(),
+        3 => "Request".to_string(),
+        4 => "Confirm".to_string(),
+        5 => "Renew".to_string(),
+        6 => "Rebind".to_string(),
+        7 => "Reply".to_string(),
+        8 => "Release".to_string(),
+        9 => "Decline".to_string(),
+        10 => "Reconfigure".to_string(),
+        11 => "Information-Request".to_string(),
+        other => format!("Type {}", other),
+    }
+}
+
+/// Parcourt les options DHCPv6 : code (2 octets), longueur (2 octets), valeur.
+fn dhcpv6_options(mut data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    std::iter::from_fn(move || {
+        let code = u16::from_be_bytes([*data.first()?, *data.get(1)?]);
+        let len = u16::from_be_bytes([*data.get(2)?, *data.get(3)?]) as usize;
+        let value = data.get(4..4 + len)?;
+        data = &data[4 + len..];
+        Some((code, value))
+    })
+}
+
+/// Extrait l'adresse MAC d'un DUID basé sur l'adresse de liaison (DUID-LLT ou DUID-LL) d'un lien Ethernet.
+fn duid_mac_address(duid: &[u8]) -> Option<String> {
+    let duid_type = u16::from_be_bytes([*duid.first()?, *duid.get(1)?]);
+    let hardware_type = u16::from_be_bytes([*duid.get(2)?, *duid.get(3)?]);
+    let address = match duid_type {
+        1 => duid.get(8..14)?,
+        3 => duid.get(4..10)?,
+        _ => return None,
+    };
+    if hardware_type != 1 {
+        return None;
+    }
+    Some(
+        MacAddr::new(
+            address[0], address[1], address[2], address[3], address[4], address[5],
+        )
+        .to_string(),
+    )
+}
+
+/// Décode un nom au format DNS (suite d'étiquettes précédées de leur longueur), sans compression.
+fn dns_name(mut data: &[u8]) -> Option<String> {
+    let mut labels = Vec::new();
+    while let Some(&len) = data.first() {
+        if len == 0 {
+            break;
+        }
+        labels.push(printable(data.get(1..1 + len as usize)?));
+        data = &data[1 + len as usize..];
+    }
+    Some(labels.join(".")).filter(|name| !name.is_empty())
+}
+
+fn non_zero_ipv4(ip: Ipv4Addr) -> Option<String> {
+    (!ip.is_unspecified()).then(|| ip.to_string())
+}
+
+fn ipv4_option(value: &[u8]) -> Option<String> {
+    let ip: [u8; 4] = value.try_into().ok()?;
+    Some(Ipv4Addr::from(ip).to_string())
+}
+
+/// Convertit une chaîne d'option en texte, sans le zéro terminal que certains clients ajoutent.
+fn printable(value: &[u8]) -> String {
+    String::from_utf8_lossy(value)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+fn hex(value: &[u8]) -> String {
+    value.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Construit un message DHCPv4 avec les options données.
+    fn dhcpv4_message(op: u8, yiaddr: [u8; 4], options: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 236];
+        data[0] = op;
+        data[1] = 1;
+        data[2] = 6;
+        data[4..8].copy_from_slice(&0xdeadbeefu32.to_be_bytes());
+        data[16..20].copy_from_slice(&yiaddr);
+        data[28..34].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x01]);
+        data.extend_from_slice(&MAGIC_COOKIE);
+        data.extend_from_slice(options);
+        data.push(OPTION_END);
+        data
+    }
+
+    #[test]
+    fn test_parse_v4_request() {
+        let mut options = vec![OPTION_MESSAGE_TYPE, 1, 3];
+        options.extend_from_slice(&[OPTION_REQUESTED_IP, 4, 192, 168, 1, 20]);
+        options.extend_from_slice(&[OPTION_HOSTNAME, 5]);
+        options.extend_from_slice(b"plc-1");
+        options.extend_from_slice(&[OPTION_VENDOR_CLASS, 8]);
+        options.extend_from_slice(b"MSFT 5.0");
+        options.extend_from_slice(&[OPTION_PARAMETER_REQUEST_LIST, 3, 1, 3, 6]);
+
+        let infos = DhcpInfos::parse_v4(&dhcpv4_message(1, [0; 4], &options)).unwrap();
+
+        assert_eq!(infos.message_type, "Request");
+        assert_eq!(infos.transaction_id, 0xdeadbeef);
+        assert_eq!(infos.client_mac.as_deref(), Some("02:00:00:00:00:01"));
+        assert_eq!(infos.requested_ip.as_deref(), Some("192.168.1.20"));
+        assert_eq!(infos.offered_ip, None);
+        assert_eq!(infos.hostname.as_deref(), Some("plc-1"));
+        assert_eq!(infos.vendor_class.as_deref(), Some("MSFT 5.0"));
+        assert_eq!(infos.parameter_request_list, vec![1, 3, 6]);
+    }
+
+    #[test]
+    fn test_parse_v4_ack() {
+        let mut options = vec![OPTION_MESSAGE_TYPE, 1, 5];
+        options.extend_from_slice(&[OPTION_SERVER_IDENTIFIER, 4, 192, 168, 1, 1]);
+        options.extend_from_slice(&[OPTION_LEASE_TIME, 4, 0, 0, 0x0e, 0x10]);
+
+        let infos = DhcpInfos::parse_v4(&dhcpv4_message(2, [192, 168, 1, 20], &options)).unwrap();
+
+        assert_eq!(infos.message_type, "Ack");
+        assert_eq!(infos.offered_ip.as_deref(), Some("192.168.1.20"));
+        assert_eq!(infos.server_identifier.as_deref(), Some("192.168.1.1"));
+        assert_eq!(infos.lease_time, Some(3600));
+
+        assert!(DhcpInfos::parse_v4(&[0u8; 240]).is_none());
+    }
+
+    #[test]
+    fn test_parse_v6_relayed_reply() {
+        let mut reply = vec![7, 0x12, 0x34, 0x56];
+        // Client ID : DUID-LL Ethernet
+        reply.extend_from_slice(&[0, 1, 0, 10, 0, 3, 0, 1, 0x02, 0, 0, 0, 0, 0x02]);
+        // IA_NA avec une adresse valide 7200 s
+        let mut ia_address = vec![0, 5, 0, 24];
+        ia_address.extend_from_slice(&"2001:db8::20".parse::<Ipv6Addr>().unwrap().octets());
+        ia_address.extend_from_slice(&[0, 0, 0x0e, 0x10, 0, 0, 0x1c, 0x20]);
+        reply.extend_from_slice(&[0, 3, 0, (12 + ia_address.len()) as u8]);
+        reply.extend_from_slice(&[0; 12]);
+        reply.extend_from_slice(&ia_address);
+        // Client FQDN
+        reply.extend_from_slice(&[0, 39, 0, 11, 0, 5]);
+        reply.extend_from_slice(b"hmi-2");
+        reply.extend_from_slice(&[3]);
+        reply.extend_from_slice(b"lan");
+        reply.push(0);
+
+        let mut relay = vec![RELAY_REPLY, 0];
+        relay.extend_from_slice(&[0; 32]);
+        relay.extend_from_slice(&[0, 9, 0, reply.len() as u8]);
+        relay.extend_from_slice(&reply);
+
+        let infos = DhcpInfos::parse_v6(&relay).unwrap();
+
+        assert_eq!(infos.message_type, "Reply");
+        assert_eq!(infos.transaction_id, 0x123456);
+        assert_eq!(infos.client_mac.as_deref(), Some("02:00:00:00:00:02"));
+        assert_eq!(infos.offered_ip.as_deref(), Some("2001:db8::20"));
+        assert_eq!(infos.lease_time, Some(7200));
+        assert_eq!(infos.hostname.as_deref(), Some("hmi-2.lan"));
+    }
+
+    #[test]
+    fn test_lease_inventory() {
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::layer_4_infos::layer_7_infos::Layer7Infos;
+
+        let packet = |port_source: &str, dhcp: DhcpInfos| {
+            let mut packet = PacketInfos {
+                mac_address_source: "02:00:00:00:00:01".to_string(),
+                mac_address_destination: "ff:ff:ff:ff:ff:ff".to_string(),
+                ..Default::default()
+            };
+            packet.layer_3_infos.layer_4_infos.port_source = Some(port_source.to_string());
+            packet.layer_3_infos.layer_4_infos.layer_7_infos = Some(Layer7Infos {
+                dhcp: Some(dhcp),
+                ..Default::default()
+            });
+            (packet, 1)
+        };
+        let packets = [
+            packet(
+                "68",
+                DhcpInfos {
+                    message_type: "Request".to_string(),
+                    requested_ip: Some("192.168.1.20".to_string()),
+                    hostname: Some("plc-1".to_string()),
+                    ..Default::default()
+                },
+            ),
+            packet(
+                "67",
+                DhcpInfos {
+                    message_type: "Ack".to_string(),
+                    client_mac: Some("02:00:00:00:00:01".to_string()),
+                    offered_ip: Some("192.168.1.21".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        // Les messages de l'échange sont enregistrés un à un, avant la fusion des flux
+        let mut inventory = DhcpLeaseInventory::default();
+        for (packet, _) in &packets {
+            inventory.record(packet);
+        }
+        let leases: Vec<&DhcpLease> = inventory.leases().collect();
+
+        assert_eq!(
+            leases,
+            vec![&DhcpLease {
+                mac_address: "02:00:00:00:00:01".to_string(),
+                ip_address: Some("192.168.1.21".to_string()),
+                hostname: Some("plc-1".to_string()),
+                vendor_class: None,
+            }]
+        );
+    }
+}
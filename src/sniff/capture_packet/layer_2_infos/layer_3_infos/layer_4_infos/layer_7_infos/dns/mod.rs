@@ -0,0 +1,532 @@
+//! # Dissection DNS
+//!
+//! Ce module décode les messages DNS (RFC 1035) transportés par UDP, ou par TCP où chaque message est
+//! précédé de sa longueur et peut s'étendre sur plusieurs segments (voir [`dns_stream_parser`]).
+//!
+//! Les réponses permettent de constituer passivement une table des noms de chaque adresse IP
+//! ([`DnsNameMap`]) : enregistrements A et AAAA, en remontant les alias CNAME jusqu'au nom demandé,
+//! et résolutions inverses PTR.
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use super::super::tcp_reassembly::{ConnectionKey, StreamDirection, StreamParser};
+use super::Layer7Infos;
+use crate::sniff::capture_packet::layer_2_infos::{layer_3_infos::Layer3Infos, PacketInfos};
+
+/// Taille de l'en-tête DNS.
+const HEADER_LEN: usize = 12;
+/// Nombre maximal de pointeurs de compression suivis pour décoder un nom.
+const MAX_COMPRESSION_POINTERS: usize = 16;
+/// Nombre maximal de noms conservés par adresse IP.
+const MAX_NAMES_PER_ADDRESS: usize = 8;
+/// Nombre maximal d'adresses IP dans la table des noms.
+const MAX_ADDRESSES: usize = 65536;
+
+pub const DNS_PORT: u16 = 53;
+
+/// Informations extraites d'un message DNS.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct DnsInfos {
+    /// Identifiant du message, commun à la requête et à sa réponse.
+    pub transaction_id: u16,
+    /// Indique si le message est une réponse.
+    pub is_response: bool,
+    /// Code de retour des réponses (NoError, NXDomain, ServFail...).
+    pub response_code: Option<String>,
+    /// Questions posées.
+    pub questions: Vec<DnsQuestion>,
+    /// Enregistrements de la section réponse.
+    pub answers: Vec<DnsRecord>,
+    /// Enregistrements des sections autorité et additionnelle.
+    pub additionals: Vec<DnsRecord>,
+}
+
+/// Question d'un message DNS.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct DnsQuestion {
+    pub name: String,
+    /// Type d'enregistrement demandé (A, AAAA, PTR...).
+    pub record_type: String,
+}
+
+/// Enregistrement de ressource d'une réponse DNS.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct DnsRecord {
+    pub name: String,
+    /// Type de l'enregistrement (A, AAAA, CNAME...).
+    pub record_type: String,
+    /// Durée de validité en secondes.
+    pub ttl: u32,
+    /// Valeur de l'enregistrement : adresse, nom cible, texte...
+    pub data: String,
+}
+
+impl DnsInfos {
+    /// Décode un message DNS (charge utile d'un datagramme UDP, ou message TCP sans son préfixe de longueur).
+    pub fn parse(data: &[u8]) -> Option<DnsInfos> {
+        let header = data.get(..HEADER_LEN)?;
+        let flags = u16::from_be_bytes([header[2], header[3]]);
+        let question_count = u16::from_be_bytes([header[4], header[5]]);
+        let answer_count = u16::from_be_bytes([header[6], header[7]]);
+        let authority_count = u32::from(u16::from_be_bytes([header[8], header[9]]));
+        let additional_count = u32::from(u16::from_be_bytes([header[10], header[11]]));
+        let is_response = flags & 0x8000 != 0;
+
+        let mut infos = DnsInfos {
+            transaction_id: u16::from_be_bytes([header[0], header[1]]),
+            is_response,
+            response_code: is_response.then(|| response_code_name(flags & 0x000f)),
+            ..Default::default()
+        };
+
+        let mut offset = HEADER_LEN;
+        for _ in 0..question_count {
+            let (name, next) = read_name(data, offset)?;
+            let record_type = u16::from_be_bytes([*data.get(next)?, *data.get(next + 1)?]);
+            infos.questions.push(DnsQuestion {
+                name,
+                record_type: record_type_name(record_type),
+            });
+            offset = next + 4;
+        }
+
+        for _ in 0..answer_count {
+            let (record, next) = read_record(data, offset)?;
+            infos.answers.push(record);
+            offset = next;
+        }
+
+        // Les sections autorité et additionnelle sont décodées au mieux, jusqu'au premier enregistrement invalide
+        for _ in 0..authority_count + additional_count {
+            match read_record(data, offset) {
+                Some((record, next)) => {
+                    infos.additionals.push(record);
+                    offset = next;
+                }
+                None => break,
+            }
+        }
+
+        Some(infos)
+    }
+}
+
+/// Décode l'enregistrement de ressource qui commence à `offset`, et retourne la position qui le suit.
+fn read_record(data: &[u8], offset: usize) -> Option<(DnsRecord, usize)> {
+    let (name, next) = read_name(data, offset)?;
+    let fixed = data.get(next..next + 10)?;
+    let record_type = u16::from_be_bytes([fixed[0], fixed[1]]);
+    let ttl = u32::from_be_bytes([fixed[4], fixed[5], fixed[6], fixed[7]]);
+    let data_len = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+    let data_start = next + 10;
+    let record_data = data.get(data_start..data_start + data_len)?;
+    let record = DnsRecord {
+        name,
+        record_type: record_type_name(record_type),
+        ttl,
+        data: record_data_string(data, record_type, data_start, record_data),
+    };
+    Some((record, data_start + data_len))
+}
+
+/// Décode un nom à partir de `offset`, en suivant les pointeurs de compression.
+///
+/// Retourne le nom, en minuscules et sans point final, et la position qui suit le nom dans le message.
+pub(crate) fn read_name(data: &[u8], mut offset: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    let mut pointers = 0;
+
+    loop {
+        let len = *data.get(offset)? as usize;
+        match len {
+            0 => {
+                end.get_or_insert(offset + 1);
+                break;
+            }
+            len if len & 0xc0 == 0xc0 => {
+                pointers += 1;
+                if pointers > MAX_COMPRESSION_POINTERS {
+                    return None;
+                }
+                end.get_or_insert(offset + 2);
+                offset = ((len & 0x3f) << 8) | *data.get(offset + 1)? as usize;
+            }
+            len if len & 0xc0 == 0 => {
+                let label = data.get(offset + 1..offset + 1 + len)?;
+                labels.push(String::from_utf8_lossy(label).to_lowercase());
+                offset += 1 + len;
+            }
+            // Types d'étiquettes obsolètes
+            _ => return None,
+        }
+    }
+
+    Some((labels.join("."), end?))
+}
+
+/// Représente la valeur d'un enregistrement sous forme de texte.
+fn record_data_string(message: &[u8], record_type: u16, start: usize, data: &[u8]) -> String {
+    let name_at = |offset: usize| read_name(message, offset).map(|(name, _)| name);
+    let value = match record_type {
+        1 => <[u8; 4]>::try_from(data)
+            .ok()
+            .map(|ip| Ipv4Addr::from(ip).to_string()),
+        28 => <[u8; 16]>::try_from(data)
+            .ok()
+            .map(|ip| Ipv6Addr::from(ip).to_string()),
+        // NS, CNAME, PTR
+        2 | 5 | 12 => name_at(start),
+        // MX : préférence puis nom
+        15 => data.get(..2).and_then(|preference| {
+            name_at(start + 2).map(|name| {
+                format!(
+                    "{} {}",
+                    u16::from_be_bytes([preference[0], preference[1]]),
+                    name
+                )
+            })
+        }),
+        // SRV : priorité, poids, port puis cible
+        33 => data.get(..6).and_then(|fields| {
+            name_at(start + 6).map(|target| {
+                format!(
+                    "{} {} {} {}",
+                    u16::from_be_bytes([fields[0], fields[1]]),
+                    u16::from_be_bytes([fields[2], fields[3]]),
+                    u16::from_be_bytes([fields[4], fields[5]]),
+                    target
+                )
+            })
+        }),
+        // TXT : chaînes précédées de leur longueur
+        16 => {
+            let mut strings = Vec::new();
+            let mut rest = data;
+            while let Some((&len, tail)) = rest.split_first() {
+                let text = tail.get(..len as usize).unwrap_or(tail);
+                strings.push(String::from_utf8_lossy(text).into_owned());
+                rest = &tail[text.len()..];
+            }
+            Some(strings.join(" "))
+        }
+        _ => None,
+    };
+    value.unwrap_or_else(|| format!("{} octets", data.len()))
+}
+
+pub(crate) fn record_type_name(record_type: u16) -> String {
+    match record_type {
+        1 => "A".to_string(),
+        2 => "NS".to_string(),
+        5 => "CNAME".to_string(),
+        6 => "SOA".to_string(),
+        12 => "PTR".to_string(),
+        15 => "MX".to_string(),
+        16 => "TXT".to_string(),
+        28 => "AAAA".to_string(),
+        33 => "SRV".to_string(),
+        41 => "OPT".to_string(),
+        47 => "NSEC".to_string(),
+        64 => "SVCB".to_string(),
+        65 => "HTTPS".to_string(),
+        255 => "ANY".to_string(),
+        other => format!("TYPE{}", other),
+    }
+}
+
+fn response_code_name(response_code: u16) -> String {
+    match response_code {
+        0 => "NoError".to_string(),
+        1 => "FormErr".to_string(),
+        2 => "ServFail".to_string(),
+        3 => "NXDomain".to_string(),
+        4 => "NotImp".to_string(),
+        5 => "Refused".to_string(),
+        other => format!("RCODE{}", other),
+    }
+}
+
+/// Analyseur des messages DNS transportés par TCP, chacun précédé de sa longueur sur deux octets.
+#[derive(Debug, Default)]
+struct DnsStreamParser {
+    client_buffer: Vec<u8>,
+    server_buffer: Vec<u8>,
+}
+
+impl DnsStreamParser {
+    fn buffer(&mut self, direction: StreamDirection) -> &mut Vec<u8> {
+        match direction {
+            StreamDirection::ClientToServer => &mut self.client_buffer,
+            StreamDirection::ServerToClient => &mut self.server_buffer,
+        }
+    }
+}
+
+impl StreamParser<Layer7Infos> for DnsStreamParser {
+    fn on_data(&mut self, direction: StreamDirection, data: &[u8], output: &mut Layer7Infos) {
+        let buffer = self.buffer(direction);
+        buffer.extend_from_slice(data);
+
+        while buffer.len() >= 2 {
+            let len = u16::from_be_bytes([buffer[0], buffer[1]]) as usize;
+            if buffer.len() < 2 + len {
+                break;
+            }
+            if let Some(dns) = DnsInfos::parse(&buffer[2..2 + len]) {
+                output.protocol = Some("DNS".to_string());
+                output.dns = Some(dns);
+            }
+            buffer.drain(..2 + len);
+        }
+    }
+
+    fn on_gap(&mut self, direction: StreamDirection) {
+        // La longueur du prochain message n'est plus connue
+        self.buffer(direction).clear();
+    }
+}
+
+/// Crée l'analyseur DNS des connexions TCP vers le port 53.
+pub fn dns_stream_parser(key: &ConnectionKey) -> Option<Box<dyn StreamParser<Layer7Infos>>> {
+    if key.server.port() == DNS_PORT {
+        Some(Box::new(DnsStreamParser::default()))
+    } else {
+        None
+    }
+}
+
+/// Table des noms DNS de chaque adresse IP, apprise à partir des réponses observées.
+#[derive(Debug, Default, Clone)]
+pub struct DnsNameMap {
+    /// Noms de chaque adresse, du plus ancien au plus récent.
+    names: BTreeMap<String, Vec<String>>,
+}
+
+impl DnsNameMap {
+    /// Apprend les associations adresse → nom d'une réponse DNS.
+    pub fn learn(&mut self, dns: &DnsInfos) {
+        if !dns.is_response || dns.response_code.as_deref() != Some("NoError") {
+            return;
+        }
+
+        // Alias CNAME : nom cible → nom d'origine
+        let aliases: HashMap<&str, &str> = dns
+            .answers
+            .iter()
+            .filter(|record| record.record_type == "CNAME")
+            .map(|record| (record.data.as_str(), record.name.as_str()))
+            .collect();
+
+        for record in &dns.answers {
+            match record.record_type.as_str() {
+                "A" | "AAAA" => {
+                    let mut name = record.name.as_str();
+                    for _ in 0..MAX_COMPRESSION_POINTERS {
+                        match aliases.get(name) {
+                            Some(alias) if *alias != name => name = alias,
+                            _ => break,
+                        }
+                    }
+                    self.insert(record.data.clone(), name);
+                }
+                "PTR" => {
+                    if let Some(address) = reverse_pointer_address(&record.name) {
+                        self.insert(address.to_string(), &record.data);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn insert(&mut self, address: String, name: &str) {
+        if name.is_empty()
+            || (!self.names.contains_key(&address) && self.names.len() >= MAX_ADDRESSES)
+        {
+            return;
+        }
+        let names = self.names.entry(address).or_default();
+        names.retain(|existing| existing != name);
+        names.push(name.to_string());
+        if names.len() > MAX_NAMES_PER_ADDRESS {
+            names.remove(0);
+        }
+    }
+
+    /// Retourne le nom le plus récemment associé à une adresse IP.
+    pub fn name_of(&self, address: &str) -> Option<&str> {
+        self.names
+            .get(address)
+            .and_then(|names| names.last())
+            .map(String::as_str)
+    }
+
+    /// Renseigne les noms connus des adresses source et destination d'un paquet.
+    pub fn annotate(&self, layer_3_infos: &mut Layer3Infos) {
+        layer_3_infos.hostname_source = layer_3_infos
+            .ip_source
+            .as_deref()
+            .and_then(|ip| self.name_of(ip))
+            .map(str::to_string);
+        layer_3_infos.hostname_destination = layer_3_infos
+            .ip_destination
+            .as_deref()
+            .and_then(|ip| self.name_of(ip))
+            .map(str::to_string);
+    }
+
+    /// Retourne les adresses connues et leurs noms, par adresse.
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &Vec<String>)> {
+        self.names.iter()
+    }
+}
+
+/// Apprend les noms portés par la réponse DNS d'un paquet, puis renseigne les noms de ses adresses.
+pub fn resolve_names(names: &Mutex<DnsNameMap>, packet: &mut PacketInfos) {
+    let mut names = names.lock().expect("Failed to lock the mutex");
+    if let Some(dns) = packet
+        .layer_3_infos
+        .layer_4_infos
+        .layer_7_infos
+        .as_ref()
+        .and_then(|l| l.dns.as_ref())
+    {
+        names.learn(dns);
+    }
+    names.annotate(&mut packet.layer_3_infos);
+}
+
+/// Retrouve l'adresse d'un nom de résolution inverse (in-addr.arpa ou ip6.arpa).
+fn reverse_pointer_address(name: &str) -> Option<IpAddr> {
+    if let Some(reversed) = name.strip_suffix(".in-addr.arpa") {
+        let octets: Vec<&str> = reversed.split('.').rev().collect();
+        return octets.join(".").parse::<Ipv4Addr>().ok().map(IpAddr::V4);
+    }
+    let reversed = name.strip_suffix(".ip6.arpa")?;
+    let nibbles: Vec<&str> = reversed.split('.').rev().collect();
+    if nibbles.len() != 32 || nibbles.iter().any(|nibble| nibble.len() != 1) {
+        return None;
+    }
+    let groups: Vec<String> = nibbles.chunks(4).map(|group| group.concat()).collect();
+    groups.join(":").parse::<Ipv6Addr>().ok().map(IpAddr::V6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_name(name: &str) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for label in name.split('.') {
+            encoded.push(label.len() as u8);
+            encoded.extend_from_slice(label.as_bytes());
+        }
+        encoded.push(0);
+        encoded
+    }
+
+    /// Réponse à une requête A pour www.example.com, via un alias CNAME compressé.
+    fn cname_response() -> Vec<u8> {
+        let mut message = vec![0x12, 0x34, 0x81, 0x80, 0, 1, 0, 2, 0, 0, 0, 0];
+        message.extend(encode_name("www.example.com"));
+        message.extend_from_slice(&[0, 1, 0, 1]);
+        // www.example.com CNAME cdn.example.net (pointeur vers la question)
+        let cname_target = encode_name("cdn.example.net");
+        message.extend_from_slice(&[0xc0, 12, 0, 5, 0, 1, 0, 0, 0, 60, 0]);
+        message.push(cname_target.len() as u8);
+        let cname_offset = message.len();
+        message.extend(cname_target);
+        // cdn.example.net A 192.0.2.10
+        message.extend_from_slice(&[0xc0, cname_offset as u8, 0, 1, 0, 1, 0, 0, 1, 0, 0, 4]);
+        message.extend_from_slice(&[192, 0, 2, 10]);
+        message
+    }
+
+    #[test]
+    fn test_parse_response_with_compression() {
+        let dns = DnsInfos::parse(&cname_response()).unwrap();
+
+        assert_eq!(dns.transaction_id, 0x1234);
+        assert!(dns.is_response);
+        assert_eq!(dns.response_code.as_deref(), Some("NoError"));
+        assert_eq!(
+            dns.questions,
+            vec![DnsQuestion {
+                name: "www.example.com".to_string(),
+                record_type: "A".to_string(),
+            }]
+        );
+        assert_eq!(dns.answers[0].record_type, "CNAME");
+        assert_eq!(dns.answers[0].data, "cdn.example.net");
+        assert_eq!(dns.answers[1].name, "cdn.example.net");
+        assert_eq!(dns.answers[1].data, "192.0.2.10");
+        assert_eq!(dns.answers[1].ttl, 256);
+    }
+
+    #[test]
+    fn test_parse_rejects_pointer_loop() {
+        let mut message = vec![0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        message.extend_from_slice(&[0xc0, 12, 0, 1, 0, 1]);
+        assert!(DnsInfos::parse(&message).is_none());
+    }
+
+    #[test]
+    fn test_name_map_follows_cnames_and_ptr() {
+        let mut names = DnsNameMap::default();
+        names.learn(&DnsInfos::parse(&cname_response()).unwrap());
+        names.learn(&DnsInfos {
+            is_response: true,
+            response_code: Some("NoError".to_string()),
+            answers: vec![DnsRecord {
+                name: "20.1.168.192.in-addr.arpa".to_string(),
+                record_type: "PTR".to_string(),
+                data: "plc-1.lan".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+
+        assert_eq!(names.name_of("192.0.2.10"), Some("www.example.com"));
+        assert_eq!(names.name_of("192.168.1.20"), Some("plc-1.lan"));
+
+        let mut layer_3_infos = Layer3Infos {
+            ip_source: Some("192.168.1.20".to_string()),
+            ip_destination: Some("192.0.2.99".to_string()),
+            ..Default::default()
+        };
+        names.annotate(&mut layer_3_infos);
+        assert_eq!(layer_3_infos.hostname_source.as_deref(), Some("plc-1.lan"));
+        assert_eq!(layer_3_infos.hostname_destination, None);
+    }
+
+    #[test]
+    fn test_reverse_pointer_ipv6() {
+        let name = "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa";
+        assert_eq!(
+            reverse_pointer_address(name),
+            Some("2001:db8::1".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_stream_parser_splits_length_prefixed_messages() {
+        let message = cname_response();
+        let mut stream = (message.len() as u16).to_be_bytes().to_vec();
+        stream.extend_from_slice(&message);
+
+        let mut parser = DnsStreamParser::default();
+        let mut output = Layer7Infos::default();
+        parser.on_data(StreamDirection::ServerToClient, &stream[..10], &mut output);
+        assert!(output.dns.is_none());
+
+        parser.on_data(StreamDirection::ServerToClient, &stream[10..], &mut output);
+        assert_eq!(output.protocol.as_deref(), Some("DNS"));
+        assert_eq!(output.dns.unwrap().answers.len(), 2);
+        assert!(parser.server_buffer.is_empty());
+    }
+}
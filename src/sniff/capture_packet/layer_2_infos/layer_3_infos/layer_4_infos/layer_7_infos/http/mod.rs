@@ -0,0 +1,462 @@
+//! # Dissection HTTP/1.x
+//!
+//! Ce module décode les lignes de requête et de statut, ainsi que quelques en-têtes (Host, User-Agent,
+//! Server), des messages HTTP/1.x transportés en clair par TCP.
+//!
+//! L'analyseur est alimenté par le flux réassemblé de chaque connexion (voir [`http_stream_parser`]) : les
+//! en-têtes peuvent s'étendre sur plusieurs segments, et le corps des messages (taille annoncée par
+//! `Content-Length` ou découpage `chunked`) est sauté pour retrouver le message suivant d'une connexion
+//! persistante.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+use super::super::tcp_reassembly::{ConnectionKey, StreamDirection, StreamParser};
+use super::{Layer7Infos, HTTP_METHODS};
+
+/// Taille maximale des en-têtes d'un message.
+const MAX_HEADER_LEN: usize = 64 * 1024;
+/// Taille maximale d'une ligne de taille de bloc, pour le découpage `chunked`.
+const MAX_CHUNK_LINE_LEN: usize = 1024;
+/// Nombre maximal de requêtes en attente de réponse sur une connexion.
+const MAX_PENDING_REQUESTS: usize = 256;
+
+/// Informations extraites d'une requête ou d'une réponse HTTP/1.x.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct HttpInfos {
+    /// Méthode de la requête (GET, POST...).
+    pub method: Option<String>,
+    /// URI demandée.
+    pub uri: Option<String>,
+    /// Version du protocole (HTTP/1.0, HTTP/1.1).
+    pub version: Option<String>,
+    /// En-tête Host de la requête.
+    pub host: Option<String>,
+    /// En-tête User-Agent de la requête.
+    pub user_agent: Option<String>,
+    /// Code de statut de la réponse.
+    pub status_code: Option<u16>,
+    /// Message associé au code de statut.
+    pub reason: Option<String>,
+    /// En-tête Server de la réponse, qui identifie souvent le logiciel et sa version.
+    pub server: Option<String>,
+}
+
+impl HttpInfos {
+    /// Complète les informations d'un flux avec celles d'un nouveau message : les champs absents du
+    /// nouveau message conservent leur valeur précédente.
+    pub fn merge(&mut self, other: HttpInfos) {
+        let HttpInfos {
+            method,
+            uri,
+            version,
+            host,
+            user_agent,
+            status_code,
+            reason,
+            server,
+        } = other;
+
+        self.method = method.or(self.method.take());
+        self.uri = uri.or(self.uri.take());
+        self.version = version.or(self.version.take());
+        self.host = host.or(self.host.take());
+        self.user_agent = user_agent.or(self.user_agent.take());
+        self.status_code = status_code.or(self.status_code);
+        self.reason = reason.or(self.reason.take());
+        self.server = server.or(self.server.take());
+    }
+}
+
+/// En-têtes d'un message, décodés.
+struct MessageHead {
+    infos: HttpInfos,
+    content_length: Option<usize>,
+    chunked: bool,
+}
+
+/// Décode la ligne de départ et les en-têtes d'un message, sans la ligne vide finale.
+fn parse_head(head: &[u8], is_request: bool) -> Option<MessageHead> {
+    let head = String::from_utf8_lossy(head);
+    let mut lines = head.split("\r\n");
+    let mut start_line = lines.next()?.splitn(3, ' ');
+    let mut infos = HttpInfos::default();
+
+    if is_request {
+        infos.method = Some(start_line.next()?.to_string());
+        infos.uri = Some(start_line.next()?.to_string());
+        infos.version = Some(start_line.next()?.to_string());
+    } else {
+        infos.version = Some(start_line.next()?.to_string());
+        infos.status_code = Some(start_line.next()?.parse().ok()?);
+        infos.reason = start_line
+            .next()
+            .filter(|reason| !reason.is_empty())
+            .map(str::to_string);
+    }
+    if !infos
+        .version
+        .as_deref()
+        .is_some_and(|v| v.starts_with("HTTP/1."))
+    {
+        return None;
+    }
+
+    let mut message = MessageHead {
+        infos,
+        content_length: None,
+        chunked: false,
+    };
+    for line in lines {
+        let (name, value) = match line.split_once(':') {
+            Some((name, value)) => (name.trim().to_ascii_lowercase(), value.trim()),
+            None => continue,
+        };
+        match name.as_str() {
+            "host" => message.infos.host = Some(value.to_string()),
+            "user-agent" => message.infos.user_agent = Some(value.to_string()),
+            "server" => message.infos.server = Some(value.to_string()),
+            "content-length" => message.content_length = value.parse().ok(),
+            "transfer-encoding" => {
+                message.chunked = value.to_ascii_lowercase().contains("chunked");
+            }
+            _ => {}
+        }
+    }
+    Some(message)
+}
+
+/// Indique si des données peuvent être le début d'un message : méthode d'une requête ou version d'une réponse.
+fn looks_like_message_start(data: &[u8], is_request: bool) -> bool {
+    let starts_with = |prefix: &[u8]| {
+        let len = data.len().min(prefix.len());
+        data[..len] == prefix[..len]
+    };
+    if is_request {
+        HTTP_METHODS.iter().any(|method| starts_with(method))
+    } else {
+        starts_with(b"HTTP/1.")
+    }
+}
+
+/// Position de lecture d'un sens de la connexion.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum MessageState {
+    /// Début du flux : les premières données doivent être celles d'un message HTTP.
+    #[default]
+    Start,
+    /// En-têtes d'un message en cours de réception.
+    Headers,
+    /// Corps de taille connue, dont il reste le nombre d'octets indiqué.
+    Body(usize),
+    /// Ligne de taille du prochain bloc d'un corps `chunked`.
+    ChunkSize,
+    /// Bloc d'un corps `chunked`, dont il reste le nombre d'octets indiqué (fin de ligne comprise).
+    ChunkData(usize),
+    /// En-têtes de fin d'un corps `chunked`.
+    Trailers,
+    /// Données perdues : le flux est ignoré jusqu'au début d'un nouveau message.
+    Resync,
+    /// Flux qui n'est pas (ou plus) analysable : autre protocole, corps délimité par la fermeture...
+    Done,
+}
+
+/// Sens d'une connexion HTTP.
+#[derive(Debug, Default)]
+struct HttpHalf {
+    state: MessageState,
+    buffer: Vec<u8>,
+}
+
+/// Analyseur des messages HTTP/1.x d'une connexion TCP.
+#[derive(Debug, Default)]
+struct HttpStreamParser {
+    requests: HttpHalf,
+    responses: HttpHalf,
+    /// Méthodes des requêtes dont la réponse n'a pas encore été reçue (les réponses à HEAD n'ont pas de corps).
+    pending_methods: VecDeque<String>,
+}
+
+impl HttpStreamParser {
+    /// Consomme les données d'un sens de la connexion, et retourne les informations du dernier message
+    /// dont les en-têtes ont été reçus.
+    fn process(&mut self, is_request: bool) -> Option<HttpInfos> {
+        let half = match is_request {
+            true => &mut self.requests,
+            false => &mut self.responses,
+        };
+        let mut infos = None;
+        let mut position = 0;
+
+        while position < half.buffer.len() {
+            let data = &half.buffer[position..];
+            match half.state {
+                MessageState::Start | MessageState::Resync => {
+                    if looks_like_message_start(data, is_request) {
+                        half.state = MessageState::Headers;
+                    } else if half.state == MessageState::Start {
+                        half.state = MessageState::Done;
+                    } else {
+                        position = half.buffer.len();
+                    }
+                }
+                MessageState::Headers => {
+                    let end = match data.windows(4).position(|w| w == b"\r\n\r\n") {
+                        Some(end) => end,
+                        None => {
+                            if data.len() > MAX_HEADER_LEN {
+                                half.state = MessageState::Done;
+                            }
+                            break;
+                        }
+                    };
+                    let head = match parse_head(&data[..end], is_request) {
+                        Some(head) => head,
+                        None => {
+                            half.state = MessageState::Done;
+                            break;
+                        }
+                    };
+                    position += end + 4;
+
+                    half.state = if is_request {
+                        self.pending_methods
+                            .push_back(head.infos.method.clone().unwrap_or_default());
+                        if self.pending_methods.len() > MAX_PENDING_REQUESTS {
+                            self.pending_methods.pop_front();
+                        }
+                        match (head.chunked, head.content_length) {
+                            (true, _) => MessageState::ChunkSize,
+                            (false, Some(len)) if len > 0 => MessageState::Body(len),
+                            _ => MessageState::Headers,
+                        }
+                    } else {
+                        let status = head.infos.status_code.unwrap_or_default();
+                        // Les réponses intermédiaires (100 Continue...) précèdent la réponse définitive
+                        let method = match status {
+                            100..=199 => None,
+                            _ => self.pending_methods.pop_front(),
+                        };
+                        match (method.as_deref(), status, head.chunked, head.content_length) {
+                            (Some("HEAD"), ..) | (_, 100..=199 | 204 | 304, ..) => {
+                                MessageState::Headers
+                            }
+                            // Tunnel établi : le flux ne contient plus de messages HTTP
+                            (Some("CONNECT"), 200..=299, ..) => MessageState::Done,
+                            (_, _, true, _) => MessageState::ChunkSize,
+                            (_, _, false, Some(0)) => MessageState::Headers,
+                            (_, _, false, Some(len)) => MessageState::Body(len),
+                            // Corps délimité par la fermeture de la connexion
+                            (_, _, false, None) => MessageState::Done,
+                        }
+                    };
+                    infos = Some(head.infos);
+                }
+                MessageState::Body(remaining) => {
+                    let len = remaining.min(data.len());
+                    position += len;
+                    half.state = match remaining - len {
+                        0 => MessageState::Headers,
+                        remaining => MessageState::Body(remaining),
+                    };
+                }
+                MessageState::ChunkSize => {
+                    let end = match data.windows(2).position(|w| w == b"\r\n") {
+                        Some(end) => end,
+                        None => {
+                            if data.len() > MAX_CHUNK_LINE_LEN {
+                                half.state = MessageState::Done;
+                            }
+                            break;
+                        }
+                    };
+                    let line = String::from_utf8_lossy(&data[..end]);
+                    let size = line.split(';').next().unwrap_or_default().trim();
+                    half.state = match usize::from_str_radix(size, 16) {
+                        Ok(0) => MessageState::Trailers,
+                        Ok(size) => MessageState::ChunkData(size.saturating_add(2)),
+                        Err(_) => MessageState::Done,
+                    };
+                    position += end + 2;
+                }
+                MessageState::ChunkData(remaining) => {
+                    let len = remaining.min(data.len());
+                    position += len;
+                    half.state = match remaining - len {
+                        0 => MessageState::ChunkSize,
+                        remaining => MessageState::ChunkData(remaining),
+                    };
+                }
+                MessageState::Trailers => {
+                    let end = match data.windows(2).position(|w| w == b"\r\n") {
+                        Some(end) => end,
+                        None => {
+                            if data.len() > MAX_HEADER_LEN {
+                                half.state = MessageState::Done;
+                            }
+                            break;
+                        }
+                    };
+                    if end == 0 {
+                        half.state = MessageState::Headers;
+                    }
+                    position += end + 2;
+                }
+                MessageState::Done => break,
+            }
+        }
+
+        if half.state == MessageState::Done {
+            half.buffer = Vec::new();
+        } else {
+            half.buffer.drain(..position);
+        }
+        infos
+    }
+}
+
+impl StreamParser<Layer7Infos> for HttpStreamParser {
+    fn on_data(&mut self, direction: StreamDirection, data: &[u8], output: &mut Layer7Infos) {
+        let is_request = direction == StreamDirection::ClientToServer;
+        let half = match is_request {
+            true => &mut self.requests,
+            false => &mut self.responses,
+        };
+        if half.state == MessageState::Done {
+            return;
+        }
+        half.buffer.extend_from_slice(data);
+
+        if let Some(http) = self.process(is_request) {
+            output.protocol = Some("HTTP".to_string());
+            output.http = Some(http);
+        }
+    }
+
+    fn on_gap(&mut self, direction: StreamDirection) {
+        let half = match direction {
+            StreamDirection::ClientToServer => &mut self.requests,
+            StreamDirection::ServerToClient => &mut self.responses,
+        };
+        if half.state != MessageState::Done {
+            half.state = MessageState::Resync;
+            half.buffer.clear();
+        }
+        // L'association des réponses aux requêtes n'est plus fiable
+        self.pending_methods.clear();
+    }
+
+    fn wants_more(&self) -> bool {
+        self.requests.state != MessageState::Done || self.responses.state != MessageState::Done
+    }
+}
+
+/// Crée l'analyseur HTTP d'une connexion TCP.
+///
+/// HTTP est souvent servi sur des ports non standard (interfaces d'administration, IHM) : toutes les connexions
+/// sont analysées, et l'analyseur se désactive dès que les premières données ne sont pas celles d'un message HTTP.
+pub fn http_stream_parser(_key: &ConnectionKey) -> Option<Box<dyn StreamParser<Layer7Infos>>> {
+    Some(Box::new(HttpStreamParser::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_split_across_segments() {
+        let mut parser = HttpStreamParser::default();
+        let mut output = Layer7Infos::default();
+
+        parser.on_data(
+            StreamDirection::ClientToServer,
+            b"GET /index.html HTTP/1.1\r\nHost: hmi.lan\r\nUser-",
+            &mut output,
+        );
+        assert!(output.http.is_none());
+
+        parser.on_data(
+            StreamDirection::ClientToServer,
+            b"Agent: curl/8.0\r\n\r\n",
+            &mut output,
+        );
+        let http = output.http.unwrap();
+        assert_eq!(http.method.as_deref(), Some("GET"));
+        assert_eq!(http.uri.as_deref(), Some("/index.html"));
+        assert_eq!(http.host.as_deref(), Some("hmi.lan"));
+        assert_eq!(http.user_agent.as_deref(), Some("curl/8.0"));
+        assert_eq!(output.protocol.as_deref(), Some("HTTP"));
+    }
+
+    #[test]
+    fn test_responses_skip_bodies() {
+        let mut parser = HttpStreamParser::default();
+        let mut output = Layer7Infos::default();
+        parser.on_data(
+            StreamDirection::ClientToServer,
+            b"GET / HTTP/1.1\r\n\r\nHEAD / HTTP/1.1\r\n\r\nGET /data HTTP/1.1\r\n\r\n",
+            &mut output,
+        );
+
+        // Corps de taille connue, puis réponse à HEAD sans corps malgré Content-Length
+        let mut output = Layer7Infos::default();
+        parser.on_data(
+            StreamDirection::ServerToClient,
+            b"HTTP/1.1 200 OK\r\nServer: lighttpd/1.4.59\r\nContent-Length: 5\r\n\r\nhello\
+              HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n",
+            &mut output,
+        );
+        assert_eq!(output.http.unwrap().status_code, Some(200));
+        assert_eq!(parser.responses.state, MessageState::Headers);
+
+        // Corps découpé en blocs
+        let mut output = Layer7Infos::default();
+        parser.on_data(
+            StreamDirection::ServerToClient,
+            b"HTTP/1.1 404 Not Found\r\nServer: Boa/0.94\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nnone\r\n0\r\n\r\n",
+            &mut output,
+        );
+        let http = output.http.unwrap();
+        assert_eq!(http.status_code, Some(404));
+        assert_eq!(http.reason.as_deref(), Some("Not Found"));
+        assert_eq!(http.server.as_deref(), Some("Boa/0.94"));
+        assert_eq!(parser.responses.state, MessageState::Headers);
+        assert!(parser.responses.buffer.is_empty());
+    }
+
+    #[test]
+    fn test_other_protocol_disables_parser() {
+        let mut parser = HttpStreamParser::default();
+        let mut output = Layer7Infos::default();
+        parser.on_data(
+            StreamDirection::ClientToServer,
+            &[0x16, 0x03, 0x01, 0x00, 0x05],
+            &mut output,
+        );
+        parser.on_data(
+            StreamDirection::ServerToClient,
+            b"SSH-2.0-OpenSSH\r\n",
+            &mut output,
+        );
+
+        assert!(output.http.is_none());
+        assert!(!parser.wants_more());
+    }
+
+    #[test]
+    fn test_merge_keeps_server() {
+        let mut http = HttpInfos {
+            status_code: Some(200),
+            server: Some("nginx/1.18.0".to_string()),
+            ..Default::default()
+        };
+        http.merge(HttpInfos {
+            status_code: Some(304),
+            ..Default::default()
+        });
+
+        assert_eq!(http.status_code, Some(304));
+        assert_eq!(http.server.as_deref(), Some("nginx/1.18.0"));
+    }
+}
@@ -0,0 +1,370 @@
+//! # Découverte des noms locaux
+//!
+//! Les équipements d'un réseau local annoncent leur nom sans passer par un serveur DNS :
+//!
+//! - mDNS (RFC 6762, port 5353) : nom d'hôte `.local` et services DNS-SD (`_ipp._tcp`, `_airplay._tcp`...) ;
+//! - LLMNR (RFC 4795, port 5355) : réponses aux résolutions de noms de postes Windows ;
+//! - NBNS (RFC 1002, port 137) : enregistrement des noms NetBIOS et des groupes de travail.
+//!
+//! Seuls les messages émis par l'équipement qui porte le nom (réponses, annonces, enregistrements) sont
+//! retenus, afin que les noms soient attribués à l'adresse MAC et IP source du paquet
+//! (voir [`LocalNameInventory`]).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use super::dns::{read_name, DnsInfos};
+use crate::sniff::capture_packet::layer_2_infos::PacketInfos;
+
+/// Type d'enregistrement NBNS portant l'adresse d'un nom.
+const NBNS_NB: u16 = 0x0020;
+/// Type d'enregistrement NBNS de la réponse d'état d'un nœud (liste de ses noms).
+const NBNS_NBSTAT: u16 = 0x0021;
+/// Drapeau des noms de groupe NetBIOS.
+const NBNS_GROUP_FLAG: u16 = 0x8000;
+
+/// Noms, services et groupes de travail annoncés par un équipement.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct LocalNameInfos {
+    /// Noms d'hôte (mDNS sans le suffixe `.local`, LLMNR, NetBIOS).
+    pub hostnames: BTreeSet<String>,
+    /// Types de services DNS-SD annoncés (`_ipp._tcp`...).
+    pub services: BTreeSet<String>,
+    /// Groupes de travail ou domaines NetBIOS.
+    pub workgroups: BTreeSet<String>,
+}
+
+impl LocalNameInfos {
+    /// Ajoute les noms, services et groupes de travail d'une autre annonce.
+    pub fn merge(&mut self, other: LocalNameInfos) {
+        self.hostnames.extend(other.hostnames);
+        self.services.extend(other.services);
+        self.workgroups.extend(other.workgroups);
+    }
+
+    fn non_empty(self) -> Option<LocalNameInfos> {
+        if self == LocalNameInfos::default() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    /// Extrait les noms et services d'une réponse ou d'une annonce mDNS.
+    pub fn from_mdns(dns: &DnsInfos) -> Option<LocalNameInfos> {
+        if !dns.is_response {
+            return None;
+        }
+
+        let mut infos = LocalNameInfos::default();
+        for record in dns.answers.iter().chain(&dns.additionals) {
+            match record.record_type.as_str() {
+                "A" | "AAAA" => {
+                    infos.hostnames.insert(local_hostname(&record.name));
+                }
+                // Énumération des types de services
+                "PTR" if record.name == "_services._dns-sd._udp.local" => {
+                    infos.services.extend(service_type(&record.data));
+                }
+                "PTR" => {
+                    infos.services.extend(service_type(&record.name));
+                }
+                "SRV" => {
+                    infos.services.extend(service_type(&record.name));
+                    // Priorité, poids, port puis nom d'hôte cible
+                    if let Some(target) = record.data.split(' ').nth(3) {
+                        infos.hostnames.insert(local_hostname(target));
+                    }
+                }
+                _ => {}
+            }
+        }
+        infos.hostnames.retain(|hostname| !hostname.is_empty());
+        infos.non_empty()
+    }
+
+    /// Extrait le nom d'hôte d'une réponse LLMNR.
+    pub fn from_llmnr(dns: &DnsInfos) -> Option<LocalNameInfos> {
+        if !dns.is_response || dns.response_code.as_deref() != Some("NoError") {
+            return None;
+        }
+
+        let mut infos = LocalNameInfos::default();
+        for record in &dns.answers {
+            if matches!(record.record_type.as_str(), "A" | "AAAA") && !record.name.is_empty() {
+                infos.hostnames.insert(record.name.clone());
+            }
+        }
+        infos.non_empty()
+    }
+
+    /// Décode un message NBNS et extrait les noms NetBIOS enregistrés ou annoncés par son émetteur.
+    ///
+    /// Sont retenus les enregistrements et rafraîchissements de noms, les réponses positives aux requêtes
+    /// de noms et les réponses d'état de nœud.
+    pub fn parse_nbns(data: &[u8]) -> Option<LocalNameInfos> {
+        let header = data.get(..12)?;
+        let flags = u16::from_be_bytes([header[2], header[3]]);
+        let is_response = flags & 0x8000 != 0;
+        let opcode = (flags >> 11) & 0x0f;
+        let response_code = flags & 0x000f;
+        let count = |i: usize| u16::from_be_bytes([header[i], header[i + 1]]) as usize;
+
+        // Requête (0) avec réponse positive, enregistrement (5) ou rafraîchissement (8, 9)
+        let announces = match opcode {
+            0 => is_response && response_code == 0,
+            5 | 8 | 9 => !is_response,
+            _ => false,
+        };
+        if !announces {
+            return None;
+        }
+
+        let mut offset = 12;
+        for _ in 0..count(4) {
+            let (_, next) = read_name(data, offset)?;
+            offset = next + 4;
+        }
+
+        let mut infos = LocalNameInfos::default();
+        for _ in 0..count(6) + count(8) + count(10) {
+            let (name, next) = read_name(data, offset)?;
+            let fixed = data.get(next..next + 10)?;
+            let record_type = u16::from_be_bytes([fixed[0], fixed[1]]);
+            let data_len = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+            let record_data = data.get(next + 10..next + 10 + data_len)?;
+            offset = next + 10 + data_len;
+
+            match record_type {
+                NBNS_NB => {
+                    if let (Some((name, suffix)), Some(nb_flags)) =
+                        (decode_netbios_name(&name), record_data.get(..2))
+                    {
+                        let nb_flags = u16::from_be_bytes([nb_flags[0], nb_flags[1]]);
+                        infos.add_netbios_name(name, suffix, nb_flags);
+                    }
+                }
+                NBNS_NBSTAT => {
+                    let name_count = *record_data.first()? as usize;
+                    for entry in record_data[1..].chunks_exact(18).take(name_count) {
+                        let name = netbios_name_string(&entry[..15]);
+                        let nb_flags = u16::from_be_bytes([entry[16], entry[17]]);
+                        infos.add_netbios_name(name, entry[15], nb_flags);
+                    }
+                }
+                _ => {}
+            }
+        }
+        infos.non_empty()
+    }
+
+    /// Classe un nom NetBIOS selon son suffixe : nom de poste (service Workstation ou Server)
+    /// ou groupe de travail.
+    fn add_netbios_name(&mut self, name: String, suffix: u8, nb_flags: u16) {
+        if name.is_empty() || name == "*" {
+            return;
+        }
+        let is_group = nb_flags & NBNS_GROUP_FLAG != 0;
+        match (is_group, suffix) {
+            (false, 0x00 | 0x20) => {
+                self.hostnames.insert(name);
+            }
+            (true, 0x00 | 0x1e) | (false, 0x1b | 0x1d) => {
+                self.workgroups.insert(name);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Retire le suffixe `.local` d'un nom mDNS.
+fn local_hostname(name: &str) -> String {
+    name.strip_suffix(".local").unwrap_or(name).to_string()
+}
+
+/// Retrouve le type de service DNS-SD (`_ipp._tcp`) d'un nom d'instance ou de type de service.
+fn service_type(name: &str) -> Option<String> {
+    let labels: Vec<&str> = name.split('.').collect();
+    labels.windows(2).find_map(|pair| {
+        (pair[0].starts_with('_') && matches!(pair[1], "_tcp" | "_udp"))
+            .then(|| format!("{}.{}", pair[0], pair[1]))
+    })
+}
+
+/// Décode un nom NetBIOS encodé (RFC 1001, « first level encoding ») : chaque octet du nom est représenté
+/// par deux lettres de `A` à `P`.
+///
+/// Retourne le nom, sans les espaces de remplissage, et son suffixe (type de service).
+fn decode_netbios_name(encoded: &str) -> Option<(String, u8)> {
+    let label = encoded.split('.').next()?.as_bytes();
+    if label.len() != 32 {
+        return None;
+    }
+    let mut bytes = [0u8; 16];
+    for (byte, pair) in bytes.iter_mut().zip(label.chunks_exact(2)) {
+        let high = pair[0].to_ascii_lowercase().checked_sub(b'a')?;
+        let low = pair[1].to_ascii_lowercase().checked_sub(b'a')?;
+        if high > 0x0f || low > 0x0f {
+            return None;
+        }
+        *byte = (high << 4) | low;
+    }
+    Some((netbios_name_string(&bytes[..15]), bytes[15]))
+}
+
+fn netbios_name_string(name: &[u8]) -> String {
+    String::from_utf8_lossy(name)
+        .trim_end_matches([' ', '\0'])
+        .to_string()
+}
+
+/// Noms découverts pour un équipement du réseau local.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LocalHost {
+    /// Adresse MAC de l'équipement.
+    pub mac_address: String,
+    /// Adresses IP depuis lesquelles l'équipement a annoncé ses noms.
+    pub ip_addresses: BTreeSet<String>,
+    /// Noms, services et groupes de travail annoncés.
+    pub names: LocalNameInfos,
+}
+
+/// Inventaire des noms locaux, indexé par adresse MAC de l'équipement qui les annonce.
+#[derive(Debug, Default)]
+pub struct LocalNameInventory {
+    hosts: BTreeMap<String, LocalHost>,
+}
+
+impl LocalNameInventory {
+    /// Construit l'inventaire à partir des flux capturés.
+    pub fn from_packets(packets: &[(PacketInfos, u32)]) -> LocalNameInventory {
+        let mut inventory = LocalNameInventory::default();
+        for (packet, _) in packets {
+            inventory.record(packet);
+        }
+        inventory
+    }
+
+    /// Complète l'inventaire avec les noms annoncés par un paquet, s'il en porte.
+    pub fn record(&mut self, packet: &PacketInfos) {
+        let names = match packet
+            .layer_3_infos
+            .layer_4_infos
+            .layer_7_infos
+            .as_ref()
+            .and_then(|l| l.local_names.as_ref())
+        {
+            Some(names) => names,
+            None => return,
+        };
+
+        let host = self
+            .hosts
+            .entry(packet.mac_address_source.clone())
+            .or_insert_with(|| LocalHost {
+                mac_address: packet.mac_address_source.clone(),
+                ..Default::default()
+            });
+        host.ip_addresses
+            .extend(packet.layer_3_infos.ip_source.clone());
+        host.names.merge(names.clone());
+    }
+
+    /// Retourne les équipements connus, par adresse MAC.
+    pub fn hosts(&self) -> impl Iterator<Item = &LocalHost> {
+        self.hosts.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::layer_4_infos::layer_7_infos::dns::DnsRecord;
+
+    fn record(name: &str, record_type: &str, data: &str) -> DnsRecord {
+        DnsRecord {
+            name: name.to_string(),
+            record_type: record_type.to_string(),
+            data: data.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn encode_netbios_name(name: &str, suffix: u8) -> Vec<u8> {
+        let mut bytes = format!("{:<15}", name).into_bytes();
+        bytes.push(suffix);
+        let mut encoded = vec![32];
+        for byte in bytes {
+            encoded.push(b'A' + (byte >> 4));
+            encoded.push(b'A' + (byte & 0x0f));
+        }
+        encoded.push(0);
+        encoded
+    }
+
+    #[test]
+    fn test_from_mdns_announcement() {
+        let dns = DnsInfos {
+            is_response: true,
+            response_code: Some("NoError".to_string()),
+            answers: vec![
+                record("_services._dns-sd._udp.local", "PTR", "_ipp._tcp.local"),
+                record("_ipp._tcp.local", "PTR", "Printer._ipp._tcp.local"),
+            ],
+            additionals: vec![
+                record("Printer._ipp._tcp.local", "SRV", "0 0 631 printer-1.local"),
+                record("printer-1.local", "A", "192.168.1.30"),
+            ],
+            ..Default::default()
+        };
+
+        let infos = LocalNameInfos::from_mdns(&dns).unwrap();
+        assert_eq!(infos.hostnames, BTreeSet::from(["printer-1".to_string()]));
+        assert_eq!(infos.services, BTreeSet::from(["_ipp._tcp".to_string()]));
+
+        let query = DnsInfos {
+            answers: dns.answers.clone(),
+            ..Default::default()
+        };
+        assert!(LocalNameInfos::from_mdns(&query).is_none());
+    }
+
+    #[test]
+    fn test_parse_nbns_registration() {
+        // Enregistrement du groupe de travail WORKGROUP (opcode 5, drapeau groupe)
+        let mut message = vec![0x00, 0x01, 0x29, 0x10, 0, 1, 0, 0, 0, 0, 0, 1];
+        message.extend(encode_netbios_name("WORKGROUP", 0x00));
+        message.extend_from_slice(&[0x00, 0x20, 0x00, 0x01]);
+        message.extend_from_slice(&[0xc0, 12, 0x00, 0x20, 0x00, 0x01, 0, 0, 0x0e, 0x10, 0, 6]);
+        message.extend_from_slice(&[0x80, 0x00, 192, 168, 1, 40]);
+
+        let infos = LocalNameInfos::parse_nbns(&message).unwrap();
+        assert_eq!(infos.workgroups, BTreeSet::from(["WORKGROUP".to_string()]));
+        assert!(infos.hostnames.is_empty());
+    }
+
+    #[test]
+    fn test_parse_nbns_node_status() {
+        let mut message = vec![0x00, 0x02, 0x84, 0x00, 0, 0, 0, 1, 0, 0, 0, 0];
+        message.extend(encode_netbios_name("*", 0x00));
+        let mut names = vec![3];
+        for (name, suffix, flags) in [
+            ("DESKTOP-1", 0x00u8, 0x0400u16),
+            ("DESKTOP-1", 0x20, 0x0400),
+            ("CORP", 0x00, 0x8400),
+        ] {
+            names.extend(format!("{:<15}", name).into_bytes());
+            names.push(suffix);
+            names.extend_from_slice(&flags.to_be_bytes());
+        }
+        names.extend_from_slice(&[0x02, 0, 0, 0, 0, 0x40]);
+        message.extend_from_slice(&[0x00, 0x21, 0x00, 0x01, 0, 0, 0, 0]);
+        message.extend_from_slice(&(names.len() as u16).to_be_bytes());
+        message.extend(names);
+
+        let infos = LocalNameInfos::parse_nbns(&message).unwrap();
+        assert_eq!(infos.hostnames, BTreeSet::from(["DESKTOP-1".to_string()]));
+        assert_eq!(infos.workgroups, BTreeSet::from(["CORP".to_string()]));
+    }
+}
@@ -1,17 +1,286 @@
-use pnet::packet::Packet;
-use pnet::packet::dhcp::DhcpPacket;
+//! # Dissection de la couche 7
+//!
+//! Ce module identifie le protocole applicatif transporté par les segments TCP et les datagrammes UDP.
+//!
+//! Le protocole des segments TCP est d'abord reconnu à la signature de la charge utile (ligne de requête HTTP,
+//! en-tête d'enregistrement TLS, bannière SSH...), puis à défaut aux ports bien connus. Celui des datagrammes
+//! UDP n'est reconnu qu'aux ports.
+//!
+//! Le message des protocoles pris en charge est ensuite décodé lorsqu'il est contenu dans un datagramme UDP
+//! (DHCP, DHCPv6, DNS, ainsi que mDNS, LLMNR et NBNS pour les noms annoncés par les équipements locaux).
+//!
+//! Les protocoles dont les messages s'étendent sur plusieurs segments TCP sont analysés à partir du flux
+//! réassemblé, par les analyseurs de [`STREAM_PARSERS`] (DNS sur TCP, HTTP/1.x, handshakes TLS).
 
-#[derive(Default,Debug)]
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use serde::Serialize;
+
+use super::tcp_reassembly::ParserFactory;
+
+pub(crate) mod dhcp;
+use dhcp::DhcpInfos;
+pub(crate) mod dns;
+use dns::{dns_stream_parser, DnsInfos};
+pub(crate) mod http;
+use http::{http_stream_parser, HttpInfos};
+pub(crate) mod local_names;
+use local_names::LocalNameInfos;
+pub(crate) mod tls;
+use tls::{certificate::CertificateInfos, tls_stream_parser, TlsInfos};
+
+/// Analyseurs applicatifs alimentés par le flux réassemblé des connexions TCP.
+pub const STREAM_PARSERS: &[ParserFactory<Layer7Infos>] =
+    &[dns_stream_parser, http_stream_parser, tls_stream_parser];
+
+/// Informations extraites de la couche 7 d'un paquet.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
 pub struct Layer7Infos {
-    proto: Option<String>
+    /// Protocole applicatif détecté (HTTP, TLS, DNS...).
+    pub protocol: Option<String>,
+    /// Message DHCP ou DHCPv6.
+    pub dhcp: Option<DhcpInfos>,
+    /// Message DNS (requête ou réponse).
+    pub dns: Option<DnsInfos>,
+    /// Noms, services et groupes de travail annoncés par mDNS, LLMNR ou NBNS.
+    pub local_names: Option<LocalNameInfos>,
+    /// Ligne de requête ou de statut et en-têtes d'un message HTTP/1.x.
+    pub http: Option<HttpInfos>,
+    /// SNI, ALPN, version, suites de chiffrement et empreintes d'un ClientHello ou d'un ServerHello TLS.
+    pub tls: Option<TlsInfos>,
+    /// Chaîne de certificats présentée par un serveur TLS 1.2 ou antérieur.
+    pub certificates: Vec<CertificateInfos>,
+}
+
+impl Layer7Infos {
+    /// Complète les informations d'un flux avec celles d'un nouveau paquet.
+    ///
+    /// Seuls certains paquets d'un flux portent des informations applicatives (début de requête,
+    /// handshake...) : les informations déjà connues sont conservées si le nouveau paquet n'en apporte pas.
+    pub fn merge(&mut self, other: Layer7Infos) {
+        let Layer7Infos {
+            protocol,
+            dhcp,
+            dns,
+            local_names,
+            http,
+            tls,
+            certificates,
+        } = other;
+
+        if protocol.is_some() {
+            self.protocol = protocol;
+        }
+        if dhcp.is_some() {
+            self.dhcp = dhcp;
+        }
+        if dns.is_some() {
+            self.dns = dns;
+        }
+        // Les annonces successives d'un équipement se complètent
+        match (&mut self.local_names, local_names) {
+            (Some(known), Some(local_names)) => known.merge(local_names),
+            (known, local_names @ Some(_)) => *known = local_names,
+            (_, None) => {}
+        }
+        if tls.is_some() {
+            self.tls = tls;
+        }
+        if !certificates.is_empty() {
+            self.certificates = certificates;
+        }
+        match (&mut self.http, http) {
+            (Some(known), Some(http)) => known.merge(http),
+            (known, http @ Some(_)) => *known = http,
+            (_, None) => {}
+        }
+    }
+
+    /// Indique si aucune information applicative n'a été extraite.
+    pub fn is_empty(&self) -> bool {
+        *self == Layer7Infos::default()
+    }
+}
+
+/// Méthodes des requêtes HTTP/1.x.
+const HTTP_METHODS: [&[u8]; 9] = [
+    b"GET ",
+    b"POST ",
+    b"PUT ",
+    b"DELETE ",
+    b"HEAD ",
+    b"OPTIONS ",
+    b"PATCH ",
+    b"CONNECT ",
+    b"TRACE ",
+];
+
+/// Reconnaît le protocole applicatif à la signature du début de la charge utile.
+fn protocol_from_payload(payload: &[u8]) -> Option<&'static str> {
+    if HTTP_METHODS
+        .iter()
+        .any(|method| payload.starts_with(method))
+        || payload.starts_with(b"HTTP/1.")
+    {
+        return Some("HTTP");
+    }
+    if payload.starts_with(b"SSH-") {
+        return Some("SSH");
+    }
+    // En-tête d'enregistrement TLS : type (ChangeCipherSpec, Alert, Handshake, ApplicationData), version 3.x
+    if payload.len() >= 5 && (0x14..=0x17).contains(&payload[0]) && payload[1] == 0x03 {
+        return Some("TLS");
+    }
+    None
 }
 
-trait PacketPyload {
-    fn proto(&self) -> Layer7Infos;
+/// Reconnaît le protocole applicatif à un port bien connu.
+fn protocol_from_port(port: u16) -> Option<&'static str> {
+    let protocol = match port {
+        20 | 21 => "FTP",
+        22 => "SSH",
+        23 => "Telnet",
+        25 | 587 => "SMTP",
+        53 => "DNS",
+        67 | 68 => "DHCP",
+        69 => "TFTP",
+        80 | 8080 => "HTTP",
+        102 => "S7comm",
+        123 => "NTP",
+        137 => "NBNS",
+        161 | 162 => "SNMP",
+        389 => "LDAP",
+        443 | 8443 => "TLS",
+        445 => "SMB",
+        502 => "Modbus",
+        514 => "Syslog",
+        546 | 547 => "DHCPv6",
+        1883 => "MQTT",
+        3389 => "RDP",
+        4840 => "OPC UA",
+        5353 => "mDNS",
+        5355 => "LLMNR",
+        20000 => "DNP3",
+        44818 => "EtherNet/IP",
+        47808 => "BACnet",
+        _ => return None,
+    };
+    Some(protocol)
 }
 
-pub fn get_layer_7_infos(data: &[u8]) -> Layer7Infos {
-    Layer7Infos {
-        proto: None,
+/// Identifie le protocole applicatif d'un segment TCP ou d'un datagramme UDP.
+///
+/// # Arguments
+///
+/// * `port_source` - Le port source du paquet.
+/// * `port_destination` - Le port de destination du paquet.
+/// * `payload` - Les données transportées par le segment ou le datagramme.
+/// * `transport` - Le protocole de transport : seuls les datagrammes UDP contiennent un message complet,
+///   les messages transportés par TCP sont décodés à partir du flux réassemblé.
+///
+/// Retourne `None` si aucun protocole n'a été reconnu.
+pub fn get_layer_7_infos(
+    port_source: u16,
+    port_destination: u16,
+    payload: &[u8],
+    transport: IpNextHeaderProtocol,
+) -> Option<Layer7Infos> {
+    let datagram = transport == IpNextHeaderProtocols::Udp;
+    // Les signatures reconnues sont celles de protocoles transportés par TCP : appliquées aux datagrammes UDP,
+    // elles masqueraient par exemple les messages DNS dont l'identifiant commence par 0x1603
+    let signature = match transport {
+        IpNextHeaderProtocols::Tcp => protocol_from_payload(payload),
+        _ => None,
+    };
+    let protocol = signature
+        .or_else(|| protocol_from_port(port_destination))
+        .or_else(|| protocol_from_port(port_source))?;
+
+    Some(Layer7Infos {
+        protocol: Some(protocol.to_string()),
+        dhcp: match protocol {
+            "DHCP" if datagram => DhcpInfos::parse_v4(payload),
+            "DHCPv6" if datagram => DhcpInfos::parse_v6(payload),
+            _ => None,
+        },
+        dns: match protocol {
+            "DNS" if datagram => DnsInfos::parse(payload),
+            _ => None,
+        },
+        local_names: match protocol {
+            "mDNS" if datagram => {
+                DnsInfos::parse(payload).and_then(|dns| LocalNameInfos::from_mdns(&dns))
+            }
+            "LLMNR" if datagram => {
+                DnsInfos::parse(payload).and_then(|dns| LocalNameInfos::from_llmnr(&dns))
+            }
+            "NBNS" if datagram => LocalNameInfos::parse_nbns(payload),
+            _ => None,
+        },
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_layer_7_infos_from_payload() {
+        let http = get_layer_7_infos(
+            50000,
+            8000,
+            b"GET / HTTP/1.1\r\n",
+            IpNextHeaderProtocols::Tcp,
+        )
+        .unwrap();
+        assert_eq!(http.protocol.as_deref(), Some("HTTP"));
+
+        let tls = get_layer_7_infos(
+            50000,
+            4443,
+            &[0x16, 0x03, 0x01, 0x00, 0x05],
+            IpNextHeaderProtocols::Tcp,
+        )
+        .unwrap();
+        assert_eq!(tls.protocol.as_deref(), Some("TLS"));
+    }
+
+    #[test]
+    fn test_get_layer_7_infos_from_port() {
+        let dns = get_layer_7_infos(53, 40000, &[0x12, 0x34], IpNextHeaderProtocols::Udp).unwrap();
+        assert_eq!(dns.protocol.as_deref(), Some("DNS"));
+
+        assert!(get_layer_7_infos(40000, 40001, b"data", IpNextHeaderProtocols::Udp).is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_udp_datagram_ignores_tcp_signatures() {
+        // Requête DNS dont l'identifiant 0x1603 ressemble à un en-tête d'enregistrement TLS
+        let mut query = vec![0x16, 0x03, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+        query.extend_from_slice(b"\x03plc\x07example\x00");
+        query.extend_from_slice(&[0, 1, 0, 1]);
+
+        let infos = get_layer_7_infos(40000, 53, &query, IpNextHeaderProtocols::Udp).unwrap();
+        assert_eq!(infos.protocol.as_deref(), Some("DNS"));
+        let dns = infos.dns.unwrap();
+        assert_eq!(dns.transaction_id, 0x1603);
+        assert_eq!(dns.questions[0].name, "plc.example");
+    }
+
+    #[test]
+    fn test_merge_keeps_known_protocol() {
+        let mut infos = Layer7Infos {
+            protocol: Some("HTTP".to_string()),
+            ..Default::default()
+        };
+
+        infos.merge(Layer7Infos::default());
+        assert_eq!(infos.protocol.as_deref(), Some("HTTP"));
+
+        infos.merge(Layer7Infos {
+            protocol: Some("TLS".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(infos.protocol.as_deref(), Some("TLS"));
+    }
+}
@@ -0,0 +1,343 @@
+//! # Inventaire des certificats X.509
+//!
+//! Jusqu'à TLS 1.2, le serveur transmet sa chaîne de certificats en clair dans le message Certificate du
+//! handshake. Ce module décode ces certificats (sujet, émetteur, noms alternatifs, validité, clé publique,
+//! empreinte) et les regroupe par serveur (voir [`CertificateInventory`]), afin de repérer les certificats
+//! expirés, proches de l'expiration ou auto-signés.
+//!
+//! En TLS 1.3, le message Certificate est chiffré : aucun certificat n'est alors extrait.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use x509_parser::{
+    certificate::X509Certificate, extensions::GeneralName, prelude::FromDer, public_key::PublicKey,
+    time::ASN1Time,
+};
+
+use super::hex;
+use crate::sniff::capture_packet::layer_2_infos::PacketInfos;
+
+/// Nombre de jours avant l'expiration à partir duquel un certificat est signalé.
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Informations extraites d'un certificat X.509.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct CertificateInfos {
+    /// Nom distinctif du sujet.
+    pub subject: String,
+    /// Nom distinctif de l'émetteur.
+    pub issuer: String,
+    /// Noms DNS et adresses IP de l'extension Subject Alternative Name.
+    pub subject_alternative_names: Vec<String>,
+    /// Début de validité, en UTC.
+    pub not_before: String,
+    /// Fin de validité, en UTC.
+    pub not_after: String,
+    /// Début de validité, en secondes depuis l'époque Unix.
+    pub not_before_timestamp: i64,
+    /// Fin de validité, en secondes depuis l'époque Unix.
+    pub not_after_timestamp: i64,
+    /// Type de la clé publique (RSA, EC, Ed25519...).
+    pub key_type: String,
+    /// Taille de la clé publique en bits, si elle est connue.
+    pub key_size: Option<u32>,
+    /// Empreinte SHA-256 du certificat encodé en DER.
+    pub fingerprint_sha256: String,
+    /// Le sujet et l'émetteur sont identiques.
+    pub self_signed: bool,
+    /// Le certificat est celui d'une autorité de certification (basicConstraints CA:true).
+    pub is_ca: bool,
+}
+
+impl CertificateInfos {
+    /// Décode un certificat encodé en DER.
+    pub fn parse(der: &[u8]) -> Option<CertificateInfos> {
+        let (_, certificate) = X509Certificate::from_der(der).ok()?;
+
+        let subject_alternative_names = match certificate.subject_alternative_name() {
+            Ok(Some(extension)) => extension
+                .value
+                .general_names
+                .iter()
+                .filter_map(general_name)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let (key_type, key_size) = public_key(&certificate);
+        let validity = certificate.validity();
+
+        Some(CertificateInfos {
+            subject: certificate.subject().to_string(),
+            issuer: certificate.issuer().to_string(),
+            subject_alternative_names,
+            not_before: format_time(&validity.not_before),
+            not_after: format_time(&validity.not_after),
+            not_before_timestamp: validity.not_before.timestamp(),
+            not_after_timestamp: validity.not_after.timestamp(),
+            key_type,
+            key_size,
+            fingerprint_sha256: hex(&Sha256::digest(der)),
+            self_signed: certificate.subject().as_raw() == certificate.issuer().as_raw(),
+            is_ca: certificate.is_ca(),
+        })
+    }
+
+    /// Décode la chaîne de certificats d'un message de handshake Certificate (TLS 1.2 et antérieurs).
+    ///
+    /// Le certificat du serveur est en première position, suivi des autorités intermédiaires. Les certificats
+    /// qui ne peuvent pas être décodés sont ignorés.
+    pub fn parse_certificate_message(body: &[u8]) -> Vec<CertificateInfos> {
+        let mut certificates = Vec::new();
+        if body.len() < 3 {
+            return certificates;
+        }
+        let list_len = read_u24(body);
+        let mut list = &body[3..body.len().min(3 + list_len)];
+
+        while list.len() >= 3 {
+            let len = read_u24(list);
+            if list.len() < 3 + len {
+                break;
+            }
+            certificates.extend(CertificateInfos::parse(&list[3..3 + len]));
+            list = &list[3 + len..];
+        }
+        certificates
+    }
+
+    /// Avertissements sur la validité du certificat à l'instant `now` (secondes depuis l'époque Unix).
+    pub fn warnings(&self, now: i64) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if now > self.not_after_timestamp {
+            warnings.push("Expiré".to_string());
+        } else if now < self.not_before_timestamp {
+            warnings.push("Pas encore valide".to_string());
+        } else if self.not_after_timestamp - now < EXPIRY_WARNING_DAYS * SECONDS_PER_DAY {
+            warnings.push(format!(
+                "Expire dans {} jours",
+                (self.not_after_timestamp - now) / SECONDS_PER_DAY
+            ));
+        }
+        if self.self_signed {
+            warnings.push("Auto-signé".to_string());
+        }
+        warnings
+    }
+}
+
+fn read_u24(bytes: &[u8]) -> usize {
+    u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]) as usize
+}
+
+fn format_time(time: &ASN1Time) -> String {
+    let time = time.to_datetime();
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// Nom DNS ou adresse IP d'une entrée de l'extension Subject Alternative Name.
+fn general_name(name: &GeneralName) -> Option<String> {
+    match name {
+        GeneralName::DNSName(name) => Some(name.to_string()),
+        GeneralName::IPAddress(&[a, b, c, d]) => Some(format!("{}.{}.{}.{}", a, b, c, d)),
+        GeneralName::IPAddress(bytes) if bytes.len() == 16 => {
+            let mut octets = [0; 16];
+            octets.copy_from_slice(bytes);
+            Some(std::net::Ipv6Addr::from(octets).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Type et taille en bits de la clé publique du certificat.
+fn public_key(certificate: &X509Certificate) -> (String, Option<u32>) {
+    let key = certificate.public_key();
+    match key.parsed() {
+        Ok(PublicKey::RSA(rsa)) => {
+            // Le module peut être précédé d'octets nuls
+            let modulus: &[u8] = match rsa.modulus.iter().position(|b| *b != 0) {
+                Some(start) => &rsa.modulus[start..],
+                None => &[],
+            };
+            let size = match modulus.first() {
+                Some(first) => modulus.len() as u32 * 8 - first.leading_zeros(),
+                None => 0,
+            };
+            ("RSA".to_string(), Some(size))
+        }
+        Ok(PublicKey::EC(point)) => ("EC".to_string(), Some(point.key_size() as u32)),
+        Ok(PublicKey::DSA(y)) => ("DSA".to_string(), Some(y.len() as u32 * 8)),
+        _ => match key.algorithm.algorithm.to_id_string().as_str() {
+            "1.3.101.112" => ("Ed25519".to_string(), Some(256)),
+            "1.3.101.113" => ("Ed448".to_string(), Some(456)),
+            other => (other.to_string(), None),
+        },
+    }
+}
+
+/// Certificat présenté par un serveur.
+#[derive(Debug, Default, Clone)]
+pub struct ServerCertificate {
+    /// Adresse IP et port du serveur.
+    pub server: String,
+    /// Position du certificat dans la chaîne envoyée par le serveur (0 pour le certificat du serveur).
+    pub position: usize,
+    /// Informations du certificat.
+    pub certificate: CertificateInfos,
+}
+
+/// Inventaire des certificats, indexé par serveur et par empreinte.
+#[derive(Debug, Default)]
+pub struct CertificateInventory {
+    certificates: BTreeMap<(String, String), ServerCertificate>,
+}
+
+impl CertificateInventory {
+    /// Construit l'inventaire à partir des flux capturés.
+    pub fn from_packets(packets: &[(PacketInfos, u32)]) -> CertificateInventory {
+        let mut inventory = CertificateInventory::default();
+        for (packet, _) in packets {
+            inventory.record(packet);
+        }
+        inventory
+    }
+
+    /// Complète l'inventaire avec les certificats présentés par un paquet, s'il en porte.
+    ///
+    /// Le message Certificate est envoyé par le serveur : le serveur est la source du paquet.
+    pub fn record(&mut self, packet: &PacketInfos) {
+        let layer_4_infos = &packet.layer_3_infos.layer_4_infos;
+        let certificates = match layer_4_infos.layer_7_infos.as_ref() {
+            Some(layer_7_infos) if !layer_7_infos.certificates.is_empty() => {
+                &layer_7_infos.certificates
+            }
+            _ => return,
+        };
+
+        let ip = packet.layer_3_infos.ip_source.clone().unwrap_or_default();
+        let port = layer_4_infos.port_source.clone().unwrap_or_default();
+        let server = match ip.contains(':') {
+            true => format!("[{}]:{}", ip, port),
+            false => format!("{}:{}", ip, port),
+        };
+
+        for (position, certificate) in certificates.iter().enumerate() {
+            self.certificates
+                .entry((server.clone(), certificate.fingerprint_sha256.clone()))
+                .or_insert_with(|| ServerCertificate {
+                    server: server.clone(),
+                    position,
+                    certificate: certificate.clone(),
+                });
+        }
+    }
+
+    /// Retourne les certificats connus, par serveur.
+    pub fn certificates(&self) -> impl Iterator<Item = &ServerCertificate> {
+        self.certificates.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Certificat auto-signé EC P-256 de plc.example, valide du 1er janvier 2020 au 1er janvier 2021.
+    const CERTIFICATE_HEX: [&str; 10] = [
+        "308201be30820165a0030201020214351399e31c7189e7f2a30eb2c3cdf7c743c238f3300a06082a8648ce3d04030230",
+        "263114301206035504030c0b706c632e6578616d706c65310e300c060355040a0c05506c616e74301e170d3230303130",
+        "313030303030305a170d3231303130313030303030305a30263114301206035504030c0b706c632e6578616d706c6531",
+        "0e300c060355040a0c05506c616e743059301306072a8648ce3d020106082a8648ce3d03010703420004aee8bf9727cb",
+        "997f3999967e9607eba8771f7b4b1e71b654747c0a76dcacefa2323ea14d7944038b900c3cb64257e2f08de5837ee6c6",
+        "fb33bb9eb85fd28ffbbaa371306f301d0603551d0e0416041490cb6f20006005cb60a5fa261d907663d1d98100301f06",
+        "03551d2304183016801490cb6f20006005cb60a5fa261d907663d1d98100300f0603551d130101ff040530030101ff30",
+        "1c0603551d1104153013820b706c632e6578616d706c658704c0a8010a300a06082a8648ce3d04030203470030440220",
+        "2d15783f161496dedce3543f9ee111d20a4f850ae525307f490a256e1a7c039e02206580c7b8b116ce54201f8c965757",
+        "42936a9b609aef11b08f1deabc5e2520941e",
+    ];
+
+    fn certificate_der() -> Vec<u8> {
+        let hex = CERTIFICATE_HEX.concat();
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_certificate() {
+        let certificate = CertificateInfos::parse(&certificate_der()).unwrap();
+
+        assert_eq!(certificate.subject, "CN=plc.example, O=Plant");
+        assert_eq!(certificate.issuer, certificate.subject);
+        assert_eq!(
+            certificate.subject_alternative_names,
+            vec!["plc.example".to_string(), "192.168.1.10".to_string()]
+        );
+        assert_eq!(certificate.not_before, "2020-01-01 00:00:00 UTC");
+        assert_eq!(certificate.not_after, "2021-01-01 00:00:00 UTC");
+        assert_eq!(certificate.key_type, "EC");
+        assert_eq!(certificate.key_size, Some(256));
+        assert_eq!(
+            certificate.fingerprint_sha256,
+            "5b3e32924e848e854eea14d5e575cca9d6d2fcdb595db298c3222de64f30046e"
+        );
+        assert!(certificate.self_signed);
+        assert!(certificate.is_ca);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let certificate = CertificateInfos::parse(&certificate_der()).unwrap();
+        let not_after = certificate.not_after_timestamp;
+
+        assert_eq!(
+            certificate.warnings(not_after + 1),
+            vec!["Expiré", "Auto-signé"]
+        );
+        assert_eq!(
+            certificate.warnings(not_after - 10 * SECONDS_PER_DAY),
+            vec!["Expire dans 10 jours", "Auto-signé"]
+        );
+        assert_eq!(
+            certificate.warnings(certificate.not_before_timestamp - 1),
+            vec!["Pas encore valide", "Auto-signé"]
+        );
+
+        let trusted = CertificateInfos {
+            self_signed: false,
+            ..certificate
+        };
+        assert!(trusted
+            .warnings(not_after - 100 * SECONDS_PER_DAY)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_certificate_message() {
+        let der = certificate_der();
+        let mut entry = (der.len() as u32).to_be_bytes()[1..].to_vec();
+        entry.extend_from_slice(&der);
+        // Second certificat illisible, ignoré
+        entry.extend_from_slice(&[0, 0, 2, 0x30, 0x00]);
+        let mut body = (entry.len() as u32).to_be_bytes()[1..].to_vec();
+        body.extend(entry);
+
+        let certificates = CertificateInfos::parse_certificate_message(&body);
+        assert_eq!(certificates.len(), 1);
+        assert_eq!(certificates[0].subject, "CN=plc.example, O=Plant");
+
+        assert!(CertificateInfos::parse_certificate_message(&body[..10]).is_empty());
+    }
+}
@@ -0,0 +1,703 @@
+//! # Dissection des handshakes TLS
+//!
+//! Ce module décode les messages ClientHello et ServerHello, transmis en clair au début de chaque connexion
+//! TLS : nom du serveur demandé (SNI), protocoles applicatifs proposés (ALPN), version et suites de
+//! chiffrement.
+//!
+//! Jusqu'à TLS 1.2, la chaîne de certificats du serveur est également décodée (voir [`certificate`]).
+//!
+//! Il calcule également les empreintes qui caractérisent la pile TLS du client et du serveur, indépendamment
+//! du port utilisé :
+//!
+//! - JA3 (client) et JA3S (serveur) : hachage MD5 des versions, suites, extensions et courbes proposées ;
+//! - JA4 (client) : empreinte lisible suivie de hachages SHA-256 tronqués des suites et extensions triées.
+//!
+//! Les enregistrements TLS peuvent s'étendre sur plusieurs segments TCP : les messages sont extraits du flux
+//! réassemblé de chaque connexion (voir [`tls_stream_parser`]).
+
+use md5::Md5;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::super::tcp_reassembly::{ConnectionKey, StreamDirection, StreamParser};
+use super::Layer7Infos;
+
+pub(crate) mod certificate;
+use certificate::CertificateInfos;
+
+/// Taille maximale d'un enregistrement TLS (2^14 octets de données, plus l'expansion du chiffrement).
+const MAX_RECORD_LEN: usize = 16384 + 2048;
+/// Taille maximale d'un message de handshake conservé en attente de ses derniers enregistrements.
+const MAX_HANDSHAKE_LEN: usize = 256 * 1024;
+
+const CONTENT_ALERT: u8 = 21;
+const CONTENT_HANDSHAKE: u8 = 22;
+
+const HANDSHAKE_CLIENT_HELLO: u8 = 1;
+const HANDSHAKE_SERVER_HELLO: u8 = 2;
+const HANDSHAKE_CERTIFICATE: u8 = 11;
+
+const EXTENSION_SERVER_NAME: u16 = 0x0000;
+const EXTENSION_SUPPORTED_GROUPS: u16 = 0x000a;
+const EXTENSION_EC_POINT_FORMATS: u16 = 0x000b;
+const EXTENSION_SIGNATURE_ALGORITHMS: u16 = 0x000d;
+const EXTENSION_ALPN: u16 = 0x0010;
+const EXTENSION_SUPPORTED_VERSIONS: u16 = 0x002b;
+
+const TLS_1_3: u16 = 0x0304;
+
+/// Informations extraites d'un message ClientHello ou ServerHello.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct TlsInfos {
+    /// Message de handshake analysé : ClientHello ou ServerHello.
+    pub handshake_type: String,
+    /// Version la plus haute proposée par le client, ou version retenue par le serveur.
+    pub version: Option<String>,
+    /// Nom du serveur demandé par le client (extension SNI).
+    pub server_name: Option<String>,
+    /// Protocoles applicatifs proposés par le client, ou retenu par le serveur (extension ALPN).
+    pub alpn: Vec<String>,
+    /// Suites de chiffrement proposées par le client, ou retenue par le serveur.
+    pub cipher_suites: Vec<String>,
+    /// Empreinte JA3 du client.
+    pub ja3: Option<String>,
+    /// Empreinte JA3S du serveur.
+    pub ja3s: Option<String>,
+    /// Empreinte JA4 du client.
+    pub ja4: Option<String>,
+}
+
+/// Champs d'un message Hello utiles aux empreintes, dans l'ordre du message.
+#[derive(Debug, Default)]
+struct Hello {
+    legacy_version: u16,
+    cipher_suites: Vec<u16>,
+    extensions: Vec<u16>,
+    supported_groups: Vec<u16>,
+    ec_point_formats: Vec<u8>,
+    signature_algorithms: Vec<u16>,
+    supported_versions: Vec<u16>,
+    server_name: Option<String>,
+    alpn: Vec<String>,
+}
+
+/// Lecteur des champs d'un message, qui échoue si le message est tronqué.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    /// Lit un bloc précédé de sa longueur sur un ou deux octets.
+    fn vector(&mut self, length_size: usize) -> Option<Reader<'a>> {
+        let len = match length_size {
+            1 => self.u8()? as usize,
+            _ => self.u16()? as usize,
+        };
+        self.bytes(len).map(Reader::new)
+    }
+
+    fn u16_list(mut self) -> Vec<u16> {
+        let mut values = Vec::new();
+        while let Some(value) = self.u16() {
+            values.push(value);
+        }
+        values
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// Indique si une valeur est une valeur GREASE (RFC 8701), ajoutée aléatoirement par les clients
+/// et ignorée par les empreintes.
+fn is_grease(value: u16) -> bool {
+    value & 0x0f0f == 0x0a0a && value >> 8 == value & 0xff
+}
+
+impl Hello {
+    /// Décode le corps d'un ClientHello (`is_client`) ou d'un ServerHello.
+    fn parse(body: &[u8], is_client: bool) -> Option<Hello> {
+        let mut reader = Reader::new(body);
+        let mut hello = Hello {
+            legacy_version: reader.u16()?,
+            ..Default::default()
+        };
+        reader.bytes(32)?;
+        reader.vector(1)?;
+        if is_client {
+            hello.cipher_suites = reader.vector(2)?.u16_list();
+            reader.vector(1)?;
+        } else {
+            hello.cipher_suites = vec![reader.u16()?];
+            reader.u8()?;
+        }
+
+        // Les extensions sont absentes des messages les plus anciens
+        if reader.is_empty() {
+            return Some(hello);
+        }
+        let mut extensions = reader.vector(2)?;
+        while !extensions.is_empty() {
+            let extension_type = extensions.u16()?;
+            let mut data = extensions.vector(2)?;
+            hello.extensions.push(extension_type);
+
+            match extension_type {
+                EXTENSION_SERVER_NAME if is_client => {
+                    let mut names = data.vector(2)?;
+                    while !names.is_empty() {
+                        let name_type = names.u8()?;
+                        let name = names.vector(2)?;
+                        if name_type == 0 {
+                            hello.server_name =
+                                Some(String::from_utf8_lossy(name.data).into_owned());
+                        }
+                    }
+                }
+                EXTENSION_SUPPORTED_GROUPS => hello.supported_groups = data.vector(2)?.u16_list(),
+                EXTENSION_EC_POINT_FORMATS => {
+                    hello.ec_point_formats = data.vector(1)?.data.to_vec();
+                }
+                EXTENSION_SIGNATURE_ALGORITHMS => {
+                    hello.signature_algorithms = data.vector(2)?.u16_list();
+                }
+                EXTENSION_ALPN => {
+                    let mut protocols = data.vector(2)?;
+                    while !protocols.is_empty() {
+                        let protocol = protocols.vector(1)?;
+                        hello
+                            .alpn
+                            .push(String::from_utf8_lossy(protocol.data).into_owned());
+                    }
+                }
+                EXTENSION_SUPPORTED_VERSIONS if is_client => {
+                    hello.supported_versions = data.vector(1)?.u16_list();
+                }
+                EXTENSION_SUPPORTED_VERSIONS => hello.supported_versions = vec![data.u16()?],
+                _ => {}
+            }
+        }
+        Some(hello)
+    }
+
+    /// Version la plus haute proposée (client) ou retenue (serveur), d'après l'extension supported_versions
+    /// ou à défaut la version du message.
+    fn version(&self) -> u16 {
+        self.supported_versions
+            .iter()
+            .copied()
+            .filter(|version| !is_grease(*version))
+            .max()
+            .unwrap_or(self.legacy_version)
+    }
+
+    fn ja3_string(&self) -> String {
+        format!(
+            "{},{},{},{},{}",
+            self.legacy_version,
+            join_decimal(&self.cipher_suites),
+            join_decimal(&self.extensions),
+            join_decimal(&self.supported_groups),
+            join_decimal(
+                &self
+                    .ec_point_formats
+                    .iter()
+                    .map(|f| u16::from(*f))
+                    .collect::<Vec<_>>()
+            ),
+        )
+    }
+
+    fn ja3s_string(&self) -> String {
+        format!(
+            "{},{},{}",
+            self.legacy_version,
+            join_decimal(&self.cipher_suites),
+            join_decimal(&self.extensions),
+        )
+    }
+
+    /// Calcule l'empreinte JA4 d'un ClientHello transporté par TCP.
+    fn ja4(&self) -> String {
+        let ciphers: Vec<u16> = self
+            .cipher_suites
+            .iter()
+            .copied()
+            .filter(|c| !is_grease(*c))
+            .collect();
+        let extensions: Vec<u16> = self
+            .extensions
+            .iter()
+            .copied()
+            .filter(|e| !is_grease(*e))
+            .collect();
+
+        let version = match self.version() {
+            0x0304 => "13",
+            0x0303 => "12",
+            0x0302 => "11",
+            0x0301 => "10",
+            0x0300 => "s3",
+            _ => "00",
+        };
+        let destination = match self.server_name {
+            Some(_) => 'd',
+            None => 'i',
+        };
+        // Premier et dernier caractères du premier protocole ALPN
+        let alpn = match self.alpn.first().map(String::as_bytes) {
+            Some(&[first, .., last]) | Some(&[first @ last]) => {
+                if first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric() {
+                    format!("{}{}", first as char, last as char)
+                } else {
+                    let first = format!("{:02x}", first);
+                    let last = format!("{:02x}", last);
+                    format!("{}{}", &first[..1], &last[1..])
+                }
+            }
+            _ => "00".to_string(),
+        };
+
+        let mut sorted_ciphers = ciphers.clone();
+        sorted_ciphers.sort_unstable();
+        let mut sorted_extensions: Vec<u16> = extensions
+            .iter()
+            .copied()
+            .filter(|e| *e != EXTENSION_SERVER_NAME && *e != EXTENSION_ALPN)
+            .collect();
+        sorted_extensions.sort_unstable();
+
+        let mut extensions_input = join_hex(&sorted_extensions);
+        if !self.signature_algorithms.is_empty() {
+            extensions_input.push('_');
+            extensions_input.push_str(&join_hex(&self.signature_algorithms));
+        }
+
+        format!(
+            "t{}{}{:02}{:02}{}_{}_{}",
+            version,
+            destination,
+            ciphers.len().min(99),
+            extensions.len().min(99),
+            alpn,
+            truncated_sha256(&sorted_ciphers, &join_hex(&sorted_ciphers)),
+            truncated_sha256(&sorted_extensions, &extensions_input),
+        )
+    }
+}
+
+fn join_decimal(values: &[u16]) -> String {
+    let values: Vec<String> = values
+        .iter()
+        .filter(|value| !is_grease(**value))
+        .map(u16::to_string)
+        .collect();
+    values.join("-")
+}
+
+fn join_hex(values: &[u16]) -> String {
+    let values: Vec<String> = values
+        .iter()
+        .map(|value| format!("{:04x}", value))
+        .collect();
+    values.join(",")
+}
+
+/// Douze premiers caractères hexadécimaux du SHA-256 de `input`, ou des zéros si `values` est vide.
+fn truncated_sha256(values: &[u16], input: &str) -> String {
+    if values.is_empty() {
+        return "0".repeat(12);
+    }
+    hex(&Sha256::digest(input.as_bytes()))[..12].to_string()
+}
+
+fn md5_hex(input: &str) -> String {
+    hex(&Md5::digest(input.as_bytes()))
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn version_name(version: u16) -> String {
+    match version {
+        0x0300 => "SSL 3.0".to_string(),
+        0x0301 => "TLS 1.0".to_string(),
+        0x0302 => "TLS 1.1".to_string(),
+        0x0303 => "TLS 1.2".to_string(),
+        0x0304 => "TLS 1.3".to_string(),
+        other => format!("0x{:04x}", other),
+    }
+}
+
+fn cipher_suite_name(cipher_suite: u16) -> String {
+    let name = match cipher_suite {
+        0x0004 => "TLS_RSA_WITH_RC4_128_MD5",
+        0x0005 => "TLS_RSA_WITH_RC4_128_SHA",
+        0x000a => "TLS_RSA_WITH_3DES_EDE_CBC_SHA",
+        0x002f => "TLS_RSA_WITH_AES_128_CBC_SHA",
+        0x0035 => "TLS_RSA_WITH_AES_256_CBC_SHA",
+        0x003c => "TLS_RSA_WITH_AES_128_CBC_SHA256",
+        0x009c => "TLS_RSA_WITH_AES_128_GCM_SHA256",
+        0x009d => "TLS_RSA_WITH_AES_256_GCM_SHA384",
+        0x00ff => "TLS_EMPTY_RENEGOTIATION_INFO_SCSV",
+        0x1301 => "TLS_AES_128_GCM_SHA256",
+        0x1302 => "TLS_AES_256_GCM_SHA384",
+        0x1303 => "TLS_CHACHA20_POLY1305_SHA256",
+        0xc009 => "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA",
+        0xc00a => "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA",
+        0xc013 => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA",
+        0xc014 => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA",
+        0xc027 => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256",
+        0xc028 => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384",
+        0xc02b => "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256",
+        0xc02c => "TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384",
+        0xc02f => "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256",
+        0xc030 => "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+        0xcca8 => "TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+        0xcca9 => "TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256",
+        other => return format!("0x{:04x}", other),
+    };
+    name.to_string()
+}
+
+impl TlsInfos {
+    /// Décode le corps d'un message ClientHello.
+    pub fn parse_client_hello(body: &[u8]) -> Option<TlsInfos> {
+        let hello = Hello::parse(body, true)?;
+        Some(TlsInfos {
+            handshake_type: "ClientHello".to_string(),
+            version: Some(version_name(hello.version())),
+            server_name: hello.server_name.clone(),
+            alpn: hello.alpn.clone(),
+            cipher_suites: cipher_suite_names(&hello.cipher_suites),
+            ja3: Some(md5_hex(&hello.ja3_string())),
+            ja3s: None,
+            ja4: Some(hello.ja4()),
+        })
+    }
+
+    /// Décode le corps d'un message ServerHello.
+    pub fn parse_server_hello(body: &[u8]) -> Option<TlsInfos> {
+        let hello = Hello::parse(body, false)?;
+        Some(TlsInfos {
+            handshake_type: "ServerHello".to_string(),
+            version: Some(version_name(hello.version())),
+            server_name: None,
+            alpn: hello.alpn.clone(),
+            cipher_suites: cipher_suite_names(&hello.cipher_suites),
+            ja3: None,
+            ja3s: Some(md5_hex(&hello.ja3s_string())),
+            ja4: None,
+        })
+    }
+}
+
+fn cipher_suite_names(cipher_suites: &[u16]) -> Vec<String> {
+    cipher_suites
+        .iter()
+        .filter(|c| !is_grease(**c))
+        .map(|c| cipher_suite_name(*c))
+        .collect()
+}
+
+/// Sens d'une connexion TLS.
+#[derive(Debug, Default)]
+struct TlsHalf {
+    /// Le premier enregistrement a été reçu.
+    started: bool,
+    /// Le flux est chiffré, ou n'est pas du TLS : il n'est plus analysé.
+    done: bool,
+    /// Enregistrements en cours de réception.
+    records: Vec<u8>,
+    /// Messages de handshake en cours de réception, qui peuvent s'étendre sur plusieurs enregistrements.
+    handshake: Vec<u8>,
+}
+
+/// Analyseur des handshakes TLS d'une connexion TCP.
+#[derive(Debug, Default)]
+struct TlsStreamParser {
+    client: TlsHalf,
+    server: TlsHalf,
+}
+
+impl TlsHalf {
+    /// Extrait les enregistrements complets reçus, et décode leurs messages de handshake.
+    fn process(&mut self, is_client: bool, output: &mut Layer7Infos) {
+        let mut position = 0;
+
+        while !self.done && self.records.len() - position >= 5 {
+            let header = &self.records[position..position + 5];
+            let content_type = header[0];
+            let len = u16::from_be_bytes([header[3], header[4]]) as usize;
+            if header[1] != 0x03
+                || len > MAX_RECORD_LEN
+                || (!self.started && content_type != CONTENT_HANDSHAKE)
+            {
+                self.done = true;
+                break;
+            }
+            if self.records.len() - position < 5 + len {
+                break;
+            }
+            self.started = true;
+
+            match content_type {
+                CONTENT_HANDSHAKE => {
+                    self.handshake
+                        .extend_from_slice(&self.records[position + 5..position + 5 + len]);
+                    self.process_handshake(is_client, output);
+                }
+                CONTENT_ALERT => {}
+                // ChangeCipherSpec, données applicatives : la suite du flux est chiffrée
+                _ => self.done = true,
+            }
+            position += 5 + len;
+        }
+
+        if self.done {
+            self.records = Vec::new();
+            self.handshake = Vec::new();
+        } else {
+            self.records.drain(..position);
+        }
+    }
+
+    fn process_handshake(&mut self, is_client: bool, output: &mut Layer7Infos) {
+        let mut position = 0;
+
+        while !self.done && self.handshake.len() - position >= 4 {
+            let header = &self.handshake[position..position + 4];
+            let message_type = header[0];
+            let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+            if len > MAX_HANDSHAKE_LEN {
+                self.done = true;
+                break;
+            }
+            if self.handshake.len() - position < 4 + len {
+                break;
+            }
+            let body = &self.handshake[position + 4..position + 4 + len];
+
+            if message_type == HANDSHAKE_CERTIFICATE && !is_client {
+                let certificates = CertificateInfos::parse_certificate_message(body);
+                if !certificates.is_empty() {
+                    output.certificates = certificates;
+                }
+            }
+            let tls = match (message_type, is_client) {
+                (HANDSHAKE_CLIENT_HELLO, true) => TlsInfos::parse_client_hello(body),
+                (HANDSHAKE_SERVER_HELLO, false) => TlsInfos::parse_server_hello(body),
+                _ => None,
+            };
+            if let Some(tls) = tls {
+                // Au-delà du ServerHello, les messages TLS 1.3 sont chiffrés
+                if tls.version.as_deref() == Some(version_name(TLS_1_3).as_str()) && !is_client {
+                    self.done = true;
+                }
+                output.protocol = Some("TLS".to_string());
+                output.tls = Some(tls);
+            }
+            position += 4 + len;
+        }
+
+        self.handshake.drain(..position.min(self.handshake.len()));
+    }
+}
+
+impl StreamParser<Layer7Infos> for TlsStreamParser {
+    fn on_data(&mut self, direction: StreamDirection, data: &[u8], output: &mut Layer7Infos) {
+        let (half, is_client) = match direction {
+            StreamDirection::ClientToServer => (&mut self.client, true),
+            StreamDirection::ServerToClient => (&mut self.server, false),
+        };
+        if half.done {
+            return;
+        }
+        half.records.extend_from_slice(data);
+        half.process(is_client, output);
+    }
+
+    fn on_gap(&mut self, direction: StreamDirection) {
+        // Les limites des enregistrements ne sont plus connues
+        let half = match direction {
+            StreamDirection::ClientToServer => &mut self.client,
+            StreamDirection::ServerToClient => &mut self.server,
+        };
+        *half = TlsHalf {
+            done: true,
+            ..Default::default()
+        };
+    }
+
+    fn wants_more(&self) -> bool {
+        !self.client.done || !self.server.done
+    }
+}
+
+/// Crée l'analyseur TLS d'une connexion TCP.
+///
+/// TLS n'est pas limité au port 443 (MQTT, OPC UA, interfaces d'administration...) : toutes les connexions
+/// sont analysées, et l'analyseur se désactive dès que les premières données ne sont pas un enregistrement
+/// de handshake.
+pub fn tls_stream_parser(_key: &ConnectionKey) -> Option<Box<dyn StreamParser<Layer7Infos>>> {
+    Some(Box::new(TlsStreamParser::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extension(extension_type: u16, data: &[u8]) -> Vec<u8> {
+        let mut bytes = extension_type.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// Enregistrement contenant un message de handshake.
+    fn handshake_record(message_type: u8, body: &[u8]) -> Vec<u8> {
+        let mut record = vec![CONTENT_HANDSHAKE, 0x03, 0x01];
+        record.extend_from_slice(&((body.len() + 4) as u16).to_be_bytes());
+        record.push(message_type);
+        record.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+        record.extend_from_slice(body);
+        record
+    }
+
+    /// ClientHello TLS 1.3 vers plc.example, avec des valeurs GREASE.
+    fn client_hello() -> Vec<u8> {
+        let mut body = vec![0x03, 0x03];
+        body.extend_from_slice(&[0; 32]);
+        body.push(0);
+        body.extend_from_slice(&[0, 8, 0x0a, 0x0a, 0x13, 0x01, 0x13, 0x02, 0xc0, 0x2f]);
+        body.extend_from_slice(&[1, 0]);
+
+        let mut extensions = extension(0x0a0a, &[]);
+        extensions.extend(extension(
+            EXTENSION_SERVER_NAME,
+            &[
+                0, 14, 0, 0, 11, b'p', b'l', b'c', b'.', b'e', b'x', b'a', b'm', b'p', b'l', b'e',
+            ],
+        ));
+        extensions.extend(extension(
+            EXTENSION_SUPPORTED_GROUPS,
+            &[0, 4, 0x00, 0x1d, 0x00, 0x17],
+        ));
+        extensions.extend(extension(EXTENSION_EC_POINT_FORMATS, &[1, 0]));
+        extensions.extend(extension(
+            EXTENSION_SIGNATURE_ALGORITHMS,
+            &[0, 4, 0x04, 0x03, 0x08, 0x04],
+        ));
+        extensions.extend(extension(EXTENSION_ALPN, &[0, 3, 2, b'h', b'2']));
+        extensions.extend(extension(
+            EXTENSION_SUPPORTED_VERSIONS,
+            &[4, 0x03, 0x04, 0x03, 0x03],
+        ));
+        body.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+        body.extend(extensions);
+
+        handshake_record(HANDSHAKE_CLIENT_HELLO, &body)
+    }
+
+    #[test]
+    fn test_client_hello_fingerprints() {
+        let record = client_hello();
+        let tls = TlsInfos::parse_client_hello(&record[9..]).unwrap();
+        let hello = Hello::parse(&record[9..], true).unwrap();
+
+        assert_eq!(tls.server_name.as_deref(), Some("plc.example"));
+        assert_eq!(tls.alpn, vec!["h2".to_string()]);
+        assert_eq!(tls.version.as_deref(), Some("TLS 1.3"));
+        assert_eq!(
+            tls.cipher_suites,
+            vec![
+                "TLS_AES_128_GCM_SHA256",
+                "TLS_AES_256_GCM_SHA384",
+                "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"
+            ]
+        );
+        assert_eq!(
+            hello.ja3_string(),
+            "771,4865-4866-49199,0-10-11-13-16-43,29-23,0"
+        );
+        assert_eq!(
+            tls.ja3.as_deref(),
+            Some(md5_hex("771,4865-4866-49199,0-10-11-13-16-43,29-23,0").as_str())
+        );
+        assert_eq!(
+            tls.ja4.as_deref(),
+            Some("t13d0306h2_40b44b994229_fb71836bce29")
+        );
+    }
+
+    #[test]
+    fn test_stream_parser_reads_split_records() {
+        let record = client_hello();
+        let mut parser = TlsStreamParser::default();
+        let mut output = Layer7Infos::default();
+
+        parser.on_data(StreamDirection::ClientToServer, &record[..20], &mut output);
+        assert!(output.tls.is_none());
+        parser.on_data(StreamDirection::ClientToServer, &record[20..], &mut output);
+        assert_eq!(output.protocol.as_deref(), Some("TLS"));
+        assert_eq!(
+            output.tls.as_ref().unwrap().server_name.as_deref(),
+            Some("plc.example")
+        );
+
+        // Serveur qui n'est pas TLS
+        parser.on_data(
+            StreamDirection::ServerToClient,
+            b"HTTP/1.1 400 Bad Request\r\n",
+            &mut output,
+        );
+        assert!(parser.server.done);
+        assert!(parser.wants_more());
+    }
+
+    #[test]
+    fn test_server_hello_ja3s() {
+        let mut body = vec![0x03, 0x03];
+        body.extend_from_slice(&[0; 32]);
+        body.extend_from_slice(&[0, 0x13, 0x01, 0]);
+        let extensions = extension(EXTENSION_SUPPORTED_VERSIONS, &[0x03, 0x04]);
+        body.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+        body.extend(extensions);
+
+        let mut parser = TlsStreamParser::default();
+        let mut output = Layer7Infos::default();
+        parser.on_data(
+            StreamDirection::ServerToClient,
+            &handshake_record(HANDSHAKE_SERVER_HELLO, &body),
+            &mut output,
+        );
+
+        let tls = output.tls.unwrap();
+        assert_eq!(tls.version.as_deref(), Some("TLS 1.3"));
+        assert_eq!(tls.ja3s, Some(md5_hex("771,4865,43")));
+        assert!(parser.server.done);
+    }
+}
@@ -5,14 +5,54 @@ use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
 use pnet::packet::tcp::TcpPacket;
 use pnet::packet::udp::UdpPacket;
 use serde::Serialize;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Instant;
 
 use log::info;
-//use pnet::packet::Packet;
+use pnet::packet::Packet;
+
+pub(crate) mod icmp;
+use icmp::IcmpInfos;
+pub(crate) mod layer_7_infos;
+use layer_7_infos::{get_layer_7_infos, Layer7Infos};
+pub(crate) mod ndp;
+pub(crate) mod tcp;
+pub(crate) mod tcp_reassembly;
+use tcp::TcpInfos;
+use tcp_reassembly::{Segment, TcpReassembler};
 
 #[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
 pub struct Layer4Infos {
     pub port_source: Option<String>,
     pub port_destination: Option<String>,
+    /// Type, code et détails des messages ICMP et ICMPv6.
+    pub icmp: Option<IcmpInfos>,
+    /// Drapeaux, numéros de séquence, fenêtre, options et état de connexion des segments TCP.
+    pub tcp: Option<TcpInfos>,
+    /// Protocole applicatif et informations extraites de la charge utile des segments TCP et datagrammes UDP.
+    pub layer_7_infos: Option<Layer7Infos>,
+}
+
+impl Layer4Infos {
+    /// Indique si deux paquets appartiennent au même flux de couche 4.
+    ///
+    /// Les champs propres à chaque paquet (identifiant et séquence d'écho, datagramme d'origine
+    /// d'une erreur ICMP, en-tête TCP, informations applicatives) ne sont pas comparés : seuls les ports et le
+    /// type/code ICMP identifient le flux.
+    pub fn same_flow(&self, other: &Layer4Infos) -> bool {
+        let Layer4Infos {
+            port_source,
+            port_destination,
+            icmp,
+            tcp: _,
+            layer_7_infos: _,
+        } = self;
+
+        *port_source == other.port_source
+            && *port_destination == other.port_destination
+            && icmp.as_ref().map(|i| (i.icmp_type, i.icmp_code))
+                == other.icmp.as_ref().map(|i| (i.icmp_type, i.icmp_code))
+    }
 }
 
 trait PacketPorts {
@@ -24,6 +64,8 @@ impl PacketPorts for TcpPacket<'_> {
         Layer4Infos {
             port_source: Some(self.get_source().to_string()),
             port_destination: Some(self.get_destination().to_string()),
+            tcp: Some(TcpInfos::from_packet(self)),
+            ..Default::default()
         }
     }
 }
@@ -33,36 +75,115 @@ impl PacketPorts for UdpPacket<'_> {
         Layer4Infos {
             port_source: Some(self.get_source().to_string()),
             port_destination: Some(self.get_destination().to_string()),
+            ..Default::default()
         }
     }
 }
 
-pub fn get_layer_4_infos(proto: IpNextHeaderProtocol, data: &[u8]) -> Layer4Infos {
+/// Extrait les ports, ou le type et le code ICMP, d'un fragment d'un datagramme pas encore réassemblé.
+///
+/// Le fragment est ainsi rattaché à son flux, sans que le segment TCP ne soit transmis au réassemblage
+/// des flux ni que la charge utile ne soit analysée : le datagramme complet le sera une fois réassemblé.
+pub fn get_layer_4_ports(proto: IpNextHeaderProtocol, data: &[u8]) -> Layer4Infos {
+    match proto {
+        IpNextHeaderProtocols::Tcp => match TcpPacket::new(data) {
+            Some(tcp_packet) => Layer4Infos {
+                tcp: None,
+                ..tcp_packet.ports()
+            },
+            None => Default::default(),
+        },
+        IpNextHeaderProtocols::Udp => match UdpPacket::new(data) {
+            Some(udp_packet) => udp_packet.ports(),
+            None => Default::default(),
+        },
+        IpNextHeaderProtocols::Icmp => match IcmpPacket::new(data) {
+            Some(icmp_packet) => Layer4Infos {
+                icmp: Some(IcmpInfos::from_icmp(&icmp_packet)),
+                ..Default::default()
+            },
+            None => Default::default(),
+        },
+        IpNextHeaderProtocols::Icmpv6 => match Icmpv6Packet::new(data) {
+            Some(icmpv6_packet) => Layer4Infos {
+                icmp: Some(IcmpInfos::from_icmpv6(&icmpv6_packet)),
+                ..Default::default()
+            },
+            None => Default::default(),
+        },
+        _ => Default::default(),
+    }
+}
+
+/// Extrait les informations de la couche 4 des données `data` d'un paquet IP.
+///
+/// Les adresses du paquet identifient la connexion des segments TCP, dont la charge utile est transmise
+/// au réassemblage des flux `tcp_streams` du thread de capture.
+pub fn get_layer_4_infos(
+    source: IpAddr,
+    destination: IpAddr,
+    proto: IpNextHeaderProtocol,
+    data: &[u8],
+    tcp_streams: &mut TcpReassembler<Layer7Infos>,
+) -> Layer4Infos {
     match proto {
         IpNextHeaderProtocols::Tcp => {
             if let Some(tcp_packet) = TcpPacket::new(data) {
-                tcp_packet.ports()
+                let mut layer_4_infos = tcp_packet.ports();
+                if let Some(tcp) = &layer_4_infos.tcp {
+                    let segment = Segment {
+                        source: SocketAddr::new(source, tcp_packet.get_source()),
+                        destination: SocketAddr::new(destination, tcp_packet.get_destination()),
+                        sequence: tcp.sequence,
+                        flags: tcp.flags,
+                        payload: &data[data.len() - tcp.payload_len..],
+                    };
+                    let mut layer_7_infos = get_layer_7_infos(
+                        tcp_packet.get_source(),
+                        tcp_packet.get_destination(),
+                        segment.payload,
+                        proto,
+                    )
+                    .unwrap_or_default();
+                    tcp_streams.add(segment, &mut layer_7_infos, Instant::now());
+                    layer_4_infos.layer_7_infos = Some(layer_7_infos).filter(|l| !l.is_empty());
+                }
+                layer_4_infos
             } else {
                 Default::default()
             }
         }
         IpNextHeaderProtocols::Udp => {
             if let Some(udp_packet) = UdpPacket::new(data) {
-                udp_packet.ports()
+                Layer4Infos {
+                    layer_7_infos: get_layer_7_infos(
+                        udp_packet.get_source(),
+                        udp_packet.get_destination(),
+                        udp_packet.payload(),
+                        proto,
+                    ),
+                    ..udp_packet.ports()
+                }
             } else {
                 Default::default()
             }
         }
         IpNextHeaderProtocols::Icmp => {
-            if let Some(_icmp_packet) = IcmpPacket::new(data) {
-                Default::default()
+            if let Some(icmp_packet) = IcmpPacket::new(data) {
+                Layer4Infos {
+                    icmp: Some(IcmpInfos::from_icmp(&icmp_packet)),
+                    ..Default::default()
+                }
             } else {
                 Default::default()
             }
         }
         IpNextHeaderProtocols::Icmpv6 => {
-            if let Some(_icmpv6_packet) = Icmpv6Packet::new(data) {
-                Default::default()
+            if let Some(icmpv6_packet) = Icmpv6Packet::new(data) {
+                Layer4Infos {
+                    icmp: Some(IcmpInfos::from_icmpv6(&icmpv6_packet)),
+                    ..Default::default()
+                }
             } else {
                 Default::default()
             }
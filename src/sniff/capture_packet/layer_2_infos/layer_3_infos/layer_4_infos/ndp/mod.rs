@@ -0,0 +1,230 @@
+//! # Dissection IPv6 Neighbor Discovery (NDP)
+//!
+//! Les messages NDP (RFC 4861) transportés par ICMPv6 sont la principale source d'information passive
+//! sur les hôtes IPv6 : sollicitations et annonces de routeur, sollicitations et annonces de voisin, redirections.
+//!
+//! Ce module décode ces messages et leurs options (adresses de couche liaison source et cible, préfixes,
+//! MTU, serveurs DNS récursifs) afin d'associer les adresses IPv6 aux adresses MAC et d'identifier les routeurs.
+
+use std::net::Ipv6Addr;
+
+use pnet::util::MacAddr;
+use serde::Serialize;
+
+/// Types de messages ICMPv6 de Neighbor Discovery.
+pub const ROUTER_SOLICITATION: u8 = 133;
+pub const ROUTER_ADVERTISEMENT: u8 = 134;
+pub const NEIGHBOR_SOLICITATION: u8 = 135;
+pub const NEIGHBOR_ADVERTISEMENT: u8 = 136;
+pub const REDIRECT: u8 = 137;
+
+/// Types d'options NDP.
+const OPTION_SOURCE_LINK_LAYER_ADDRESS: u8 = 1;
+const OPTION_TARGET_LINK_LAYER_ADDRESS: u8 = 2;
+const OPTION_PREFIX_INFORMATION: u8 = 3;
+const OPTION_MTU: u8 = 5;
+const OPTION_RECURSIVE_DNS_SERVER: u8 = 25;
+
+/// Informations extraites d'un message Neighbor Discovery.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct NdpInfos {
+    /// Adresse IPv6 cible (sollicitation, annonce de voisin et redirection).
+    pub target_address: Option<String>,
+    /// Adresse de destination redirigée (redirection).
+    pub destination_address: Option<String>,
+    /// Adresse MAC de l'émetteur (option Source Link-Layer Address).
+    pub source_link_layer_address: Option<String>,
+    /// Adresse MAC associée à la cible (option Target Link-Layer Address).
+    pub target_link_layer_address: Option<String>,
+    /// Durée de validité du routeur par défaut, en secondes (annonce de routeur).
+    pub router_lifetime: Option<u16>,
+    /// Hop limit conseillé aux hôtes (annonce de routeur).
+    pub hop_limit: Option<u8>,
+    /// Drapeaux du message : Managed/Other pour une annonce de routeur, Router/Solicited/Override pour une annonce de voisin.
+    pub flags: Vec<String>,
+    /// Préfixes annoncés par un routeur.
+    pub prefixes: Vec<NdpPrefix>,
+    /// MTU annoncée par un routeur.
+    pub mtu: Option<u32>,
+    /// Serveurs DNS récursifs annoncés par un routeur (RFC 8106).
+    pub dns_servers: Vec<String>,
+}
+
+/// Préfixe annoncé dans une option Prefix Information.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct NdpPrefix {
+    /// Préfixe au format CIDR (par exemple `2001:db8::/64`).
+    pub prefix: String,
+    /// Le préfixe est directement joignable sur le lien (drapeau L).
+    pub on_link: bool,
+    /// Le préfixe peut servir à l'autoconfiguration SLAAC (drapeau A).
+    pub autonomous: bool,
+    /// Durée de validité, en secondes.
+    pub valid_lifetime: u32,
+    /// Durée de préférence, en secondes.
+    pub preferred_lifetime: u32,
+}
+
+impl NdpInfos {
+    /// Décode un message NDP à partir de son type ICMPv6 et du corps du message (après les 4 octets d'en-tête).
+    ///
+    /// Retourne `None` si le type n'est pas un message Neighbor Discovery ou si le message est tronqué.
+    pub fn parse(icmp_type: u8, body: &[u8]) -> Option<NdpInfos> {
+        let mut infos = NdpInfos::default();
+
+        let options = match icmp_type {
+            ROUTER_SOLICITATION => body.get(4..)?,
+            ROUTER_ADVERTISEMENT => {
+                let header = body.get(..12)?;
+                infos.hop_limit = Some(header[0]);
+                infos.flags = flag_names(header[1], &[(0x80, "Managed"), (0x40, "Other")]);
+                infos.router_lifetime = Some(u16::from_be_bytes([header[2], header[3]]));
+                &body[12..]
+            }
+            NEIGHBOR_SOLICITATION => {
+                infos.target_address = Some(read_ipv6(body.get(4..20)?));
+                &body[20..]
+            }
+            NEIGHBOR_ADVERTISEMENT => {
+                infos.flags = flag_names(
+                    *body.first()?,
+                    &[(0x80, "Router"), (0x40, "Solicited"), (0x20, "Override")],
+                );
+                infos.target_address = Some(read_ipv6(body.get(4..20)?));
+                &body[20..]
+            }
+            REDIRECT => {
+                infos.target_address = Some(read_ipv6(body.get(4..20)?));
+                infos.destination_address = Some(read_ipv6(body.get(20..36)?));
+                &body[36..]
+            }
+            _ => return None,
+        };
+
+        infos.parse_options(options);
+        Some(infos)
+    }
+
+    /// Décode les options NDP, dont la longueur est exprimée en unités de 8 octets.
+    fn parse_options(&mut self, mut options: &[u8]) {
+        while options.len() >= 2 {
+            let option_type = options[0];
+            let option_len = options[1] as usize * 8;
+            if option_len == 0 || option_len > options.len() {
+                break;
+            }
+            let data = &options[2..option_len];
+
+            match option_type {
+                OPTION_SOURCE_LINK_LAYER_ADDRESS if data.len() >= 6 => {
+                    self.source_link_layer_address = Some(read_mac(data))
+                }
+                OPTION_TARGET_LINK_LAYER_ADDRESS if data.len() >= 6 => {
+                    self.target_link_layer_address = Some(read_mac(data))
+                }
+                OPTION_PREFIX_INFORMATION if data.len() >= 30 => self.prefixes.push(NdpPrefix {
+                    prefix: format!("{}/{}", read_ipv6(&data[14..30]), data[0]),
+                    on_link: data[1] & 0x80 != 0,
+                    autonomous: data[1] & 0x40 != 0,
+                    valid_lifetime: u32::from_be_bytes([data[2], data[3], data[4], data[5]]),
+                    preferred_lifetime: u32::from_be_bytes([data[6], data[7], data[8], data[9]]),
+                }),
+                OPTION_MTU if data.len() >= 6 => {
+                    self.mtu = Some(u32::from_be_bytes([data[2], data[3], data[4], data[5]]))
+                }
+                OPTION_RECURSIVE_DNS_SERVER if data.len() >= 6 => {
+                    self.dns_servers
+                        .extend(data[6..].chunks_exact(16).map(read_ipv6));
+                }
+                _ => {}
+            }
+            options = &options[option_len..];
+        }
+    }
+}
+
+fn flag_names(flags: u8, names: &[(u8, &str)]) -> Vec<String> {
+    names
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+fn read_ipv6(octets: &[u8]) -> String {
+    let mut address = [0u8; 16];
+    address.copy_from_slice(&octets[..16]);
+    Ipv6Addr::from(address).to_string()
+}
+
+fn read_mac(octets: &[u8]) -> String {
+    MacAddr::new(
+        octets[0], octets[1], octets[2], octets[3], octets[4], octets[5],
+    )
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ipv6(address: &str) -> [u8; 16] {
+        address.parse::<Ipv6Addr>().unwrap().octets()
+    }
+
+    #[test]
+    fn test_parse_neighbor_advertisement() {
+        let mut body = vec![0x60, 0, 0, 0];
+        body.extend_from_slice(&ipv6("fe80::1"));
+        body.extend_from_slice(&[OPTION_TARGET_LINK_LAYER_ADDRESS, 1, 0x02, 0, 0, 0, 0, 0x01]);
+
+        let infos = NdpInfos::parse(NEIGHBOR_ADVERTISEMENT, &body).unwrap();
+
+        assert_eq!(infos.target_address.as_deref(), Some("fe80::1"));
+        assert_eq!(
+            infos.target_link_layer_address.as_deref(),
+            Some("02:00:00:00:00:01")
+        );
+        assert_eq!(infos.flags, vec!["Solicited", "Override"]);
+    }
+
+    #[test]
+    fn test_parse_router_advertisement() {
+        let mut body = vec![64, 0x40, 0x07, 0x08, 0, 0, 0, 0, 0, 0, 0, 0];
+        body.extend_from_slice(&[OPTION_SOURCE_LINK_LAYER_ADDRESS, 1, 0x02, 0, 0, 0, 0, 0xfe]);
+        body.extend_from_slice(&[OPTION_MTU, 1, 0, 0, 0, 0, 0x05, 0xdc]);
+        let mut prefix = vec![OPTION_PREFIX_INFORMATION, 4, 64, 0xc0];
+        prefix.extend_from_slice(&86400u32.to_be_bytes());
+        prefix.extend_from_slice(&14400u32.to_be_bytes());
+        prefix.extend_from_slice(&[0; 4]);
+        prefix.extend_from_slice(&ipv6("2001:db8:1::"));
+        body.extend(prefix);
+
+        let infos = NdpInfos::parse(ROUTER_ADVERTISEMENT, &body).unwrap();
+
+        assert_eq!(infos.hop_limit, Some(64));
+        assert_eq!(infos.flags, vec!["Other"]);
+        assert_eq!(infos.router_lifetime, Some(1800));
+        assert_eq!(
+            infos.source_link_layer_address.as_deref(),
+            Some("02:00:00:00:00:fe")
+        );
+        assert_eq!(infos.mtu, Some(1500));
+        assert_eq!(
+            infos.prefixes,
+            vec![NdpPrefix {
+                prefix: "2001:db8:1::/64".to_string(),
+                on_link: true,
+                autonomous: true,
+                valid_lifetime: 86400,
+                preferred_lifetime: 14400,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_truncated_and_other_types() {
+        assert!(NdpInfos::parse(NEIGHBOR_SOLICITATION, &[0; 10]).is_none());
+        assert!(NdpInfos::parse(128, &[0; 10]).is_none());
+    }
+}
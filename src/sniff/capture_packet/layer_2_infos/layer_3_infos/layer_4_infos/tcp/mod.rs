@@ -0,0 +1,276 @@
+//! # Dissection de l'en-tête TCP
+//!
+//! Ce module extrait les drapeaux, les numéros de séquence et d'acquittement, la fenêtre et les options
+//! (MSS, facteur d'échelle de fenêtre, SACK, horodatages) des segments TCP.
+//!
+//! L'état de la connexion et le rôle de l'émetteur (client ou serveur) ne peuvent pas être déduits d'un
+//! segment isolé : ils sont renseignés après coup par le suivi des connexions
+//! ([`TcpConnectionTracker`](crate::sniff::capture_packet::tcp_connections::TcpConnectionTracker)).
+
+use std::fmt;
+
+use pnet::packet::tcp::TcpPacket;
+use pnet::packet::Packet;
+use serde::Serialize;
+
+/// Drapeaux TCP, dans l'ordre de l'octet 13 de l'en-tête.
+pub const FIN: u8 = 0x01;
+pub const SYN: u8 = 0x02;
+pub const RST: u8 = 0x04;
+pub const PSH: u8 = 0x08;
+pub const ACK: u8 = 0x10;
+pub const URG: u8 = 0x20;
+pub const ECE: u8 = 0x40;
+pub const CWR: u8 = 0x80;
+
+const FLAG_NAMES: [(u8, &str); 8] = [
+    (SYN, "SYN"),
+    (ACK, "ACK"),
+    (FIN, "FIN"),
+    (RST, "RST"),
+    (PSH, "PSH"),
+    (URG, "URG"),
+    (ECE, "ECE"),
+    (CWR, "CWR"),
+];
+
+/// Types d'options TCP.
+const OPTION_END_OF_LIST: u8 = 0;
+const OPTION_NO_OPERATION: u8 = 1;
+const OPTION_MSS: u8 = 2;
+const OPTION_WINDOW_SCALE: u8 = 3;
+const OPTION_SACK_PERMITTED: u8 = 4;
+const OPTION_SACK: u8 = 5;
+const OPTION_TIMESTAMPS: u8 = 8;
+
+/// Informations extraites de l'en-tête d'un segment TCP.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct TcpInfos {
+    /// Valeur brute des drapeaux.
+    pub flags: u8,
+    /// Noms des drapeaux positionnés (SYN, ACK, FIN...).
+    pub flag_names: Vec<String>,
+    /// Numéro de séquence.
+    pub sequence: u32,
+    /// Numéro d'acquittement.
+    pub acknowledgement: u32,
+    /// Taille de la fenêtre annoncée (avant application du facteur d'échelle).
+    pub window: u16,
+    /// Taille maximale de segment (option MSS).
+    pub mss: Option<u16>,
+    /// Facteur d'échelle de la fenêtre (option Window Scale).
+    pub window_scale: Option<u8>,
+    /// Option SACK Permitted présente.
+    pub sack_permitted: bool,
+    /// Blocs acquittés sélectivement (option SACK), sous forme (début, fin).
+    pub sack_blocks: Vec<(u32, u32)>,
+    /// Horodatage de l'émetteur (option Timestamps, TSval).
+    pub timestamp_value: Option<u32>,
+    /// Horodatage renvoyé en écho (option Timestamps, TSecr).
+    pub timestamp_echo_reply: Option<u32>,
+    /// Taille des données transportées par le segment.
+    pub payload_len: usize,
+    /// État de la connexion après ce segment, renseigné par le suivi des connexions.
+    pub connection_state: Option<TcpConnectionState>,
+    /// Rôle de l'émetteur du segment dans la connexion, s'il est connu.
+    pub role: Option<TcpRole>,
+}
+
+/// État d'une connexion TCP, déduit des drapeaux observés dans les deux sens.
+#[derive(Debug, Serialize, Clone, Copy, Eq, Hash, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TcpConnectionState {
+    /// SYN envoyé par le client, sans réponse pour l'instant.
+    SynSent,
+    /// SYN-ACK envoyé par le serveur.
+    SynAckReceived,
+    /// Connexion établie, ou déjà en cours au début de la capture.
+    Established,
+    /// FIN envoyé par un seul côté.
+    Closing,
+    /// FIN envoyé par les deux côtés.
+    Closed,
+    /// Connexion interrompue par un RST.
+    Reset,
+    /// SYN refusé par un RST du serveur.
+    Refused,
+}
+
+/// Rôle de l'émetteur d'un segment dans la connexion.
+#[derive(Debug, Serialize, Clone, Copy, Eq, Hash, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TcpRole {
+    /// L'émetteur a ouvert la connexion (SYN).
+    Client,
+    /// L'émetteur a accepté ou refusé la connexion.
+    Server,
+}
+
+impl fmt::Display for TcpConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            TcpConnectionState::SynSent => "SYN sent",
+            TcpConnectionState::SynAckReceived => "SYN-ACK",
+            TcpConnectionState::Established => "Established",
+            TcpConnectionState::Closing => "FIN",
+            TcpConnectionState::Closed => "Closed",
+            TcpConnectionState::Reset => "RST",
+            TcpConnectionState::Refused => "Refused",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+impl fmt::Display for TcpRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TcpRole::Client => write!(f, "Client"),
+            TcpRole::Server => write!(f, "Server"),
+        }
+    }
+}
+
+impl TcpInfos {
+    /// Décode l'en-tête d'un segment TCP et ses options.
+    pub fn from_packet(tcp_packet: &TcpPacket<'_>) -> TcpInfos {
+        let header = tcp_packet.packet();
+        // Les drapeaux sont lus directement dans l'en-tête : leur représentation varie selon les versions de pnet
+        let flags = header.get(13).copied().unwrap_or_default();
+        let header_len = (tcp_packet.get_data_offset() as usize * 4).clamp(20, header.len());
+
+        let mut infos = TcpInfos {
+            flags,
+            flag_names: FLAG_NAMES
+                .iter()
+                .filter(|(bit, _)| flags & bit != 0)
+                .map(|(_, name)| name.to_string())
+                .collect(),
+            sequence: tcp_packet.get_sequence(),
+            acknowledgement: tcp_packet.get_acknowledgement(),
+            window: tcp_packet.get_window(),
+            payload_len: header.len() - header_len,
+            ..Default::default()
+        };
+        infos.parse_options(&header[20..header_len]);
+        infos
+    }
+
+    /// Indique si tous les drapeaux donnés sont positionnés.
+    pub fn has_flags(&self, flags: u8) -> bool {
+        self.flags & flags == flags
+    }
+
+    fn parse_options(&mut self, mut options: &[u8]) {
+        while let Some(&kind) = options.first() {
+            match kind {
+                OPTION_END_OF_LIST => break,
+                OPTION_NO_OPERATION => {
+                    options = &options[1..];
+                    continue;
+                }
+                _ => {}
+            }
+
+            let len = match options.get(1) {
+                Some(&len) if len >= 2 && len as usize <= options.len() => len as usize,
+                _ => break,
+            };
+            let value = &options[2..len];
+            match (kind, value.len()) {
+                (OPTION_MSS, 2) => self.mss = Some(u16::from_be_bytes([value[0], value[1]])),
+                (OPTION_WINDOW_SCALE, 1) => self.window_scale = Some(value[0]),
+                (OPTION_SACK_PERMITTED, 0) => self.sack_permitted = true,
+                (OPTION_SACK, _) => {
+                    self.sack_blocks = value
+                        .chunks_exact(8)
+                        .map(|block| {
+                            (
+                                u32::from_be_bytes([block[0], block[1], block[2], block[3]]),
+                                u32::from_be_bytes([block[4], block[5], block[6], block[7]]),
+                            )
+                        })
+                        .collect()
+                }
+                (OPTION_TIMESTAMPS, 8) => {
+                    self.timestamp_value =
+                        Some(u32::from_be_bytes([value[0], value[1], value[2], value[3]]));
+                    self.timestamp_echo_reply =
+                        Some(u32::from_be_bytes([value[4], value[5], value[6], value[7]]));
+                }
+                _ => {}
+            }
+            options = &options[len..];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_packet_syn_with_options() {
+        let mut segment = vec![
+            0xc3, 0x50, 0x01, 0xbb, // ports 50000 -> 443
+            0x00, 0x00, 0x03, 0xe8, // séquence 1000
+            0x00, 0x00, 0x00, 0x00, // acquittement
+            0xa0, SYN, 0xfa, 0xf0, // data offset 10, SYN, fenêtre 64240
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        segment.extend_from_slice(&[OPTION_MSS, 4, 0x05, 0xb4]);
+        segment.extend_from_slice(&[OPTION_SACK_PERMITTED, 2]);
+        segment.extend_from_slice(&[OPTION_TIMESTAMPS, 10, 0, 0, 0, 7, 0, 0, 0, 0]);
+        segment.extend_from_slice(&[OPTION_NO_OPERATION, OPTION_WINDOW_SCALE, 3, 7]);
+        let tcp_packet = TcpPacket::new(&segment).unwrap();
+
+        let infos = TcpInfos::from_packet(&tcp_packet);
+
+        assert_eq!(infos.flag_names, vec!["SYN"]);
+        assert!(infos.has_flags(SYN));
+        assert!(!infos.has_flags(SYN | ACK));
+        assert_eq!(infos.sequence, 1000);
+        assert_eq!(infos.window, 64240);
+        assert_eq!(infos.mss, Some(1460));
+        assert!(infos.sack_permitted);
+        assert_eq!(infos.timestamp_value, Some(7));
+        assert_eq!(infos.timestamp_echo_reply, Some(0));
+        assert_eq!(infos.window_scale, Some(7));
+        assert_eq!(infos.payload_len, 0);
+    }
+
+    #[test]
+    fn test_from_packet_sack_blocks_and_payload() {
+        let mut segment = vec![
+            0x01,
+            0xbb,
+            0xc3,
+            0x50,
+            0,
+            0,
+            0,
+            1,
+            0,
+            0,
+            0,
+            2,
+            0x80,
+            ACK | PSH,
+            0x01,
+            0x00,
+            0,
+            0,
+            0,
+            0,
+        ];
+        segment.extend_from_slice(&[OPTION_NO_OPERATION, OPTION_NO_OPERATION, OPTION_SACK, 10]);
+        segment.extend_from_slice(&[0, 0, 0, 10, 0, 0, 0, 20]);
+        segment.extend_from_slice(b"hello");
+        let tcp_packet = TcpPacket::new(&segment).unwrap();
+
+        let infos = TcpInfos::from_packet(&tcp_packet);
+
+        assert_eq!(infos.flag_names, vec!["ACK", "PSH"]);
+        assert_eq!(infos.sack_blocks, vec![(10, 20)]);
+        assert_eq!(infos.payload_len, 5);
+    }
+}
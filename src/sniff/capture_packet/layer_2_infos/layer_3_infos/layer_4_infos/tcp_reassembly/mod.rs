@@ -0,0 +1,591 @@
+//! # Réassemblage des flux TCP
+//!
+//! Les protocoles applicatifs transportés par TCP (HTTP, TLS...) ne respectent pas les limites des segments :
+//! un message peut être découpé en plusieurs segments, arrivés dans le désordre ou retransmis. Ce module
+//! reconstitue, pour chaque sens de chaque connexion, le flux d'octets dans l'ordre des numéros de séquence
+//! et le transmet par blocs aux analyseurs applicatifs ([`StreamParser`]) enregistrés pour la connexion.
+//!
+//! - Les retransmissions et les parties de segments déjà transmises sont ignorées : en cas de chevauchement,
+//!   les premières données reçues sont conservées.
+//! - Les segments arrivés en avance sont conservés jusqu'à ce que le trou qui les précède soit comblé.
+//! - La mémoire est bornée par sens de connexion et par thread de capture : au-delà, le trou est abandonné
+//!   et les analyseurs en sont informés ([`StreamParser::on_gap`]). Les connexions inactives sont oubliées.
+//!
+//! Les connexions sans analyseur ne sont pas suivies. Chaque thread de capture possède son propre
+//! réassembleur, comme pour les fragments IP (voir [`Reassemblers`](super::super::Reassemblers)).
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use super::tcp::{ACK, FIN, RST, SYN};
+
+/// Délai d'inactivité au-delà duquel une connexion est oubliée.
+pub const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+/// Données en attente maximales pour un sens de connexion.
+pub const MAX_PENDING_BYTES_PER_DIRECTION: usize = 1024 * 1024;
+/// Données en attente maximales pour l'ensemble des connexions d'un thread de capture.
+pub const MAX_PENDING_BYTES: usize = 16 * 1024 * 1024;
+/// Nombre maximal de connexions suivies par thread de capture.
+const MAX_CONNECTIONS: usize = 16384;
+/// Intervalle minimal entre deux purges des connexions inactives.
+const EXPIRATION_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+/// Avance maximale d'un segment sur le flux pour être conservé ; au-delà, il est considéré comme invalide.
+const MAX_SEQUENCE_ADVANCE: i64 = 1 << 30;
+
+/// Identifie une connexion TCP par ses deux extrémités.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConnectionKey {
+    /// Extrémité qui a ouvert la connexion (ou, à défaut, celle dont le port est le plus élevé).
+    pub client: SocketAddr,
+    /// Extrémité qui a accepté la connexion.
+    pub server: SocketAddr,
+}
+
+/// Sens d'un bloc de données dans la connexion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamDirection {
+    ClientToServer,
+    ServerToClient,
+}
+
+/// Analyseur applicatif alimenté par le flux réassemblé d'une connexion.
+///
+/// Chaque connexion dispose de ses propres instances d'analyseurs, qui peuvent conserver un état entre
+/// deux blocs (message incomplet...). Les informations extraites sont écrites dans `output`, qui décrit
+/// le paquet dont le segment a permis de les obtenir.
+pub trait StreamParser<O> {
+    /// Reçoit le bloc suivant du flux, dans l'ordre, pour un sens de la connexion.
+    fn on_data(&mut self, direction: StreamDirection, data: &[u8], output: &mut O);
+
+    /// Signale que des données ont été perdues dans un sens : le prochain bloc n'est pas contigu au précédent.
+    fn on_gap(&mut self, _direction: StreamDirection) {}
+
+    /// Indique si l'analyseur attend encore des données ; une connexion dont tous les analyseurs
+    /// ont terminé reste suivie jusqu'à sa fermeture, mais ses données ne sont plus conservées.
+    fn wants_more(&self) -> bool {
+        true
+    }
+}
+
+/// Crée les analyseurs d'une nouvelle connexion, ou retourne `None` si la connexion ne le concerne pas.
+pub type ParserFactory<O> = fn(&ConnectionKey) -> Option<Box<dyn StreamParser<O>>>;
+
+/// Segment TCP reçu.
+#[derive(Debug, Clone, Copy)]
+pub struct Segment<'a> {
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub sequence: u32,
+    pub flags: u8,
+    pub payload: &'a [u8],
+}
+
+/// Flux d'un sens de connexion.
+#[derive(Debug, Default)]
+struct HalfStream {
+    /// Prochain numéro de séquence attendu, connu dès le premier segment reçu.
+    next_sequence: Option<u32>,
+    /// Nombre d'octets transmis aux analyseurs.
+    delivered: u64,
+    /// Segments arrivés en avance, indexés par leur position dans le flux.
+    pending: BTreeMap<u64, Vec<u8>>,
+    pending_bytes: usize,
+    fin: bool,
+}
+
+impl HalfStream {
+    /// Position dans le flux d'un numéro de séquence, relative aux données déjà transmises.
+    fn relative_offset(&self, sequence: u32) -> i64 {
+        match self.next_sequence {
+            Some(next_sequence) => sequence.wrapping_sub(next_sequence) as i32 as i64,
+            None => 0,
+        }
+    }
+
+    fn advance(&mut self, len: usize) {
+        self.delivered += len as u64;
+        self.next_sequence = self
+            .next_sequence
+            .map(|next_sequence| next_sequence.wrapping_add(len as u32));
+    }
+
+    /// Retire les segments en attente devenus contigus, tronqués des octets déjà transmis.
+    fn pop_contiguous(&mut self) -> Option<Vec<u8>> {
+        while let Some(entry) = self.pending.first_entry() {
+            if *entry.key() > self.delivered {
+                return None;
+            }
+            let start = *entry.key();
+            let data = entry.remove();
+            self.pending_bytes -= data.len();
+            let already_delivered = (self.delivered - start) as usize;
+            if already_delivered < data.len() {
+                return Some(data[already_delivered..].to_vec());
+            }
+        }
+        None
+    }
+
+    /// Abandonne le trou qui précède le premier segment en attente.
+    fn skip_gap(&mut self) {
+        if let Some(&start) = self.pending.keys().next() {
+            self.advance((start - self.delivered) as usize);
+        }
+    }
+}
+
+/// Connexion en cours de réassemblage.
+struct Connection<O> {
+    client_to_server: HalfStream,
+    server_to_client: HalfStream,
+    parsers: Vec<Box<dyn StreamParser<O>>>,
+    last_seen: Instant,
+}
+
+impl<O> Connection<O> {
+    fn half_stream(&mut self, direction: StreamDirection) -> &mut HalfStream {
+        match direction {
+            StreamDirection::ClientToServer => &mut self.client_to_server,
+            StreamDirection::ServerToClient => &mut self.server_to_client,
+        }
+    }
+
+    fn pending_bytes(&self) -> usize {
+        self.client_to_server.pending_bytes + self.server_to_client.pending_bytes
+    }
+
+    fn finished(&self) -> bool {
+        self.client_to_server.fin && self.server_to_client.fin
+    }
+
+    fn wants_more(&self) -> bool {
+        self.parsers.iter().any(|parser| parser.wants_more())
+    }
+
+    fn deliver(&mut self, direction: StreamDirection, data: &[u8], output: &mut O) {
+        if data.is_empty() {
+            return;
+        }
+        for parser in self.parsers.iter_mut().filter(|parser| parser.wants_more()) {
+            parser.on_data(direction, data, output);
+        }
+    }
+
+    /// Transmet les segments en attente devenus contigus.
+    fn drain(&mut self, direction: StreamDirection, output: &mut O) {
+        while let Some(data) = self.half_stream(direction).pop_contiguous() {
+            self.half_stream(direction).advance(data.len());
+            self.deliver(direction, &data, output);
+        }
+    }
+
+    fn gap(&mut self, direction: StreamDirection) {
+        for parser in self.parsers.iter_mut() {
+            parser.on_gap(direction);
+        }
+    }
+}
+
+/// Réassembleur de flux TCP, avec délai d'expiration et limites mémoire.
+pub struct TcpReassembler<O: 'static> {
+    connections: HashMap<ConnectionKey, Connection<O>>,
+    factories: &'static [ParserFactory<O>],
+    pending_bytes: usize,
+    max_pending_bytes: usize,
+    max_pending_bytes_per_direction: usize,
+    last_sweep: Option<Instant>,
+}
+
+impl<O: 'static> TcpReassembler<O> {
+    pub fn new(factories: &'static [ParserFactory<O>]) -> Self {
+        TcpReassembler {
+            connections: HashMap::new(),
+            factories,
+            pending_bytes: 0,
+            max_pending_bytes: MAX_PENDING_BYTES,
+            max_pending_bytes_per_direction: MAX_PENDING_BYTES_PER_DIRECTION,
+            last_sweep: None,
+        }
+    }
+
+    /// Ajoute un segment au flux de sa connexion et transmet aux analyseurs les données devenues contiguës.
+    pub fn add(&mut self, segment: Segment<'_>, output: &mut O, now: Instant) {
+        self.expire(now);
+
+        let (key, direction) = match self.lookup(&segment) {
+            Some(found) => found,
+            None => match self.open(&segment, now) {
+                Some(opened) => opened,
+                None => return,
+            },
+        };
+
+        if segment.flags & RST != 0 {
+            self.remove(&key);
+            return;
+        }
+
+        let max_per_direction = self.max_pending_bytes_per_direction;
+        let memory_available = self.pending_bytes + segment.payload.len() <= self.max_pending_bytes;
+        let connection = match self.connections.get_mut(&key) {
+            Some(connection) => connection,
+            None => return,
+        };
+        connection.last_seen = now;
+        let pending_before = connection.pending_bytes();
+        if !connection.wants_more() {
+            // Les analyseurs ont terminé : les données en attente sont libérées
+            for half_stream in [
+                &mut connection.client_to_server,
+                &mut connection.server_to_client,
+            ] {
+                half_stream.pending.clear();
+                half_stream.pending_bytes = 0;
+            }
+            self.pending_bytes -= pending_before;
+            connection.half_stream(direction).fin |= segment.flags & FIN != 0;
+            if connection.finished() {
+                self.remove(&key);
+            }
+            return;
+        }
+
+        let half_stream = connection.half_stream(direction);
+        // Le SYN consomme un numéro de séquence : les données commencent après
+        let data_sequence = if segment.flags & SYN != 0 {
+            if half_stream.delivered == 0 {
+                half_stream.next_sequence = Some(segment.sequence.wrapping_add(1));
+            }
+            segment.sequence.wrapping_add(1)
+        } else {
+            if half_stream.next_sequence.is_none() {
+                // Connexion en cours au début de la capture
+                half_stream.next_sequence = Some(segment.sequence);
+            }
+            segment.sequence
+        };
+
+        let offset = half_stream.relative_offset(data_sequence);
+        let in_window = offset > 0 && offset < MAX_SEQUENCE_ADVANCE && !segment.payload.is_empty();
+        if in_window
+            && (half_stream.pending_bytes + segment.payload.len() > max_per_direction
+                || !memory_available)
+        {
+            // Les trous ne seront pas comblés à temps : les données manquantes sont abandonnées
+            while !connection.half_stream(direction).pending.is_empty() {
+                connection.half_stream(direction).skip_gap();
+                connection.gap(direction);
+                connection.drain(direction, output);
+            }
+            let half_stream = connection.half_stream(direction);
+            let offset = half_stream.relative_offset(data_sequence);
+            if offset > 0 {
+                half_stream.advance(offset as usize);
+                connection.gap(direction);
+            }
+        }
+
+        let half_stream = connection.half_stream(direction);
+        let offset = half_stream.relative_offset(data_sequence);
+        let end = offset + segment.payload.len() as i64;
+        if offset <= 0 && end > 0 {
+            let data = &segment.payload[(-offset) as usize..];
+            half_stream.advance(data.len());
+            connection.deliver(direction, data, output);
+        } else if in_window {
+            let start = half_stream.delivered + offset as u64;
+            let previous_len = half_stream.pending.get(&start).map_or(0, Vec::len);
+            if segment.payload.len() > previous_len {
+                half_stream.pending_bytes += segment.payload.len() - previous_len;
+                half_stream.pending.insert(start, segment.payload.to_vec());
+            }
+        }
+        connection.drain(direction, output);
+
+        if segment.flags & FIN != 0 {
+            connection.half_stream(direction).fin = true;
+        }
+
+        let pending_after = connection.pending_bytes();
+        self.pending_bytes = self.pending_bytes + pending_after - pending_before;
+        if connection.finished() {
+            self.remove(&key);
+        }
+    }
+
+    /// Retrouve la connexion d'un segment et le sens dans lequel il circule.
+    fn lookup(&self, segment: &Segment<'_>) -> Option<(ConnectionKey, StreamDirection)> {
+        let forward = ConnectionKey {
+            client: segment.source,
+            server: segment.destination,
+        };
+        if self.connections.contains_key(&forward) {
+            return Some((forward, StreamDirection::ClientToServer));
+        }
+        let backward = ConnectionKey {
+            client: segment.destination,
+            server: segment.source,
+        };
+        if self.connections.contains_key(&backward) {
+            return Some((backward, StreamDirection::ServerToClient));
+        }
+        None
+    }
+
+    /// Commence le suivi d'une connexion si au moins un analyseur la prend en charge.
+    fn open(
+        &mut self,
+        segment: &Segment<'_>,
+        now: Instant,
+    ) -> Option<(ConnectionKey, StreamDirection)> {
+        if segment.flags & RST != 0 {
+            return None;
+        }
+
+        // Le client est l'émetteur du SYN ; pour une connexion déjà ouverte, celui dont le port est le plus élevé
+        let from_client = if segment.flags & SYN != 0 {
+            segment.flags & ACK == 0
+        } else {
+            segment.source.port() >= segment.destination.port()
+        };
+        let (key, direction) = if from_client {
+            (
+                ConnectionKey {
+                    client: segment.source,
+                    server: segment.destination,
+                },
+                StreamDirection::ClientToServer,
+            )
+        } else {
+            (
+                ConnectionKey {
+                    client: segment.destination,
+                    server: segment.source,
+                },
+                StreamDirection::ServerToClient,
+            )
+        };
+
+        let parsers: Vec<Box<dyn StreamParser<O>>> = self
+            .factories
+            .iter()
+            .filter_map(|factory| factory(&key))
+            .collect();
+        if parsers.is_empty() {
+            return None;
+        }
+
+        if self.connections.len() >= MAX_CONNECTIONS {
+            let oldest = self
+                .connections
+                .iter()
+                .min_by_key(|(_, connection)| connection.last_seen)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                self.remove(&oldest);
+            }
+        }
+        self.connections.insert(
+            key,
+            Connection {
+                client_to_server: HalfStream::default(),
+                server_to_client: HalfStream::default(),
+                parsers,
+                last_seen: now,
+            },
+        );
+        Some((key, direction))
+    }
+
+    fn remove(&mut self, key: &ConnectionKey) {
+        if let Some(connection) = self.connections.remove(key) {
+            self.pending_bytes -= connection.pending_bytes();
+        }
+    }
+
+    /// Oublie les connexions inactives depuis plus de [`STREAM_IDLE_TIMEOUT`].
+    fn expire(&mut self, now: Instant) {
+        if self
+            .last_sweep
+            .is_some_and(|last_sweep| now.duration_since(last_sweep) < EXPIRATION_SWEEP_INTERVAL)
+        {
+            return;
+        }
+        self.last_sweep = Some(now);
+
+        let mut expired_bytes = 0;
+        self.connections.retain(|_, connection| {
+            let alive = now.duration_since(connection.last_seen) < STREAM_IDLE_TIMEOUT;
+            if !alive {
+                expired_bytes += connection.pending_bytes();
+            }
+            alive
+        });
+        self.pending_bytes -= expired_bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Analyseur de test qui conserve les blocs reçus.
+    struct Recorder;
+
+    impl StreamParser<Vec<(StreamDirection, Vec<u8>)>> for Recorder {
+        fn on_data(
+            &mut self,
+            direction: StreamDirection,
+            data: &[u8],
+            output: &mut Vec<(StreamDirection, Vec<u8>)>,
+        ) {
+            output.push((direction, data.to_vec()));
+        }
+    }
+
+    type Output = Vec<(StreamDirection, Vec<u8>)>;
+
+    fn recorder(_key: &ConnectionKey) -> Option<Box<dyn StreamParser<Output>>> {
+        Some(Box::new(Recorder))
+    }
+
+    const CLIENT: &str = "10.0.0.1:50000";
+    const SERVER: &str = "10.0.0.2:80";
+
+    fn segment<'a>(
+        source: &str,
+        destination: &str,
+        sequence: u32,
+        flags: u8,
+        payload: &'a [u8],
+    ) -> Segment<'a> {
+        Segment {
+            source: source.parse().unwrap(),
+            destination: destination.parse().unwrap(),
+            sequence,
+            flags,
+            payload,
+        }
+    }
+
+    fn client_data(output: &Output) -> Vec<u8> {
+        output
+            .iter()
+            .filter(|(direction, _)| *direction == StreamDirection::ClientToServer)
+            .flat_map(|(_, data)| data.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_in_order_and_out_of_order_segments() {
+        let mut reassembler = TcpReassembler::new(&[recorder as ParserFactory<Output>]);
+        let now = Instant::now();
+        let mut output = Vec::new();
+
+        reassembler.add(segment(CLIENT, SERVER, 99, SYN, b""), &mut output, now);
+        reassembler.add(
+            segment(SERVER, CLIENT, 499, SYN | ACK, b""),
+            &mut output,
+            now,
+        );
+        reassembler.add(
+            segment(CLIENT, SERVER, 106, ACK, b"world"),
+            &mut output,
+            now,
+        );
+        assert!(output.is_empty());
+
+        reassembler.add(
+            segment(CLIENT, SERVER, 100, ACK, b"hello "),
+            &mut output,
+            now,
+        );
+        assert_eq!(client_data(&output), b"hello world");
+
+        reassembler.add(segment(SERVER, CLIENT, 500, ACK, b"ok"), &mut output, now);
+        assert_eq!(
+            output.last(),
+            Some(&(StreamDirection::ServerToClient, b"ok".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_retransmission_and_overlap() {
+        let mut reassembler = TcpReassembler::new(&[recorder as ParserFactory<Output>]);
+        let now = Instant::now();
+        let mut output = Vec::new();
+
+        reassembler.add(
+            segment(CLIENT, SERVER, 1000, ACK, b"abcd"),
+            &mut output,
+            now,
+        );
+        reassembler.add(
+            segment(CLIENT, SERVER, 1000, ACK, b"abcd"),
+            &mut output,
+            now,
+        );
+        reassembler.add(
+            segment(CLIENT, SERVER, 1002, ACK, b"XXef"),
+            &mut output,
+            now,
+        );
+
+        assert_eq!(client_data(&output), b"abcdef");
+    }
+
+    #[test]
+    fn test_gap_is_skipped_when_buffer_is_full() {
+        let mut reassembler = TcpReassembler::new(&[recorder as ParserFactory<Output>]);
+        reassembler.max_pending_bytes_per_direction = 4;
+        let now = Instant::now();
+        let mut output = Vec::new();
+
+        reassembler.add(segment(CLIENT, SERVER, 0, ACK, b"ab"), &mut output, now);
+        reassembler.add(segment(CLIENT, SERVER, 10, ACK, b"cdef"), &mut output, now);
+        reassembler.add(segment(CLIENT, SERVER, 14, ACK, b"gh"), &mut output, now);
+
+        assert_eq!(client_data(&output), b"abcdefgh");
+        assert_eq!(reassembler.pending_bytes, 0);
+    }
+
+    #[test]
+    fn test_connection_lifecycle() {
+        let mut reassembler = TcpReassembler::new(&[recorder as ParserFactory<Output>]);
+        let now = Instant::now();
+        let mut output = Vec::new();
+
+        reassembler.add(segment(CLIENT, SERVER, 0, ACK, b"a"), &mut output, now);
+        reassembler.add(segment(CLIENT, SERVER, 5, ACK, b"b"), &mut output, now);
+        assert_eq!(reassembler.connections.len(), 1);
+        assert_eq!(reassembler.pending_bytes, 1);
+
+        reassembler.add(segment(SERVER, CLIENT, 0, RST, b""), &mut output, now);
+        assert!(reassembler.connections.is_empty());
+        assert_eq!(reassembler.pending_bytes, 0);
+
+        reassembler.add(segment(CLIENT, SERVER, 0, ACK, b"a"), &mut output, now);
+        reassembler.add(segment(CLIENT, SERVER, 5, ACK, b"b"), &mut output, now);
+        reassembler.expire(now + STREAM_IDLE_TIMEOUT);
+        assert!(reassembler.connections.is_empty());
+        assert_eq!(reassembler.pending_bytes, 0);
+    }
+
+    #[test]
+    fn test_connections_without_parser_are_not_tracked() {
+        let mut reassembler: TcpReassembler<Output> = TcpReassembler::new(&[]);
+        let mut output = Vec::new();
+
+        reassembler.add(
+            segment(CLIENT, SERVER, 0, ACK, b"a"),
+            &mut output,
+            Instant::now(),
+        );
+
+        assert!(reassembler.connections.is_empty());
+        assert!(output.is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+//! # Dissection LLDP
+//!
+//! Le protocole LLDP (IEEE 802.1AB) est émis périodiquement par les commutateurs et de nombreux équipements :
+//! c'est la source passive la plus fiable pour connaître le nom d'un équipement et le port auquel on est relié.
+//!
+//! Ce module décode les TLV d'une LLDPDU : identifiant de châssis, identifiant de port, TTL, description du port,
+//! nom et description du système, capacités et adresse de gestion.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use pnet::util::MacAddr;
+use serde::Serialize;
+
+/// Types de TLV LLDP pris en charge.
+const TLV_END: u8 = 0;
+const TLV_CHASSIS_ID: u8 = 1;
+const TLV_PORT_ID: u8 = 2;
+const TLV_TTL: u8 = 3;
+const TLV_PORT_DESCRIPTION: u8 = 4;
+const TLV_SYSTEM_NAME: u8 = 5;
+const TLV_SYSTEM_DESCRIPTION: u8 = 6;
+const TLV_SYSTEM_CAPABILITIES: u8 = 7;
+const TLV_MANAGEMENT_ADDRESS: u8 = 8;
+
+/// Noms des capacités LLDP, dans l'ordre des bits du champ de capacités.
+const CAPABILITIES: [&str; 11] = [
+    "Other",
+    "Repeater",
+    "Bridge",
+    "WLAN Access Point",
+    "Router",
+    "Telephone",
+    "DOCSIS",
+    "Station Only",
+    "C-VLAN",
+    "S-VLAN",
+    "TPMR",
+];
+
+/// Informations extraites d'une trame LLDP.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct LldpInfos {
+    /// Identifiant du châssis de l'équipement émetteur (souvent son adresse MAC).
+    pub chassis_id: Option<String>,
+    /// Identifiant du port émetteur (nom d'interface, adresse MAC...).
+    pub port_id: Option<String>,
+    /// Durée de validité des informations, en secondes.
+    pub ttl: Option<u16>,
+    /// Description du port émetteur.
+    pub port_description: Option<String>,
+    /// Nom de l'équipement.
+    pub system_name: Option<String>,
+    /// Description de l'équipement (modèle, version logicielle...).
+    pub system_description: Option<String>,
+    /// Capacités supportées par l'équipement (Bridge, Router...).
+    pub system_capabilities: Vec<String>,
+    /// Capacités activées sur l'équipement.
+    pub enabled_capabilities: Vec<String>,
+    /// Adresse de gestion annoncée par l'équipement.
+    pub management_address: Option<String>,
+}
+
+impl LldpInfos {
+    /// Décode les TLV d'une LLDPDU (charge utile de la trame Ethernet).
+    ///
+    /// Retourne `None` si la trame ne commence pas par les TLV obligatoires (châssis, port, TTL).
+    pub fn parse(data: &[u8]) -> Option<LldpInfos> {
+        let mut infos = LldpInfos::default();
+        let mut offset = 0;
+
+        while offset + 2 <= data.len() {
+            let header = u16::from_be_bytes([data[offset], data[offset + 1]]);
+            let tlv_type = (header >> 9) as u8;
+            let tlv_len = (header & 0x01ff) as usize;
+            offset += 2;
+
+            if tlv_type == TLV_END {
+                break;
+            }
+            let value = data.get(offset..offset + tlv_len)?;
+            offset += tlv_len;
+
+            match tlv_type {
+                TLV_CHASSIS_ID => infos.chassis_id = parse_id(value, 4, 5),
+                TLV_PORT_ID => infos.port_id = parse_id(value, 3, 4),
+                TLV_TTL if value.len() >= 2 => {
+                    infos.ttl = Some(u16::from_be_bytes([value[0], value[1]]))
+                }
+                TLV_PORT_DESCRIPTION => infos.port_description = Some(to_text(value)),
+                TLV_SYSTEM_NAME => infos.system_name = Some(to_text(value)),
+                TLV_SYSTEM_DESCRIPTION => infos.system_description = Some(to_text(value)),
+                TLV_SYSTEM_CAPABILITIES if value.len() >= 4 => {
+                    infos.system_capabilities =
+                        capability_names(u16::from_be_bytes([value[0], value[1]]));
+                    infos.enabled_capabilities =
+                        capability_names(u16::from_be_bytes([value[2], value[3]]));
+                }
+                TLV_MANAGEMENT_ADDRESS if infos.management_address.is_none() => {
+                    infos.management_address = parse_management_address(value)
+                }
+                // TLV propres à un constructeur (type 127) et autres TLV ignorés
+                _ => {}
+            }
+        }
+
+        if infos.chassis_id.is_none() || infos.port_id.is_none() || infos.ttl.is_none() {
+            return None;
+        }
+        Some(infos)
+    }
+}
+
+/// Décode un identifiant de châssis ou de port selon son sous-type.
+///
+/// Les sous-types "adresse MAC" et "adresse réseau" diffèrent entre les deux TLV ; ils sont donc passés en paramètre.
+fn parse_id(value: &[u8], mac_subtype: u8, network_subtype: u8) -> Option<String> {
+    let (&subtype, id) = value.split_first()?;
+    match subtype {
+        s if s == mac_subtype && id.len() == 6 => Some(to_mac(id)),
+        s if s == network_subtype => parse_network_address(id),
+        _ => Some(to_text(id)),
+    }
+}
+
+/// Décode le TLV d'adresse de gestion : longueur, sous-type IANA de l'adresse, puis l'adresse.
+fn parse_management_address(value: &[u8]) -> Option<String> {
+    let address_len = *value.first()? as usize;
+    let address = value.get(1..1 + address_len)?;
+    parse_network_address(address)
+}
+
+/// Décode une adresse réseau précédée de sa famille IANA (1 pour IPv4, 2 pour IPv6, 6 pour 802).
+fn parse_network_address(value: &[u8]) -> Option<String> {
+    let (&family, address) = value.split_first()?;
+    match (family, address.len()) {
+        (1, 4) => Some(Ipv4Addr::new(address[0], address[1], address[2], address[3]).to_string()),
+        (2, 16) => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(address);
+            Some(Ipv6Addr::from(octets).to_string())
+        }
+        (6, 6) => Some(to_mac(address)),
+        _ => Some(to_hex(address)),
+    }
+}
+
+/// Retourne les noms des capacités dont le bit est positionné.
+fn capability_names(bits: u16) -> Vec<String> {
+    CAPABILITIES
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| bits & (1 << bit) != 0)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+fn to_mac(octets: &[u8]) -> String {
+    MacAddr::new(
+        octets[0], octets[1], octets[2], octets[3], octets[4], octets[5],
+    )
+    .to_string()
+}
+
+/// Convertit une valeur textuelle, ou son écriture hexadécimale si elle n'est pas encodée en UTF-8.
+///
+/// Les descriptions sur plusieurs lignes (Cisco, Juniper) conservent leurs retours à la ligne, les octets nuls
+/// terminaux sont retirés et les autres caractères de contrôle sont remplacés par des espaces.
+fn to_text(value: &[u8]) -> String {
+    match std::str::from_utf8(value) {
+        Ok(text) => text
+            .trim_end_matches('\0')
+            .replace("\r\n", "\n")
+            .chars()
+            .map(|c| match c {
+                '\n' | '\t' => c,
+                c if c.is_control() => ' ',
+                c => c,
+            })
+            .collect::<String>()
+            .trim_end()
+            .to_string(),
+        Err(_) => to_hex(value),
+    }
+}
+
+fn to_hex(value: &[u8]) -> String {
+    value
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tlv(tlv_type: u8, value: &[u8]) -> Vec<u8> {
+        let header = ((tlv_type as u16) << 9) | value.len() as u16;
+        let mut bytes = header.to_be_bytes().to_vec();
+        bytes.extend_from_slice(value);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_lldp() {
+        let mut data = Vec::new();
+        data.extend(tlv(
+            TLV_CHASSIS_ID,
+            &[4, 0x00, 0x1b, 0x54, 0xaa, 0xbb, 0xcc],
+        ));
+        data.extend(tlv(TLV_PORT_ID, b"\x05Gi1/0/24"));
+        data.extend(tlv(TLV_TTL, &[0, 120]));
+        data.extend(tlv(TLV_PORT_DESCRIPTION, b"Uplink atelier"));
+        data.extend(tlv(TLV_SYSTEM_NAME, b"sw-usine-01"));
+        data.extend(tlv(TLV_SYSTEM_DESCRIPTION, b"Cisco IOS 15.2"));
+        data.extend(tlv(TLV_SYSTEM_CAPABILITIES, &[0x00, 0x14, 0x00, 0x04]));
+        data.extend(tlv(
+            TLV_MANAGEMENT_ADDRESS,
+            &[5, 1, 10, 0, 0, 1, 2, 0, 0, 0, 1, 0],
+        ));
+        data.extend(tlv(TLV_END, &[]));
+
+        let infos = LldpInfos::parse(&data).unwrap();
+
+        assert_eq!(infos.chassis_id.as_deref(), Some("00:1b:54:aa:bb:cc"));
+        assert_eq!(infos.port_id.as_deref(), Some("Gi1/0/24"));
+        assert_eq!(infos.ttl, Some(120));
+        assert_eq!(infos.port_description.as_deref(), Some("Uplink atelier"));
+        assert_eq!(infos.system_name.as_deref(), Some("sw-usine-01"));
+        assert_eq!(infos.system_description.as_deref(), Some("Cisco IOS 15.2"));
+        assert_eq!(infos.system_capabilities, vec!["Bridge", "Router"]);
+        assert_eq!(infos.enabled_capabilities, vec!["Bridge"]);
+        assert_eq!(infos.management_address.as_deref(), Some("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_to_text() {
+        let description = b"Cisco IOS Software, C2960 Software\r\nTechnical Support:\thttp://www.cisco.com/techsupport\r\n";
+        assert_eq!(
+            to_text(description),
+            "Cisco IOS Software, C2960 Software\nTechnical Support:\thttp://www.cisco.com/techsupport"
+        );
+        assert_eq!(to_text(b"sw-usine-01\0\0"), "sw-usine-01");
+        assert_eq!(to_text(&[0xff, 0xfe]), "ff:fe");
+    }
+
+    #[test]
+    fn test_parse_lldp_truncated() {
+        let mut data = tlv(TLV_CHASSIS_ID, &[7, b'a', b'b']);
+        data.extend(tlv(TLV_PORT_ID, b"\x07p1"));
+        // TTL annoncé sur 2 octets mais trame tronquée
+        data.extend_from_slice(&[0x06, 0x02, 0x00]);
+
+        assert!(LldpInfos::parse(&data).is_none());
+    }
+}
@@ -7,12 +7,13 @@
 //!
 //! ```rust
 //! use pnet::packet::ethernet::EthernetPacket;
-//! use layer_3_infos::get_layer_3_infos;
+//! use layer_3_infos::{get_layer_3_infos, Reassemblers};
 //!
 //! // Exemple d'utilisation de la fonction get_layer_3_infos avec un paquet Ethernet
+//! let mut reassemblers = Reassemblers::default();
 //! let ethernet_packet_data: &[u8] = &[/* données du paquet Ethernet */];
 //! if let Some(ethernet_packet) = EthernetPacket::new(ethernet_packet_data) {
-//!     let layer_3_infos = get_layer_3_infos(&ethernet_packet);
+//!     let layer_3_infos = get_layer_3_infos(&ethernet_packet, &mut reassemblers);
 //!     println!("Layer 3 Infos: {:?}", layer_3_infos);
 //! }
 //! ```
@@ -20,6 +21,7 @@
 //! ## Structures
 //!
 //! - [`Layer3Infos`](struct.Layer3Infos.html): Représente les informations extraites de la couche 3 d'un paquet réseau.
+//! - [`Reassemblers`](struct.Reassemblers.html): État de réassemblage conservé d'une trame à l'autre par un thread de capture.
 //!
 //! ## Traits
 //!
@@ -33,6 +35,9 @@
 //!     - [`ArpHandler`](struct.ArpHandler.html)
 //!     - [`VlanHandler`](struct.VlanHandler.html)
 //!     - [`PppoeDiscoveryHandler`](struct.PppoeDiscoveryHandler.html)
+//!     - [`PppoeSessionHandler`](struct.PppoeSessionHandler.html)
+//!     - [`LldpHandler`](struct.LldpHandler.html)
+//!     - [`MplsHandler`](struct.MplsHandler.html)
 //!
 //! ## Handlers de Paquets
 //!
@@ -42,6 +47,7 @@
 //! ## Fonctions
 //!
 //! - [`get_layer_3_infos`](fn.get_layer_3_infos.html): Fonction d'entrée pour traiter un paquet Ethernet et extraire les informations de la couche 3.
+//! - [`get_layer_3_infos_by_ethertype`](fn.get_layer_3_infos_by_ethertype.html): Extrait les informations de la couche 3 d'une charge utile dont l'EtherType est connu.
 //!
 //! ## Handlers de Paquets
 //!
@@ -51,17 +57,40 @@
 use pnet::packet::{
     arp::ArpPacket,
     ethernet::{
+        EtherType,
         EtherTypes::{self},
         EthernetPacket,
     },
-    ipv4::Ipv4Packet,
+    ip::{IpNextHeaderProtocol, IpNextHeaderProtocols},
+    ipv4::{Ipv4Flags, Ipv4Packet},
     ipv6::Ipv6Packet,
-    vlan::VlanPacket,
     Packet,
 };
 
-mod layer_4_infos;
-use layer_4_infos::{get_layer_4_infos, Layer4Infos};
+pub(crate) mod arp;
+pub(crate) mod fragments;
+pub(crate) mod ipv6_extensions;
+pub(crate) mod layer_4_infos;
+pub(crate) mod lldp;
+pub(crate) mod mpls;
+pub(crate) mod pppoe;
+use std::{collections::BTreeSet, net::IpAddr, time::Instant};
+
+use super::vlan::{is_vlan_ethertype, strip_vlan_tags};
+
+use arp::ArpInfos;
+use fragments::{Fragment, FragmentKey, FragmentReassembler};
+use ipv6_extensions::walk_extension_headers;
+use layer_4_infos::{
+    get_layer_4_infos, get_layer_4_ports,
+    layer_7_infos::{Layer7Infos, STREAM_PARSERS},
+    tcp_reassembly::TcpReassembler,
+    Layer4Infos,
+};
+use lldp::LldpInfos;
+use log::debug;
+use mpls::{MplsInfos, MplsPayload};
+use pppoe::{PppoeInfos, PPP_IPV4, PPP_IPV6};
 use serde::Serialize;
 
 /// Représente les informations extraites de la couche 3 d'un paquet réseau.
@@ -71,6 +100,80 @@ pub struct Layer3Infos {
     pub ip_destination: Option<String>,
     pub l_4_protocol: Option<String>,
     pub layer_4_infos: Layer4Infos,
+    /// Détails ARP (opération, adresses matérielles, classification), pour les paquets ARP.
+    pub arp: Option<ArpInfos>,
+    /// Informations LLDP (nom de l'équipement, port...), pour les trames LLDP.
+    pub lldp: Option<LldpInfos>,
+    /// Informations PPPoE (code, session, tags), pour les trames PPPoE.
+    pub pppoe: Option<PppoeInfos>,
+    /// Pile d'étiquettes MPLS, pour les trames MPLS.
+    pub mpls: Option<MplsInfos>,
+    /// En-têtes d'extension IPv6 présents avant la couche 4 (Hop-by-Hop, Routing, Fragment...).
+    pub ipv6_extension_headers: Vec<String>,
+    /// Anomalies détectées (fragments qui se chevauchent, fragments trop courts...).
+    pub anomalies: BTreeSet<String>,
+    /// Nom DNS de l'adresse source, appris à partir des réponses DNS observées.
+    pub hostname_source: Option<String>,
+    /// Nom DNS de l'adresse de destination, appris à partir des réponses DNS observées.
+    pub hostname_destination: Option<String>,
+}
+
+impl Layer3Infos {
+    /// Indique si deux paquets appartiennent au même flux.
+    ///
+    /// Les informations de couche 3 sont comparées intégralement, et celles de couche 4 avec
+    /// [`Layer4Infos::same_flow`], qui ignore les champs propres à chaque paquet. Les en-têtes d'extension IPv6
+    /// et les anomalies peuvent varier d'un paquet à l'autre d'un même flux (fragmentation) et ne sont pas comparés,
+    /// pas plus que les noms DNS, appris au fil de la capture.
+    pub fn same_flow(&self, other: &Layer3Infos) -> bool {
+        let Layer3Infos {
+            ip_source,
+            ip_destination,
+            l_4_protocol,
+            layer_4_infos,
+            arp,
+            lldp,
+            pppoe,
+            mpls,
+            ipv6_extension_headers: _,
+            anomalies: _,
+            hostname_source: _,
+            hostname_destination: _,
+        } = self;
+
+        *ip_source == other.ip_source
+            && *ip_destination == other.ip_destination
+            && *l_4_protocol == other.l_4_protocol
+            && layer_4_infos.same_flow(&other.layer_4_infos)
+            && *arp == other.arp
+            && *lldp == other.lldp
+            && *pppoe == other.pppoe
+            && match (mpls, &other.mpls) {
+                (Some(mpls), Some(other_mpls)) => mpls.same_path(other_mpls),
+                (mpls, other_mpls) => mpls.is_none() && other_mpls.is_none(),
+            }
+    }
+}
+
+/// État de réassemblage conservé d'une trame à l'autre.
+///
+/// Chaque thread de capture possède ses propres réassembleurs, créés au démarrage de la capture et transmis
+/// à la dissection de chaque trame : deux captures successives ne partagent aucun fragment ni flux TCP
+/// en attente.
+pub struct Reassemblers {
+    /// Fragments IPv4 et IPv6 en attente de réassemblage.
+    pub fragments: FragmentReassembler,
+    /// Flux TCP en cours de réassemblage, transmis aux analyseurs de la couche 7.
+    pub tcp_streams: TcpReassembler<Layer7Infos>,
+}
+
+impl Default for Reassemblers {
+    fn default() -> Self {
+        Reassemblers {
+            fragments: FragmentReassembler::default(),
+            tcp_streams: TcpReassembler::new(STREAM_PARSERS),
+        }
+    }
 }
 
 // Définitions des handlers pour chaque type de paquet pris en charge...
@@ -79,16 +182,20 @@ struct Ipv6Handler;
 struct ArpHandler;
 struct VlanHandler;
 struct PppoeDiscoveryHandler;
+struct PppoeSessionHandler;
 struct LldpHandler;
+struct MplsHandler;
 
 /// Trait définissant la fonctionnalité pour extraire les informations de la couche 3.
 trait HandlePacket {
-    fn get_layer_3(data: &[u8]) -> Layer3Infos;
+    fn get_layer_3(data: &[u8], reassemblers: &mut Reassemblers) -> Layer3Infos;
 }
 
 impl HandlePacket for Ipv4Handler {
     /// Traite les paquets IPv4 pour extraire les informations de la couche 3 et 4.
-    fn get_layer_3(data: &[u8]) -> Layer3Infos {
+    ///
+    /// Les fragments passent par le réassemblage avant d'être disséqués.
+    fn get_layer_3(data: &[u8], reassemblers: &mut Reassemblers) -> Layer3Infos {
         if let Some(ipv4_packet) = Ipv4Packet::new(data) {
             // //println!(
             //     "Layer 3: IPv4 packet: source {} destination {} => {} {}",
@@ -102,7 +209,7 @@ impl HandlePacket for Ipv4Handler {
                 ip_source: Some(ipv4_packet.get_source().to_string()),
                 ip_destination: Some(ipv4_packet.get_destination().to_string()),
                 l_4_protocol: Some(ipv4_packet.get_next_level_protocol().to_string()),
-                layer_4_infos: get_layer_4_infos(ipv4_packet.get_next_level_protocol(), data),
+                ..ipv4_layer_4(&ipv4_packet, data, reassemblers)
             }
         } else {
             Default::default()
@@ -110,9 +217,104 @@ impl HandlePacket for Ipv4Handler {
     }
 }
 
+/// Retourne les données de la couche 4 d'un paquet IPv4, situées après l'en-tête et ses options.
+///
+/// La longueur de l'en-tête est donnée par l'IHL (en mots de 32 bits) et la fin des données par la longueur totale,
+/// bornée à la taille capturée. Une longueur totale nulle ou incohérente, fréquente avec le TSO, est ignorée.
+/// Retourne `None` pour un en-tête invalide.
+pub(crate) fn ipv4_payload<'a>(ipv4_packet: &Ipv4Packet<'_>, data: &'a [u8]) -> Option<&'a [u8]> {
+    let header_len = ipv4_packet.get_header_length() as usize * 4;
+    if header_len < 20 || header_len > data.len() {
+        return None;
+    }
+    let total_len = ipv4_packet.get_total_length() as usize;
+    let end = if total_len < header_len {
+        data.len()
+    } else {
+        total_len.min(data.len())
+    };
+    Some(&data[header_len..end])
+}
+
+/// Dissèque la couche 4 d'un paquet IPv4, en passant par le réassemblage si le paquet est un fragment.
+fn ipv4_layer_4(
+    ipv4_packet: &Ipv4Packet<'_>,
+    data: &[u8],
+    reassemblers: &mut Reassemblers,
+) -> Layer3Infos {
+    let protocol = ipv4_packet.get_next_level_protocol();
+    let payload = match ipv4_payload(ipv4_packet, data) {
+        Some(payload) => payload,
+        None => return Default::default(),
+    };
+
+    let source = IpAddr::V4(ipv4_packet.get_source());
+    let destination = IpAddr::V4(ipv4_packet.get_destination());
+    let offset = ipv4_packet.get_fragment_offset() as usize * 8;
+    let more_fragments = ipv4_packet.get_flags() & Ipv4Flags::MoreFragments != 0;
+    if offset == 0 && !more_fragments {
+        return Layer3Infos {
+            layer_4_infos: get_layer_4_infos(
+                source,
+                destination,
+                protocol,
+                payload,
+                &mut reassemblers.tcp_streams,
+            ),
+            ..Default::default()
+        };
+    }
+
+    let key = FragmentKey {
+        source,
+        destination,
+        protocol: protocol.0,
+        identification: ipv4_packet.get_identification() as u32,
+    };
+    let reassembly = reassemblers.fragments.add(
+        key,
+        Fragment {
+            offset,
+            more_fragments,
+            payload,
+            min_header_len: min_layer_4_header_len(protocol),
+        },
+        Instant::now(),
+    );
+    Layer3Infos {
+        layer_4_infos: match (&reassembly.payload, reassembly.complete) {
+            (Some(payload), true) => get_layer_4_infos(
+                source,
+                destination,
+                protocol,
+                payload,
+                &mut reassemblers.tcp_streams,
+            ),
+            (Some(first_fragment), false) => get_layer_4_ports(protocol, first_fragment),
+            (None, _) => Default::default(),
+        },
+        anomalies: reassembly.anomalies.into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+/// Taille minimale de l'en-tête de la couche 4, qu'un premier fragment doit contenir en entier.
+fn min_layer_4_header_len(protocol: IpNextHeaderProtocol) -> usize {
+    match protocol {
+        IpNextHeaderProtocols::Tcp => 20,
+        IpNextHeaderProtocols::Udp
+        | IpNextHeaderProtocols::Icmp
+        | IpNextHeaderProtocols::Icmpv6 => 8,
+        _ => 0,
+    }
+}
+
 impl HandlePacket for Ipv6Handler {
     /// Traite les paquets IPv6 pour extraire les informations de la couche 3 et 4.
-    fn get_layer_3(data: &[u8]) -> Layer3Infos {
+    ///
+    /// La chaîne des en-têtes d'extension est parcourue pour retrouver le protocole de la couche 4 ;
+    /// les fragments passent par le réassemblage avant d'être disséqués.
+    fn get_layer_3(data: &[u8], reassemblers: &mut Reassemblers) -> Layer3Infos {
         if let Some(ipv6_packet) = Ipv6Packet::new(data) {
             // println!(
             //     "Layer 3: IPv6 packet: source {} destination {} => {} {}",
@@ -121,11 +323,74 @@ impl HandlePacket for Ipv6Handler {
             //     ipv6_packet.get_next_header(),
             //     ipv6_packet.get_payload_length()
             // );
+            let source = IpAddr::V6(ipv6_packet.get_source());
+            let destination = IpAddr::V6(ipv6_packet.get_destination());
+            let upper_layer =
+                walk_extension_headers(ipv6_packet.get_next_header(), ipv6_packet.payload());
+            let mut extension_headers = upper_layer.extension_headers;
+            let mut anomalies = BTreeSet::new();
+
+            let (l_4_protocol, layer_4_infos) = match upper_layer.fragment {
+                None => (
+                    upper_layer.protocol,
+                    get_layer_4_infos(
+                        source,
+                        destination,
+                        upper_layer.protocol,
+                        upper_layer.payload,
+                        &mut reassemblers.tcp_streams,
+                    ),
+                ),
+                Some(fragment) => {
+                    let key = FragmentKey {
+                        source,
+                        destination,
+                        protocol: upper_layer.protocol.0,
+                        identification: fragment.identification,
+                    };
+                    let reassembly = reassemblers.fragments.add(
+                        key,
+                        Fragment {
+                            offset: fragment.offset as usize,
+                            more_fragments: fragment.more_fragments,
+                            payload: upper_layer.payload,
+                            min_header_len: min_layer_4_header_len(upper_layer.protocol),
+                        },
+                        Instant::now(),
+                    );
+                    anomalies.extend(reassembly.anomalies);
+                    match &reassembly.payload {
+                        // La partie fragmentable peut contenir d'autres en-têtes d'extension
+                        Some(payload) => {
+                            let reassembled = walk_extension_headers(upper_layer.protocol, payload);
+                            extension_headers.extend(reassembled.extension_headers);
+                            let layer_4_infos = match reassembly.complete {
+                                true => get_layer_4_infos(
+                                    source,
+                                    destination,
+                                    reassembled.protocol,
+                                    reassembled.payload,
+                                    &mut reassemblers.tcp_streams,
+                                ),
+                                false => {
+                                    get_layer_4_ports(reassembled.protocol, reassembled.payload)
+                                }
+                            };
+                            (reassembled.protocol, layer_4_infos)
+                        }
+                        None => (upper_layer.protocol, Default::default()),
+                    }
+                }
+            };
+
             Layer3Infos {
                 ip_source: Some(ipv6_packet.get_source().to_string()),
                 ip_destination: Some(ipv6_packet.get_destination().to_string()),
-                l_4_protocol: Some(ipv6_packet.get_next_header().to_string()),
-                layer_4_infos: get_layer_4_infos(ipv6_packet.get_next_header(), data),
+                l_4_protocol: Some(l_4_protocol.to_string()),
+                layer_4_infos,
+                ipv6_extension_headers: extension_headers,
+                anomalies,
+                ..Default::default()
             }
             //handle_next_proto_util(data, ipv6_packet.get_next_header());
         } else {
@@ -136,8 +401,8 @@ impl HandlePacket for Ipv6Handler {
 }
 
 impl HandlePacket for ArpHandler {
-    /// Traite les paquets ARP pour extraire les informations de la couche 3.
-    fn get_layer_3(data: &[u8]) -> Layer3Infos {
+    /// Traite les paquets ARP pour extraire les informations de la couche 3, l'opération et les adresses matérielles.
+    fn get_layer_3(data: &[u8], _reassemblers: &mut Reassemblers) -> Layer3Infos {
         if let Some(arp_packet) = ArpPacket::new(data) {
             // println!(
             //     "Layer 2: arp packet: source {} destination {} => {:?} {} {} {:?} {} {}",
@@ -154,10 +419,9 @@ impl HandlePacket for ArpHandler {
                 ip_source: Some(arp_packet.get_sender_proto_addr().to_string()),
                 ip_destination: Some(arp_packet.get_target_proto_addr().to_string()),
                 l_4_protocol: Default::default(),
-                layer_4_infos: Layer4Infos {
-                    port_source: None,
-                    port_destination: None,
-                },
+                layer_4_infos: Default::default(),
+                arp: Some(ArpInfos::from_packet(&arp_packet)),
+                ..Default::default()
             }
         } else {
             // Handle the case when the data is not a valid IPv4 packet
@@ -167,62 +431,45 @@ impl HandlePacket for ArpHandler {
 }
 
 impl HandlePacket for VlanHandler {
-    /// Traite les paquets VLAN pour extraire les informations de la couche 3 et 4, y compris le support QinQ.
-    fn get_layer_3(data: &[u8]) -> Layer3Infos {
-        if let Some(outer_vlan_packet) = VlanPacket::new(data) {
-            // Check if the encapsulated packet is also a VLAN packet (QinQ)
-            if outer_vlan_packet.get_ethertype() == EtherTypes::Vlan {
-                if let Some(inner_vlan_packet) = VlanPacket::new(outer_vlan_packet.payload()) {
-                    // Handle the encapsulated packet inside the inner VLAN tag
-                    let encapsulated_ether_type = inner_vlan_packet.get_ethertype();
-                    let encapsulated_data = inner_vlan_packet.payload();
-
-                    match encapsulated_ether_type {
-                        EtherTypes::Ipv4 => Ipv4Handler::get_layer_3(encapsulated_data),
-                        EtherTypes::Ipv6 => Ipv6Handler::get_layer_3(encapsulated_data),
-                        // Handle other types or default...
-                        _ => Default::default(),
-                    }
-                } else {
-                    // Handle case where inner VLAN packet is not valid
-                    Default::default()
-                }
-            } else {
-                // Process single VLAN-tagged packet as before
-                let encapsulated_ether_type = outer_vlan_packet.get_ethertype();
-                let encapsulated_data = outer_vlan_packet.payload();
-
-                match encapsulated_ether_type {
-                    EtherTypes::Ipv4 => Ipv4Handler::get_layer_3(encapsulated_data),
-                    EtherTypes::Ipv6 => Ipv6Handler::get_layer_3(encapsulated_data),
-                    // Handle other types or default...
-                    _ => Default::default(),
-                }
-            }
-        } else {
-            Default::default()
+    /// Traite les paquets VLAN, y compris QinQ, en poursuivant la dissection avec le protocole encapsulé
+    /// (IPv4, IPv6, ARP, LLDP...).
+    ///
+    /// Les étiquettes elles-mêmes sont conservées par [`PacketInfos`](super::PacketInfos), qui les retire
+    /// avant la couche 3 ; ce handler ne traite que les charges utiles VLAN qui lui sont transmises directement.
+    fn get_layer_3(data: &[u8], reassemblers: &mut Reassemblers) -> Layer3Infos {
+        let (_, ethertype, payload) = strip_vlan_tags(EtherTypes::Vlan, data);
+        if is_vlan_ethertype(ethertype) {
+            // Étiquette VLAN tronquée
+            return Default::default();
         }
+        get_layer_3_infos_by_ethertype(ethertype, payload, reassemblers)
     }
 }
 
 impl HandlePacket for PppoeDiscoveryHandler {
-    /// Traite les paquets PPPoE Discovery pour extraire les informations pertinentes.
-    fn get_layer_3(data: &[u8]) -> Layer3Infos {
-        // Here, you would parse the PPPoE Discovery packet.
-        // This is a simplified example, as actual parsing would be more complex.
-        if let Some(ethernet_packet) = EthernetPacket::new(data) {
-            if ethernet_packet.get_ethertype() == EtherTypes::PppoeDiscovery {
-                Layer3Infos {
-                    ip_source: None, // PPPoE packets do not have IP source/destination
-                    ip_destination: None,
-                    l_4_protocol: Default::default(),
-                    layer_4_infos: Layer4Infos {
-                        port_source: None,
-                        port_destination: None,
-                    },
-                }
-            } else {
-                Default::default()
+    /// Traite les paquets PPPoE Discovery pour extraire le code, la session et les tags (AC-Name, Service-Name...).
+    fn get_layer_3(data: &[u8], _reassemblers: &mut Reassemblers) -> Layer3Infos {
+        Layer3Infos {
+            // PPPoE packets do not have IP source/destination
+            pppoe: PppoeInfos::parse_discovery(data),
+            ..Default::default()
+        }
+    }
+}
+
+impl HandlePacket for PppoeSessionHandler {
+    /// Traite les paquets PPPoE Session en suivant la trame PPP jusqu'au paquet IPv4 ou IPv6 transporté.
+    fn get_layer_3(data: &[u8], reassemblers: &mut Reassemblers) -> Layer3Infos {
+        if let Some((pppoe_infos, ppp_protocol, ppp_payload)) = PppoeInfos::parse_session(data) {
+            let layer_3_infos = match ppp_protocol {
+                PPP_IPV4 => Ipv4Handler::get_layer_3(ppp_payload, reassemblers),
+                PPP_IPV6 => Ipv6Handler::get_layer_3(ppp_payload, reassemblers),
+                // Trames de contrôle PPP (LCP, IPCP, authentification...)
+                _ => Default::default(),
+            };
+            Layer3Infos {
+                pppoe: Some(pppoe_infos),
+                ..layer_3_infos
             }
         } else {
             Default::default()
@@ -231,38 +478,85 @@ impl HandlePacket for PppoeDiscoveryHandler {
 }
 
 impl HandlePacket for LldpHandler {
-    fn get_layer_3(_data: &[u8]) -> Layer3Infos {
-        // Ici, vous devez implémenter la logique pour parser les paquets LLDP.
-        // Comme LLDP est un protocole de la couche 2, les informations spécifiques de la couche 3 peuvent ne pas être disponibles.
-        // Vous pourriez vouloir extraire des informations comme le nom de l'appareil, le port, la description, etc., et les stocker d'une manière qui a du sens pour votre application.
-
+    /// Traite les trames LLDP pour extraire l'identité de l'équipement émetteur.
+    ///
+    /// LLDP étant un protocole de la couche 2, aucune adresse IP ni port n'est renseigné ;
+    /// les TLV décodés sont conservés dans le champ `lldp`.
+    fn get_layer_3(data: &[u8], _reassemblers: &mut Reassemblers) -> Layer3Infos {
         Layer3Infos {
-            // Ajustez selon les informations que vous pouvez extraire des paquets LLDP.
-            ip_source: None,
-            ip_destination: None,
-            l_4_protocol: None,
-            layer_4_infos: Layer4Infos {
-                port_source: None,
-                port_destination: None,
-            },
+            lldp: LldpInfos::parse(data),
+            ..Default::default()
+        }
+    }
+}
+
+impl HandlePacket for MplsHandler {
+    /// Traite les trames MPLS : décode la pile d'étiquettes puis poursuit la dissection avec le paquet IPv4 ou IPv6,
+    /// ou la trame Ethernet d'un pseudowire, situé sous l'étiquette de fond de pile.
+    fn get_layer_3(data: &[u8], reassemblers: &mut Reassemblers) -> Layer3Infos {
+        if let Some((mut mpls_infos, mpls_payload)) = MplsInfos::parse(data) {
+            let layer_3_infos = match mpls_payload {
+                MplsPayload::Ipv4(payload) => Ipv4Handler::get_layer_3(payload, reassemblers),
+                MplsPayload::Ipv6(payload) => Ipv6Handler::get_layer_3(payload, reassemblers),
+                MplsPayload::Ethernet(frame) => match EthernetPacket::new(frame) {
+                    Some(ethernet_packet) => {
+                        mpls_infos.pseudowire_mac_source =
+                            Some(ethernet_packet.get_source().to_string());
+                        mpls_infos.pseudowire_mac_destination =
+                            Some(ethernet_packet.get_destination().to_string());
+                        get_layer_3_infos(&ethernet_packet, reassemblers)
+                    }
+                    None => Default::default(),
+                },
+                MplsPayload::Unknown => Default::default(),
+            };
+            Layer3Infos {
+                mpls: Some(mpls_infos),
+                ..layer_3_infos
+            }
+        } else {
+            Default::default()
         }
     }
 }
 
 /// Fonction d'entrée pour traiter un paquet Ethernet et extraire les informations de la couche 3 en fonction du type EtherType.
-pub fn get_layer_3_infos(ethernet_packet: &EthernetPacket<'_>) -> Layer3Infos {
-    match ethernet_packet.get_ethertype() {
-        EtherTypes::Ipv6 => Ipv6Handler::get_layer_3(ethernet_packet.payload()),
-        EtherTypes::Ipv4 => Ipv4Handler::get_layer_3(ethernet_packet.payload()),
-        EtherTypes::Arp => ArpHandler::get_layer_3(ethernet_packet.payload()),
-        EtherTypes::Vlan => VlanHandler::get_layer_3(ethernet_packet.payload()),
-        EtherTypes::PppoeDiscovery => PppoeDiscoveryHandler::get_layer_3(ethernet_packet.payload()),
-        EtherTypes::Lldp => LldpHandler::get_layer_3(ethernet_packet.payload()),
+pub fn get_layer_3_infos(
+    ethernet_packet: &EthernetPacket<'_>,
+    reassemblers: &mut Reassemblers,
+) -> Layer3Infos {
+    get_layer_3_infos_by_ethertype(
+        ethernet_packet.get_ethertype(),
+        ethernet_packet.payload(),
+        reassemblers,
+    )
+}
+
+/// Extrait les informations de la couche 3 d'une charge utile dont l'EtherType est connu.
+///
+/// Cette fonction permet de disséquer les trames dont l'en-tête de liaison n'est pas Ethernet
+/// (Linux cooked, IP brut), une fois l'EtherType et la charge utile retrouvés.
+pub fn get_layer_3_infos_by_ethertype(
+    ethertype: EtherType,
+    data: &[u8],
+    reassemblers: &mut Reassemblers,
+) -> Layer3Infos {
+    match ethertype {
+        EtherTypes::Ipv6 => Ipv6Handler::get_layer_3(data, reassemblers),
+        EtherTypes::Ipv4 => Ipv4Handler::get_layer_3(data, reassemblers),
+        EtherTypes::Arp => ArpHandler::get_layer_3(data, reassemblers),
+        EtherTypes::Vlan | EtherTypes::PBridge | EtherTypes::QinQ => {
+            VlanHandler::get_layer_3(data, reassemblers)
+        }
+        EtherTypes::PppoeDiscovery => PppoeDiscoveryHandler::get_layer_3(data, reassemblers),
+        EtherTypes::PppoeSession => PppoeSessionHandler::get_layer_3(data, reassemblers),
+        EtherTypes::Lldp => LldpHandler::get_layer_3(data, reassemblers),
+        EtherTypes::Mpls | EtherTypes::MplsMcast => MplsHandler::get_layer_3(data, reassemblers),
         _ => {
             // General case for all other EtherTypes
-            println!(
+            debug!(
                 "Layer 3 - Unknown or unsupported packet type: {}",
-                ethernet_packet.get_ethertype()
+                ethertype
             );
             Default::default()
         }
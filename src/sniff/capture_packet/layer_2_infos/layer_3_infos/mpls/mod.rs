@@ -0,0 +1,166 @@
+//! # Dissection MPLS
+//!
+//! Les trames MPLS (EtherTypes 0x8847 et 0x8848) portent une pile d'étiquettes de 4 octets
+//! (étiquette, classe de trafic, bit de fond de pile S, TTL), suivie de la charge utile.
+//!
+//! La charge utile sous l'étiquette de fond de pile n'a pas de type explicite : elle est déduite
+//! du premier quartet, 4 pour IPv4, 6 pour IPv6, et 0 pour un pseudowire Ethernet avec mot de contrôle
+//! (RFC 4448), qui transporte une trame Ethernet complète.
+
+use serde::Serialize;
+
+/// Taille d'une entrée de la pile d'étiquettes.
+const LABEL_STACK_ENTRY_LEN: usize = 4;
+/// Taille du mot de contrôle d'un pseudowire.
+const CONTROL_WORD_LEN: usize = 4;
+
+/// Une entrée de la pile d'étiquettes MPLS.
+#[derive(Debug, Default, Serialize, Clone, Copy, Eq, Hash, PartialEq)]
+pub struct MplsLabel {
+    /// Valeur de l'étiquette (20 bits).
+    pub label: u32,
+    /// Classe de trafic (TC, anciennement EXP).
+    pub traffic_class: u8,
+    /// Bit S : dernière étiquette de la pile.
+    pub bottom_of_stack: bool,
+    /// TTL de l'étiquette.
+    pub ttl: u8,
+}
+
+/// Informations extraites d'une trame MPLS.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct MplsInfos {
+    /// Pile d'étiquettes, de l'étiquette externe à l'étiquette de fond de pile.
+    pub labels: Vec<MplsLabel>,
+    /// Type de la charge utile sous la pile : Ipv4, Ipv6, Ethernet (pseudowire) ou Unknown.
+    pub payload_type: String,
+    /// Numéro de séquence du mot de contrôle, pour un pseudowire.
+    pub control_word_sequence: Option<u16>,
+    /// Adresse MAC source de la trame transportée par le pseudowire.
+    pub pseudowire_mac_source: Option<String>,
+    /// Adresse MAC destination de la trame transportée par le pseudowire.
+    pub pseudowire_mac_destination: Option<String>,
+}
+
+/// Charge utile située sous la pile d'étiquettes.
+#[derive(Debug, PartialEq)]
+pub enum MplsPayload<'a> {
+    Ipv4(&'a [u8]),
+    Ipv6(&'a [u8]),
+    /// Trame Ethernet transportée par un pseudowire, après le mot de contrôle.
+    Ethernet(&'a [u8]),
+    Unknown,
+}
+
+impl MplsInfos {
+    /// Décode la pile d'étiquettes et identifie la charge utile située dessous.
+    ///
+    /// Retourne `None` si la pile est tronquée avant l'étiquette de fond de pile.
+    pub fn parse(data: &[u8]) -> Option<(MplsInfos, MplsPayload<'_>)> {
+        let mut infos = MplsInfos::default();
+        let mut offset = 0;
+
+        loop {
+            let entry = data.get(offset..offset + LABEL_STACK_ENTRY_LEN)?;
+            let value = u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            let label = MplsLabel {
+                label: value >> 12,
+                traffic_class: ((value >> 9) & 0x07) as u8,
+                bottom_of_stack: value & 0x100 != 0,
+                ttl: (value & 0xff) as u8,
+            };
+            infos.labels.push(label);
+            offset += LABEL_STACK_ENTRY_LEN;
+            if label.bottom_of_stack {
+                break;
+            }
+        }
+
+        let payload = &data[offset..];
+        let mpls_payload = match payload.first().map(|byte| byte >> 4) {
+            Some(4) => MplsPayload::Ipv4(payload),
+            Some(6) => MplsPayload::Ipv6(payload),
+            Some(0) if payload.len() >= CONTROL_WORD_LEN => {
+                infos.control_word_sequence = Some(u16::from_be_bytes([payload[2], payload[3]]));
+                MplsPayload::Ethernet(&payload[CONTROL_WORD_LEN..])
+            }
+            _ => MplsPayload::Unknown,
+        };
+        infos.payload_type = match mpls_payload {
+            MplsPayload::Ipv4(_) => "Ipv4",
+            MplsPayload::Ipv6(_) => "Ipv6",
+            MplsPayload::Ethernet(_) => "Ethernet",
+            MplsPayload::Unknown => "Unknown",
+        }
+        .to_string();
+
+        Some((infos, mpls_payload))
+    }
+
+    /// Indique si deux trames suivent le même chemin MPLS (mêmes étiquettes), le TTL et la séquence
+    /// du mot de contrôle variant d'une trame à l'autre.
+    pub fn same_path(&self, other: &MplsInfos) -> bool {
+        self.labels.len() == other.labels.len()
+            && self
+                .labels
+                .iter()
+                .zip(&other.labels)
+                .all(|(label, other)| label.label == other.label)
+            && self.payload_type == other.payload_type
+            && self.pseudowire_mac_source == other.pseudowire_mac_source
+            && self.pseudowire_mac_destination == other.pseudowire_mac_destination
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_label_stack_ipv4() {
+        // Étiquette 1000 (TC 5), puis étiquette 16 en fond de pile, TTL 64
+        let data = [
+            0x00, 0x3e, 0x8a, 0xff, // 1000, TC 5, S = 0, TTL 255
+            0x00, 0x01, 0x01, 0x40, // 16, TC 0, S = 1, TTL 64
+            0x45, 0x00,
+        ];
+
+        let (infos, payload) = MplsInfos::parse(&data).unwrap();
+
+        assert_eq!(
+            infos.labels,
+            vec![
+                MplsLabel {
+                    label: 1000,
+                    traffic_class: 5,
+                    bottom_of_stack: false,
+                    ttl: 255,
+                },
+                MplsLabel {
+                    label: 16,
+                    traffic_class: 0,
+                    bottom_of_stack: true,
+                    ttl: 64,
+                },
+            ]
+        );
+        assert_eq!(infos.payload_type, "Ipv4");
+        assert_eq!(payload, MplsPayload::Ipv4(&data[8..]));
+    }
+
+    #[test]
+    fn test_parse_pseudowire_control_word() {
+        let data = [0x00, 0x01, 0x41, 0x40, 0x00, 0x00, 0x00, 0x07, 0xaa, 0xbb];
+
+        let (infos, payload) = MplsInfos::parse(&data).unwrap();
+
+        assert_eq!(infos.labels[0].label, 20);
+        assert_eq!(infos.control_word_sequence, Some(7));
+        assert_eq!(payload, MplsPayload::Ethernet(&data[8..]));
+    }
+
+    #[test]
+    fn test_parse_truncated_stack() {
+        assert!(MplsInfos::parse(&[0x00, 0x3e, 0x8a, 0xff, 0x00]).is_none());
+    }
+}
@@ -0,0 +1,191 @@
+//! # Dissection PPPoE
+//!
+//! PPPoE (RFC 2516) transporte des sessions PPP sur Ethernet en deux étapes :
+//!
+//! - la phase de découverte (EtherType 0x8863) : PADI, PADO, PADR, PADS et PADT, dont les tags
+//!   identifient le concentrateur d'accès (AC-Name) et le service demandé (Service-Name) ;
+//! - la phase de session (EtherType 0x8864) : chaque trame porte un identifiant de session suivi
+//!   d'une trame PPP, dont le protocole indique le paquet IPv4 ou IPv6 transporté.
+
+use serde::Serialize;
+
+/// Taille de l'en-tête PPPoE : version/type, code, identifiant de session, longueur.
+const PPPOE_HEADER_LEN: usize = 6;
+
+/// Tags PPPoE de la phase de découverte.
+const TAG_END_OF_LIST: u16 = 0x0000;
+const TAG_SERVICE_NAME: u16 = 0x0101;
+const TAG_AC_NAME: u16 = 0x0102;
+const TAG_HOST_UNIQ: u16 = 0x0103;
+const TAG_SERVICE_NAME_ERROR: u16 = 0x0201;
+const TAG_AC_SYSTEM_ERROR: u16 = 0x0202;
+const TAG_GENERIC_ERROR: u16 = 0x0203;
+
+/// Protocoles PPP transportant des paquets IP.
+pub const PPP_IPV4: u16 = 0x0021;
+pub const PPP_IPV6: u16 = 0x0057;
+
+/// Informations extraites d'une trame PPPoE.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct PppoeInfos {
+    /// Code du paquet : PADI, PADO, PADR, PADS, PADT, ou Session pour la phase de session.
+    pub code: String,
+    /// Identifiant de la session PPPoE (0 avant l'établissement de la session).
+    pub session_id: u16,
+    /// Service demandé ou proposé (tag Service-Name).
+    pub service_name: Option<String>,
+    /// Nom du concentrateur d'accès (tag AC-Name).
+    pub ac_name: Option<String>,
+    /// Valeur opaque choisie par le client pour associer les réponses (tag Host-Uniq), en hexadécimal.
+    pub host_uniq: Option<String>,
+    /// Message d'erreur renvoyé par le concentrateur, le cas échéant.
+    pub error: Option<String>,
+    /// Protocole PPP transporté, pour les trames de session.
+    pub ppp_protocol: Option<String>,
+}
+
+impl PppoeInfos {
+    /// Décode une trame de la phase de découverte PPPoE (charge utile de la trame Ethernet).
+    pub fn parse_discovery(data: &[u8]) -> Option<PppoeInfos> {
+        let (code, session_id, payload) = parse_header(data)?;
+        let mut infos = PppoeInfos {
+            code: code_name(code),
+            session_id,
+            ..Default::default()
+        };
+
+        let mut offset = 0;
+        while offset + 4 <= payload.len() {
+            let tag_type = u16::from_be_bytes([payload[offset], payload[offset + 1]]);
+            let tag_len = u16::from_be_bytes([payload[offset + 2], payload[offset + 3]]) as usize;
+            offset += 4;
+            if tag_type == TAG_END_OF_LIST {
+                break;
+            }
+            let value = match payload.get(offset..offset + tag_len) {
+                Some(value) => value,
+                None => break,
+            };
+            offset += tag_len;
+
+            match tag_type {
+                // Un Service-Name vide signifie "n'importe quel service"
+                TAG_SERVICE_NAME if infos.service_name.is_none() => {
+                    infos.service_name = Some(String::from_utf8_lossy(value).into_owned())
+                }
+                TAG_AC_NAME => infos.ac_name = Some(String::from_utf8_lossy(value).into_owned()),
+                TAG_HOST_UNIQ => {
+                    infos.host_uniq = Some(value.iter().map(|b| format!("{:02x}", b)).collect())
+                }
+                TAG_SERVICE_NAME_ERROR | TAG_AC_SYSTEM_ERROR | TAG_GENERIC_ERROR => {
+                    infos.error = Some(String::from_utf8_lossy(value).into_owned())
+                }
+                _ => {}
+            }
+        }
+
+        Some(infos)
+    }
+
+    /// Décode une trame de la phase de session PPPoE.
+    ///
+    /// Retourne les informations PPPoE, le protocole PPP et la charge utile PPP (le paquet IP le cas échéant).
+    pub fn parse_session(data: &[u8]) -> Option<(PppoeInfos, u16, &[u8])> {
+        let (_, session_id, payload) = parse_header(data)?;
+        let ppp_protocol = u16::from_be_bytes([*payload.first()?, *payload.get(1)?]);
+
+        let infos = PppoeInfos {
+            code: "Session".to_string(),
+            session_id,
+            ppp_protocol: Some(ppp_protocol_name(ppp_protocol)),
+            ..Default::default()
+        };
+        Some((infos, ppp_protocol, &payload[2..]))
+    }
+}
+
+/// Lit l'en-tête PPPoE et retourne le code, l'identifiant de session et la charge utile bornée par le champ longueur.
+fn parse_header(data: &[u8]) -> Option<(u8, u16, &[u8])> {
+    let header = data.get(..PPPOE_HEADER_LEN)?;
+    // Seules la version 1 et le type 1 sont définis
+    if header[0] != 0x11 {
+        return None;
+    }
+    let code = header[1];
+    let session_id = u16::from_be_bytes([header[2], header[3]]);
+    let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+    let payload = &data[PPPOE_HEADER_LEN..];
+
+    Some((code, session_id, &payload[..length.min(payload.len())]))
+}
+
+fn code_name(code: u8) -> String {
+    match code {
+        0x09 => "PADI".to_string(),
+        0x07 => "PADO".to_string(),
+        0x19 => "PADR".to_string(),
+        0x65 => "PADS".to_string(),
+        0xa7 => "PADT".to_string(),
+        0x00 => "Session".to_string(),
+        other => format!("0x{:02x}", other),
+    }
+}
+
+fn ppp_protocol_name(protocol: u16) -> String {
+    match protocol {
+        PPP_IPV4 => "Ipv4".to_string(),
+        PPP_IPV6 => "Ipv6".to_string(),
+        0xc021 => "LCP".to_string(),
+        0x8021 => "IPCP".to_string(),
+        0x8057 => "IPV6CP".to_string(),
+        0xc023 => "PAP".to_string(),
+        0xc223 => "CHAP".to_string(),
+        other => format!("0x{:04x}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_discovery_pado() {
+        let mut data = vec![0x11, 0x07, 0x00, 0x00];
+        let mut tags = Vec::new();
+        tags.extend_from_slice(&[0x01, 0x02, 0x00, 0x06]);
+        tags.extend_from_slice(b"BRAS-1");
+        tags.extend_from_slice(&[0x01, 0x01, 0x00, 0x08]);
+        tags.extend_from_slice(b"internet");
+        tags.extend_from_slice(&[0x01, 0x03, 0x00, 0x02, 0xbe, 0xef]);
+        data.extend_from_slice(&(tags.len() as u16).to_be_bytes());
+        data.extend(tags);
+
+        let infos = PppoeInfos::parse_discovery(&data).unwrap();
+
+        assert_eq!(infos.code, "PADO");
+        assert_eq!(infos.session_id, 0);
+        assert_eq!(infos.ac_name.as_deref(), Some("BRAS-1"));
+        assert_eq!(infos.service_name.as_deref(), Some("internet"));
+        assert_eq!(infos.host_uniq.as_deref(), Some("beef"));
+        assert_eq!(infos.error, None);
+    }
+
+    #[test]
+    fn test_parse_session() {
+        let data = [0x11, 0x00, 0x12, 0x34, 0x00, 0x04, 0x00, 0x21, 0x45, 0x00];
+
+        let (infos, protocol, payload) = PppoeInfos::parse_session(&data).unwrap();
+
+        assert_eq!(infos.code, "Session");
+        assert_eq!(infos.session_id, 0x1234);
+        assert_eq!(infos.ppp_protocol.as_deref(), Some("Ipv4"));
+        assert_eq!(protocol, PPP_IPV4);
+        assert_eq!(payload, &[0x45, 0x00]);
+    }
+
+    #[test]
+    fn test_parse_invalid_version() {
+        assert!(PppoeInfos::parse_discovery(&[0x22, 0x09, 0, 0, 0, 0]).is_none());
+        assert!(PppoeInfos::parse_session(&[0x11, 0x00]).is_none());
+    }
+}
@@ -0,0 +1,171 @@
+//! # Types de liens de la couche 2
+//!
+//! Toutes les sources de trames ne commencent pas par un en-tête Ethernet : les interfaces tun/wireguard
+//! livrent directement des paquets IP, et les fichiers pcap peuvent utiliser les en-têtes "Linux cooked"
+//! (`LINKTYPE_LINUX_SLL` et `LINKTYPE_LINUX_SLL2`) ou `LINKTYPE_RAW`.
+//!
+//! Ce module identifie le type de lien d'une source et retrouve, pour chaque trame, l'EtherType et la charge
+//! utile de la couche 3 afin de poursuivre la dissection comme pour une trame Ethernet.
+
+use pnet::datalink::NetworkInterface;
+use pnet::packet::ethernet::{EtherType, EtherTypes, EthernetPacket};
+
+/// `LINKTYPE_ETHERNET`
+const LINKTYPE_ETHERNET: u32 = 1;
+/// `LINKTYPE_RAW`
+const LINKTYPE_RAW: u32 = 101;
+/// `LINKTYPE_LINUX_SLL`
+const LINKTYPE_LINUX_SLL: u32 = 113;
+/// `LINKTYPE_LINUX_SLL2`
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+/// Taille de l'en-tête Linux cooked v1 (`LINKTYPE_LINUX_SLL`).
+const SLL_HEADER_LEN: usize = 16;
+/// Taille de l'en-tête Linux cooked v2 (`LINKTYPE_LINUX_SLL2`).
+const SLL2_HEADER_LEN: usize = 20;
+
+/// Type de lien d'une source de trames, qui détermine la manière de lire l'en-tête de la couche 2.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LinkType {
+    /// Trames Ethernet II (`LINKTYPE_ETHERNET`).
+    Ethernet,
+    /// En-tête Linux cooked v1 de 16 octets (`LINKTYPE_LINUX_SLL`).
+    LinuxSll,
+    /// En-tête Linux cooked v2 de 20 octets (`LINKTYPE_LINUX_SLL2`).
+    LinuxSll2,
+    /// Paquets IPv4 ou IPv6 sans en-tête de liaison (`LINKTYPE_RAW`, interfaces tun/wireguard).
+    RawIp,
+}
+
+impl LinkType {
+    /// Retourne le type de lien correspondant à une valeur `LINKTYPE_*` (pcap ou datalink).
+    ///
+    /// Retourne `None` pour les types de lien non pris en charge.
+    pub fn from_pcap_linktype(linktype: u32) -> Option<LinkType> {
+        match linktype {
+            LINKTYPE_ETHERNET => Some(LinkType::Ethernet),
+            LINKTYPE_LINUX_SLL => Some(LinkType::LinuxSll),
+            LINKTYPE_LINUX_SLL2 => Some(LinkType::LinuxSll2),
+            // LINKTYPE_RAW, ainsi que les valeurs DLT_RAW historiques de certains systèmes
+            LINKTYPE_RAW | 12 | 14 => Some(LinkType::RawIp),
+            _ => None,
+        }
+    }
+
+    /// Déduit la valeur `LINKTYPE_*` des trames capturées sur une interface réseau.
+    ///
+    /// Les interfaces point à point sans adresse MAC (tun, wireguard, ppp) livrent des paquets IP bruts ;
+    /// toutes les autres interfaces sont traitées comme Ethernet.
+    pub fn pcap_linktype_for_interface(interface: &NetworkInterface) -> u32 {
+        let has_mac = interface.mac.is_some_and(|mac| !mac.is_zero());
+
+        if !interface.is_loopback() && (interface.is_point_to_point() || !has_mac) {
+            LINKTYPE_RAW
+        } else {
+            LINKTYPE_ETHERNET
+        }
+    }
+
+    /// Retourne l'EtherType lu dans l'en-tête de liaison de la trame, avant le retrait des étiquettes VLAN
+    /// et la décapsulation des tunnels (802.1Q, PPPoE, IP transportant un tunnel...).
+    ///
+    /// Retourne `None` si l'en-tête de liaison ne peut pas être lu.
+    pub fn ethertype(self, frame: &[u8]) -> Option<EtherType> {
+        match self {
+            LinkType::Ethernet => EthernetPacket::new(frame).map(|packet| packet.get_ethertype()),
+            _ => self.layer_3_payload(frame).map(|(ethertype, _)| ethertype),
+        }
+    }
+
+    /// Extrait l'EtherType et la charge utile de la couche 3 d'une trame non Ethernet.
+    ///
+    /// Retourne `None` si la trame est trop courte ou si son contenu ne correspond pas au type de lien.
+    /// Les trames Ethernet sont traitées directement par [`EthernetPacket`](pnet::packet::ethernet::EthernetPacket)
+    /// et retournent également `None`.
+    pub fn layer_3_payload(self, frame: &[u8]) -> Option<(EtherType, &[u8])> {
+        match self {
+            LinkType::Ethernet => None,
+            LinkType::LinuxSll => {
+                // type de paquet (2), ARPHRD (2), longueur d'adresse (2), adresse (8), protocole (2)
+                let header = frame.get(..SLL_HEADER_LEN)?;
+                let protocol = u16::from_be_bytes([header[14], header[15]]);
+                Some((EtherType::new(protocol), &frame[SLL_HEADER_LEN..]))
+            }
+            LinkType::LinuxSll2 => {
+                // protocole (2), réservé (2), index d'interface (4), ARPHRD (2), type de paquet (1),
+                // longueur d'adresse (1), adresse (8)
+                let header = frame.get(..SLL2_HEADER_LEN)?;
+                let protocol = u16::from_be_bytes([header[0], header[1]]);
+                Some((EtherType::new(protocol), &frame[SLL2_HEADER_LEN..]))
+            }
+            LinkType::RawIp => match frame.first()? >> 4 {
+                4 => Some((EtherTypes::Ipv4, frame)),
+                6 => Some((EtherTypes::Ipv6, frame)),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_pcap_linktype() {
+        assert_eq!(LinkType::from_pcap_linktype(1), Some(LinkType::Ethernet));
+        assert_eq!(LinkType::from_pcap_linktype(113), Some(LinkType::LinuxSll));
+        assert_eq!(LinkType::from_pcap_linktype(276), Some(LinkType::LinuxSll2));
+        assert_eq!(LinkType::from_pcap_linktype(101), Some(LinkType::RawIp));
+        assert_eq!(LinkType::from_pcap_linktype(147), None);
+    }
+
+    #[test]
+    fn test_ethertype() {
+        // Trame Ethernet portant une étiquette 802.1Q
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&[0x81, 0x00, 0x00, 0x0a, 0x08, 0x00]);
+        assert_eq!(LinkType::Ethernet.ethertype(&frame), Some(EtherTypes::Vlan));
+        assert_eq!(LinkType::Ethernet.ethertype(&frame[..10]), None);
+
+        assert_eq!(
+            LinkType::RawIp.ethertype(&[0x60, 0, 0, 0]),
+            Some(EtherTypes::Ipv6)
+        );
+    }
+
+    #[test]
+    fn test_layer_3_payload_sll() {
+        let mut frame = vec![0u8; SLL_HEADER_LEN];
+        frame[14..16].copy_from_slice(&[0x86, 0xdd]);
+        frame.extend_from_slice(&[0x60, 0, 0, 0]);
+
+        let (ethertype, payload) = LinkType::LinuxSll.layer_3_payload(&frame).unwrap();
+        assert_eq!(ethertype, EtherTypes::Ipv6);
+        assert_eq!(payload, &[0x60, 0, 0, 0]);
+
+        assert!(LinkType::LinuxSll.layer_3_payload(&frame[..10]).is_none());
+    }
+
+    #[test]
+    fn test_layer_3_payload_sll2() {
+        let mut frame = vec![0u8; SLL2_HEADER_LEN];
+        frame[0..2].copy_from_slice(&[0x08, 0x06]);
+        frame.extend_from_slice(&[0xaa]);
+
+        let (ethertype, payload) = LinkType::LinuxSll2.layer_3_payload(&frame).unwrap();
+        assert_eq!(ethertype, EtherTypes::Arp);
+        assert_eq!(payload, &[0xaa]);
+    }
+
+    #[test]
+    fn test_layer_3_payload_raw_ip() {
+        let ipv4 = [0x45, 0, 0, 20];
+        assert_eq!(
+            LinkType::RawIp.layer_3_payload(&ipv4),
+            Some((EtherTypes::Ipv4, &ipv4[..]))
+        );
+        assert!(LinkType::RawIp.layer_3_payload(&[0x00]).is_none());
+        assert!(LinkType::RawIp.layer_3_payload(&[]).is_none());
+    }
+}
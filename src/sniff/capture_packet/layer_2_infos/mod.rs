@@ -7,6 +7,7 @@
 //! ## Fonctions
 //!
 //! - [`PacketInfos::new`](struct.PacketInfos.html#method.new): Construit une nouvelle instance de `PacketInfos` à partir d'un paquet Ethernet et du nom de l'interface.
+//! - [`PacketInfos::from_frame`](struct.PacketInfos.html#method.from_frame): Construit une instance de `PacketInfos` à partir d'une trame brute, selon le type de lien de sa source.
 //!
 //! ## Implémentation de Trait
 //!
@@ -16,9 +17,16 @@ use std::fmt;
 
 use pnet::packet::{ethernet::EthernetPacket, Packet};
 
-use layer_3_infos::{get_layer_3_infos, Layer3Infos};
+use layer_3_infos::{get_layer_3_infos, get_layer_3_infos_by_ethertype, Layer3Infos, Reassemblers};
+use link_type::LinkType;
+use pnet::packet::ethernet::EtherType;
 use serde::Serialize;
+use tunnel::{decapsulate, InnerFrame, TunnelInfos};
+use vlan::{strip_vlan_tags, VlanTag};
 pub(crate) mod layer_3_infos;
+pub(crate) mod link_type;
+pub(crate) mod tunnel;
+pub(crate) mod vlan;
 
 /// Représente des informations détaillées sur un paquet réseau, y compris les adresses MAC, l'interface, et les données des couches 3 et 4.
 #[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
@@ -29,10 +37,17 @@ pub struct PacketInfos {
     pub mac_address_destination: String,
     /// Interface réseau par laquelle le paquet a été reçu ou sera envoyé.
     pub interface: String,
-    /// Protocole de couche 3 utilisé dans le paquet.
+    /// Protocole de couche 3 utilisé dans le paquet, après retrait des étiquettes VLAN.
     pub l_3_protocol: String,
+    /// Étiquette VLAN externe (étiquette unique, ou S-tag en QinQ).
+    pub vlan_outer: Option<VlanTag>,
+    /// Étiquette VLAN interne (C-tag en QinQ).
+    pub vlan_inner: Option<VlanTag>,
     /// Informations détaillées de la couche 3 (par exemple, adresses IP, protocole).
     pub layer_3_infos: Layer3Infos,
+    /// Tunnel dont le paquet a été extrait (GRE, ERSPAN, VXLAN...) : les autres champs décrivent alors
+    /// la trame interne.
+    pub tunnel: Option<TunnelInfos>,
     /// La taille totale du paquet en octets.
     pub packet_size: usize,
 }
@@ -40,18 +55,140 @@ pub struct PacketInfos {
 impl PacketInfos {
     /// Construit une nouvelle instance de `PacketInfos` à partir d'un paquet Ethernet et du nom de l'interface.
     ///
+    /// Les étiquettes VLAN éventuelles sont conservées dans `vlan_outer` et `vlan_inner`,
+    /// et la dissection se poursuit avec le protocole qu'elles encapsulent.
+    ///
     /// # Arguments
     ///
     /// * `interface_name` - Une chaîne de caractères qui contient le nom de l'interface réseau.
     /// * `ethernet_packet` - Une référence au paquet Ethernet à partir duquel extraire les informations.
-    pub fn new(interface_name: &String, ethernet_packet: &EthernetPacket<'_>) -> PacketInfos {
-        PacketInfos {
+    /// * `reassemblers` - L'état de réassemblage du thread de capture, conservé d'une trame à l'autre.
+    pub fn new(
+        interface_name: &String,
+        ethernet_packet: &EthernetPacket<'_>,
+        reassemblers: &mut Reassemblers,
+    ) -> PacketInfos {
+        let (vlan_tags, ethertype, payload) =
+            strip_vlan_tags(ethernet_packet.get_ethertype(), ethernet_packet.payload());
+        let packet_infos = PacketInfos {
             mac_address_source: ethernet_packet.get_source().to_string(),
             mac_address_destination: ethernet_packet.get_destination().to_string(),
             interface: interface_name.to_string(),
-            l_3_protocol: ethernet_packet.get_ethertype().to_string(),
-            layer_3_infos: get_layer_3_infos(ethernet_packet),
+            l_3_protocol: ethertype.to_string(),
+            vlan_outer: vlan_tags.outer,
+            vlan_inner: vlan_tags.inner,
             packet_size: ethernet_packet.packet().len(), // Initialize packet size with total packet length
+            ..Default::default()
+        };
+
+        match decapsulate(ethertype, payload) {
+            Some((tunnel, inner)) => packet_infos.decapsulated(tunnel, inner, reassemblers),
+            None => PacketInfos {
+                layer_3_infos: get_layer_3_infos(ethernet_packet, reassemblers),
+                ..packet_infos
+            },
+        }
+    }
+
+    /// Indique si deux paquets appartiennent aux mêmes VLAN (identifiants externe et interne).
+    pub fn same_vlans(&self, other: &PacketInfos) -> bool {
+        let vlan_id = |tag: &Option<VlanTag>| tag.map(|tag| tag.vlan_id);
+        vlan_id(&self.vlan_outer) == vlan_id(&other.vlan_outer)
+            && vlan_id(&self.vlan_inner) == vlan_id(&other.vlan_inner)
+    }
+
+    /// Construit une instance de `PacketInfos` à partir d'une trame brute, en tenant compte du type de lien.
+    ///
+    /// Les trames sans en-tête Ethernet (Linux cooked, IP brut) produisent des adresses MAC vides,
+    /// les informations des couches 3 et 4 étant extraites normalement.
+    ///
+    /// # Arguments
+    ///
+    /// * `interface_name` - Le nom de l'interface réseau ou de la source de la trame.
+    /// * `link_type` - Le type de lien de la source, qui détermine l'en-tête de la couche 2.
+    /// * `frame` - Les octets de la trame capturée.
+    /// * `reassemblers` - L'état de réassemblage du thread de capture, conservé d'une trame à l'autre.
+    ///
+    /// Retourne `None` si la trame ne peut pas être lue avec ce type de lien.
+    pub fn from_frame(
+        interface_name: &str,
+        link_type: LinkType,
+        frame: &[u8],
+        reassemblers: &mut Reassemblers,
+    ) -> Option<PacketInfos> {
+        if link_type == LinkType::Ethernet {
+            return EthernetPacket::new(frame).map(|ethernet_packet| {
+                PacketInfos::new(&interface_name.to_string(), &ethernet_packet, reassemblers)
+            });
+        }
+
+        let (ethertype, payload) = link_type.layer_3_payload(frame)?;
+        let (vlan_tags, ethertype, payload) = strip_vlan_tags(ethertype, payload);
+        let packet_infos = PacketInfos {
+            interface: interface_name.to_string(),
+            vlan_outer: vlan_tags.outer,
+            vlan_inner: vlan_tags.inner,
+            packet_size: frame.len(),
+            ..Default::default()
+        };
+        Some(packet_infos.with_layer_3(ethertype, payload, reassemblers))
+    }
+
+    /// Complète les informations de la couche 3 à partir d'une charge utile dont l'EtherType est connu,
+    /// en décapsulant les tunnels éventuels.
+    fn with_layer_3(
+        self,
+        ethertype: EtherType,
+        payload: &[u8],
+        reassemblers: &mut Reassemblers,
+    ) -> PacketInfos {
+        match decapsulate(ethertype, payload) {
+            Some((tunnel, inner)) => self.decapsulated(tunnel, inner, reassemblers),
+            None => PacketInfos {
+                l_3_protocol: ethertype.to_string(),
+                layer_3_infos: get_layer_3_infos_by_ethertype(ethertype, payload, reassemblers),
+                ..self
+            },
+        }
+    }
+
+    /// Remplace les informations du paquet externe par celles du contenu du tunnel.
+    ///
+    /// Une trame Ethernet interne fournit ses propres adresses MAC et étiquettes VLAN ; un paquet IP interne
+    /// conserve celles de la trame externe. La taille reste celle de la trame capturée.
+    fn decapsulated(
+        self,
+        mut tunnel: TunnelInfos,
+        inner: InnerFrame<'_>,
+        reassemblers: &mut Reassemblers,
+    ) -> PacketInfos {
+        tunnel.outer_mac_source =
+            Some(self.mac_address_source.clone()).filter(|mac| !mac.is_empty());
+        tunnel.outer_mac_destination =
+            Some(self.mac_address_destination.clone()).filter(|mac| !mac.is_empty());
+
+        let inner_packet = match inner {
+            InnerFrame::Ethernet(frame) => match EthernetPacket::new(frame) {
+                Some(ethernet_packet) => {
+                    PacketInfos::new(&self.interface, &ethernet_packet, reassemblers)
+                }
+                None => {
+                    return PacketInfos {
+                        tunnel: Some(tunnel),
+                        ..self
+                    }
+                }
+            },
+            InnerFrame::Layer3(ethertype, payload) => {
+                self.clone().with_layer_3(ethertype, payload, reassemblers)
+            }
+        };
+
+        PacketInfos {
+            packet_size: self.packet_size,
+            // Le tunnel le plus externe est conservé en cas d'encapsulations imbriquées
+            tunnel: Some(tunnel),
+            ..inner_packet
         }
     }
 }
@@ -63,17 +200,19 @@ impl PacketInfos {
 ///
 /// * `interface_name` - Une chaîne de caractères qui contient le nom de l'interface réseau.
 /// * `ethernet_packet` - Une référence au paquet Ethernet à partir duquel extraire les informations.
+/// * `reassemblers` - L'état de réassemblage du thread de capture, conservé d'une trame à l'autre.
 ///
 /// # Exemple
 ///
 /// ```rust
 /// use pnet::packet::ethernet::EthernetPacket;
-/// use packet_infos::PacketInfos;
+/// use packet_infos::{layer_3_infos::Reassemblers, PacketInfos};
 ///
 /// let interface_name = String::from("eth0");
+/// let mut reassemblers = Reassemblers::default();
 /// let ethernet_packet_data: &[u8] = &[/* données du paquet Ethernet */];
 /// if let Some(ethernet_packet) = EthernetPacket::new(ethernet_packet_data) {
-///     let packet_infos = PacketInfos::new(&interface_name, &ethernet_packet);
+///     let packet_infos = PacketInfos::new(&interface_name, &ethernet_packet, &mut reassemblers);
 ///     println!("Packet Infos: {:?}", packet_infos);
 /// }
 /// ```
@@ -126,6 +265,16 @@ impl fmt::Display for PacketInfos {
             "L4 Protocol: {}",
             self.layer_3_infos.l_4_protocol.as_deref().unwrap_or("N/A")
         )?;
+        writeln!(
+            f,
+            "L7 Protocol: {}",
+            self.layer_3_infos
+                .layer_4_infos
+                .layer_7_infos
+                .as_ref()
+                .and_then(|l| l.protocol.as_deref())
+                .unwrap_or("N/A")
+        )?;
         writeln!(f, "Packet Size: {} bytes", self.packet_size)?;
         Ok(())
     }
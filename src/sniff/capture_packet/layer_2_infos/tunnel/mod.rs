@@ -0,0 +1,304 @@
+//! # Décapsulation des tunnels
+//!
+//! Le trafic mirroré ou virtualisé arrive souvent encapsulé : GRE (y compris ERSPAN I, II et III pour la
+//! recopie de ports distante), VXLAN, Geneve, IPv4 dans IPv4 et IPv6 dans IPv4 (6in4).
+//!
+//! Ce module reconnaît ces encapsulations à partir de l'en-tête IP externe, retient les extrémités du tunnel
+//! et son identifiant (clé GRE, session ERSPAN, VNI), et retourne la trame ou le paquet interne, qui est
+//! ensuite disséqué à la place du paquet externe.
+
+use std::net::Ipv6Addr;
+
+use pnet::packet::{
+    ethernet::{EtherType, EtherTypes},
+    ip::{IpNextHeaderProtocol, IpNextHeaderProtocols},
+    ipv4::{Ipv4Flags, Ipv4Packet},
+};
+use serde::Serialize;
+
+use super::layer_3_infos::{ipv4_payload, ipv6_extensions::walk_extension_headers};
+
+/// Ports UDP des tunnels VXLAN et Geneve.
+const VXLAN_PORT: u16 = 4789;
+const GENEVE_PORT: u16 = 6081;
+
+/// Types de protocole GRE propres aux tunnels.
+const GRE_TRANSPARENT_ETHERNET_BRIDGING: u16 = 0x6558;
+const GRE_ERSPAN_TYPE_II: u16 = 0x88be;
+const GRE_ERSPAN_TYPE_III: u16 = 0x22eb;
+
+/// Taille de l'en-tête IPv6 fixe.
+const IPV6_HEADER_LEN: usize = 40;
+
+/// Informations sur le tunnel dont le paquet a été extrait.
+#[derive(Debug, Default, Serialize, Clone, Eq, Hash, PartialEq)]
+pub struct TunnelInfos {
+    /// Type de tunnel : GRE, ERSPAN I, ERSPAN II, ERSPAN III, VXLAN, Geneve, IPIP, 6in4, 4in6 ou 6in6.
+    pub kind: String,
+    /// Adresse IP source de l'en-tête externe.
+    pub outer_ip_source: String,
+    /// Adresse IP destination de l'en-tête externe.
+    pub outer_ip_destination: String,
+    /// Adresse MAC source de la trame externe.
+    pub outer_mac_source: Option<String>,
+    /// Adresse MAC destination de la trame externe.
+    pub outer_mac_destination: Option<String>,
+    /// Identifiant du tunnel : clé GRE, identifiant de session ERSPAN ou VNI VXLAN/Geneve.
+    pub tunnel_id: Option<u32>,
+}
+
+/// Contenu transporté par un tunnel.
+#[derive(Debug, PartialEq)]
+pub enum InnerFrame<'a> {
+    /// Trame Ethernet complète (VXLAN, Geneve, GRE en mode pont, ERSPAN).
+    Ethernet(&'a [u8]),
+    /// Charge utile de couche 3 identifiée par son EtherType (IP dans IP, GRE).
+    Layer3(EtherType, &'a [u8]),
+}
+
+/// Recherche un tunnel dans une charge utile de couche 3 et retourne ses informations et son contenu.
+///
+/// Retourne `None` si la charge utile n'est pas un paquet IP encapsulant un tunnel reconnu,
+/// ou si le paquet externe est un fragment.
+pub fn decapsulate(ethertype: EtherType, payload: &[u8]) -> Option<(TunnelInfos, InnerFrame<'_>)> {
+    let (outer_ip_source, outer_ip_destination, outer_is_ipv4, protocol, data) = match ethertype {
+        EtherTypes::Ipv4 => {
+            let ipv4_packet = Ipv4Packet::new(payload)?;
+            if ipv4_packet.get_fragment_offset() != 0
+                || ipv4_packet.get_flags() & Ipv4Flags::MoreFragments != 0
+            {
+                return None;
+            }
+            (
+                ipv4_packet.get_source().to_string(),
+                ipv4_packet.get_destination().to_string(),
+                true,
+                ipv4_packet.get_next_level_protocol(),
+                ipv4_payload(&ipv4_packet, payload)?,
+            )
+        }
+        EtherTypes::Ipv6 => {
+            let header = payload.get(..IPV6_HEADER_LEN)?;
+            let payload_len = u16::from_be_bytes([header[4], header[5]]) as usize;
+            let end = (IPV6_HEADER_LEN + payload_len).min(payload.len());
+            let upper_layer = walk_extension_headers(
+                IpNextHeaderProtocol(header[6]),
+                &payload[IPV6_HEADER_LEN..end],
+            );
+            if upper_layer.fragment.is_some() {
+                return None;
+            }
+            (
+                read_ipv6(&header[8..24]).to_string(),
+                read_ipv6(&header[24..40]).to_string(),
+                false,
+                upper_layer.protocol,
+                upper_layer.payload,
+            )
+        }
+        _ => return None,
+    };
+
+    let (kind, tunnel_id, inner) = match protocol {
+        IpNextHeaderProtocols::Gre => parse_gre(data)?,
+        IpNextHeaderProtocols::Ipv4 => {
+            let kind = if outer_is_ipv4 { "IPIP" } else { "4in6" };
+            (kind, None, InnerFrame::Layer3(EtherTypes::Ipv4, data))
+        }
+        IpNextHeaderProtocols::Ipv6 => {
+            let kind = if outer_is_ipv4 { "6in4" } else { "6in6" };
+            (kind, None, InnerFrame::Layer3(EtherTypes::Ipv6, data))
+        }
+        IpNextHeaderProtocols::Udp => parse_udp_tunnel(data)?,
+        _ => return None,
+    };
+
+    Some((
+        TunnelInfos {
+            kind: kind.to_string(),
+            outer_ip_source,
+            outer_ip_destination,
+            tunnel_id,
+            ..Default::default()
+        },
+        inner,
+    ))
+}
+
+/// Décode un en-tête GRE (RFC 2784 et 2890) et, le cas échéant, l'en-tête ERSPAN qui le suit.
+fn parse_gre(data: &[u8]) -> Option<(&'static str, Option<u32>, InnerFrame<'_>)> {
+    let header = data.get(..4)?;
+    let checksum_present = header[0] & 0x80 != 0;
+    let key_present = header[0] & 0x20 != 0;
+    let sequence_present = header[0] & 0x10 != 0;
+    // Seule la version 0 est un tunnel ; la version 1 est le GRE amélioré de PPTP
+    if header[1] & 0x07 != 0 {
+        return None;
+    }
+    let protocol = u16::from_be_bytes([header[2], header[3]]);
+
+    let mut offset = 4;
+    if checksum_present {
+        offset += 4;
+    }
+    let key = if key_present {
+        let key = data.get(offset..offset + 4)?;
+        offset += 4;
+        Some(u32::from_be_bytes([key[0], key[1], key[2], key[3]]))
+    } else {
+        None
+    };
+    if sequence_present {
+        offset += 4;
+    }
+    let payload = data.get(offset..)?;
+
+    match protocol {
+        GRE_TRANSPARENT_ETHERNET_BRIDGING => Some(("GRE", key, InnerFrame::Ethernet(payload))),
+        // ERSPAN type I n'a pas d'en-tête ni de numéro de séquence GRE
+        GRE_ERSPAN_TYPE_II if !sequence_present => {
+            Some(("ERSPAN I", None, InnerFrame::Ethernet(payload)))
+        }
+        GRE_ERSPAN_TYPE_II => {
+            let header = payload.get(..8)?;
+            Some((
+                "ERSPAN II",
+                Some(erspan_session_id(header)),
+                InnerFrame::Ethernet(&payload[8..]),
+            ))
+        }
+        GRE_ERSPAN_TYPE_III => {
+            let header = payload.get(..12)?;
+            // Le bit O annonce un sous-en-tête de plateforme de 8 octets
+            let header_len = if header[11] & 0x01 != 0 { 20 } else { 12 };
+            Some((
+                "ERSPAN III",
+                Some(erspan_session_id(header)),
+                InnerFrame::Ethernet(payload.get(header_len..)?),
+            ))
+        }
+        _ => Some((
+            "GRE",
+            key,
+            InnerFrame::Layer3(EtherType::new(protocol), payload),
+        )),
+    }
+}
+
+/// Identifiant de session ERSPAN (10 bits), commun aux en-têtes de type II et III.
+fn erspan_session_id(header: &[u8]) -> u32 {
+    (u16::from_be_bytes([header[2], header[3]]) & 0x03ff) as u32
+}
+
+/// Décode les tunnels VXLAN (RFC 7348) et Geneve (RFC 8926) transportés par UDP.
+fn parse_udp_tunnel(data: &[u8]) -> Option<(&'static str, Option<u32>, InnerFrame<'_>)> {
+    let udp_header = data.get(..8)?;
+    let port_destination = u16::from_be_bytes([udp_header[2], udp_header[3]]);
+    let udp_payload = &data[8..];
+
+    match port_destination {
+        VXLAN_PORT => {
+            let header = udp_payload.get(..8)?;
+            // Le drapeau I indique un VNI valide
+            let vni = (header[0] & 0x08 != 0).then(|| read_vni(&header[4..7]));
+            Some(("VXLAN", vni, InnerFrame::Ethernet(&udp_payload[8..])))
+        }
+        GENEVE_PORT => {
+            let header = udp_payload.get(..8)?;
+            if header[0] >> 6 != 0 {
+                return None;
+            }
+            let header_len = 8 + (header[0] & 0x3f) as usize * 4;
+            let protocol = u16::from_be_bytes([header[2], header[3]]);
+            let payload = udp_payload.get(header_len..)?;
+            let inner = if protocol == GRE_TRANSPARENT_ETHERNET_BRIDGING {
+                InnerFrame::Ethernet(payload)
+            } else {
+                InnerFrame::Layer3(EtherType::new(protocol), payload)
+            };
+            Some(("Geneve", Some(read_vni(&header[4..7])), inner))
+        }
+        _ => None,
+    }
+}
+
+fn read_vni(octets: &[u8]) -> u32 {
+    u32::from_be_bytes([0, octets[0], octets[1], octets[2]])
+}
+
+fn read_ipv6(octets: &[u8]) -> Ipv6Addr {
+    let mut address = [0u8; 16];
+    address.copy_from_slice(octets);
+    Ipv6Addr::from(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    /// Construit un en-tête IPv4 minimal.
+    fn ipv4_header(protocol: IpNextHeaderProtocol, payload_len: usize) -> Vec<u8> {
+        let total_len = (20 + payload_len) as u16;
+        let mut header = vec![0x45, 0, 0, 0, 0, 0, 0, 0, 64, protocol.0, 0, 0];
+        header[2..4].copy_from_slice(&total_len.to_be_bytes());
+        header.extend_from_slice(&Ipv4Addr::new(192, 0, 2, 1).octets());
+        header.extend_from_slice(&Ipv4Addr::new(192, 0, 2, 2).octets());
+        header
+    }
+
+    #[test]
+    fn test_decapsulate_vxlan() {
+        let inner_frame = [0xaa; 14];
+        let mut udp = vec![0xc0, 0x00, 0x12, 0xb5, 0, 0, 0, 0];
+        udp.extend_from_slice(&[0x08, 0, 0, 0, 0x00, 0x13, 0x88, 0x00]);
+        udp.extend_from_slice(&inner_frame);
+        let mut packet = ipv4_header(IpNextHeaderProtocols::Udp, udp.len());
+        packet.extend_from_slice(&udp);
+
+        let (tunnel, inner) = decapsulate(EtherTypes::Ipv4, &packet).unwrap();
+
+        assert_eq!(tunnel.kind, "VXLAN");
+        assert_eq!(tunnel.tunnel_id, Some(5000));
+        assert_eq!(tunnel.outer_ip_source, "192.0.2.1");
+        assert_eq!(tunnel.outer_ip_destination, "192.0.2.2");
+        assert_eq!(inner, InnerFrame::Ethernet(&inner_frame));
+    }
+
+    #[test]
+    fn test_decapsulate_gre_key_and_erspan() {
+        // GRE avec clé 42 transportant IPv4
+        let mut gre = vec![0x20, 0x00, 0x08, 0x00, 0, 0, 0, 42, 0x45];
+        let mut packet = ipv4_header(IpNextHeaderProtocols::Gre, gre.len());
+        packet.extend_from_slice(&gre);
+
+        let (tunnel, inner) = decapsulate(EtherTypes::Ipv4, &packet).unwrap();
+        assert_eq!(tunnel.kind, "GRE");
+        assert_eq!(tunnel.tunnel_id, Some(42));
+        assert_eq!(inner, InnerFrame::Layer3(EtherTypes::Ipv4, &[0x45]));
+
+        // ERSPAN type II, session 7
+        gre = vec![0x10, 0x00, 0x88, 0xbe, 0, 0, 0, 1];
+        gre.extend_from_slice(&[0x10, 0x00, 0x00, 0x07, 0, 0, 0, 0]);
+        gre.extend_from_slice(&[0xbb; 14]);
+        let mut packet = ipv4_header(IpNextHeaderProtocols::Gre, gre.len());
+        packet.extend_from_slice(&gre);
+
+        let (tunnel, inner) = decapsulate(EtherTypes::Ipv4, &packet).unwrap();
+        assert_eq!(tunnel.kind, "ERSPAN II");
+        assert_eq!(tunnel.tunnel_id, Some(7));
+        assert_eq!(inner, InnerFrame::Ethernet(&[0xbb; 14]));
+    }
+
+    #[test]
+    fn test_decapsulate_6in4_and_plain_ip() {
+        let mut packet = ipv4_header(IpNextHeaderProtocols::Ipv6, 1);
+        packet.push(0x60);
+        let (tunnel, inner) = decapsulate(EtherTypes::Ipv4, &packet).unwrap();
+        assert_eq!(tunnel.kind, "6in4");
+        assert_eq!(inner, InnerFrame::Layer3(EtherTypes::Ipv6, &[0x60]));
+
+        let packet = ipv4_header(IpNextHeaderProtocols::Tcp, 0);
+        assert!(decapsulate(EtherTypes::Ipv4, &packet).is_none());
+    }
+}
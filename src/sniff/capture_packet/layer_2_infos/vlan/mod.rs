@@ -0,0 +1,112 @@
+//! # Étiquettes VLAN (802.1Q et 802.1ad)
+//!
+//! Une trame peut porter une étiquette VLAN (802.1Q) ou deux (QinQ, 802.1ad) entre l'en-tête Ethernet et
+//! la charge utile. Ce module retire ces étiquettes en conservant l'identifiant de VLAN, la priorité (PCP)
+//! et le bit DEI de chacune, puis retourne l'EtherType et la charge utile qu'elles encapsulent,
+//! qu'il s'agisse d'IP, d'ARP, de LLDP ou de tout autre protocole.
+
+use pnet::packet::{
+    ethernet::{EtherType, EtherTypes},
+    vlan::VlanPacket,
+};
+use serde::Serialize;
+
+/// Étiquette VLAN d'une trame.
+#[derive(Debug, Default, Serialize, Clone, Copy, Eq, Hash, PartialEq)]
+pub struct VlanTag {
+    /// Identifiant du VLAN (VID), de 0 à 4095.
+    pub vlan_id: u16,
+    /// Priorité 802.1p (PCP), de 0 à 7.
+    pub priority: u8,
+    /// Bit DEI : la trame peut être supprimée en priorité en cas de congestion.
+    pub drop_eligible: bool,
+}
+
+/// Étiquettes VLAN retirées d'une trame.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct VlanTags {
+    /// Étiquette externe (S-tag en QinQ, ou étiquette unique).
+    pub outer: Option<VlanTag>,
+    /// Étiquette interne (C-tag en QinQ).
+    pub inner: Option<VlanTag>,
+}
+
+/// Indique si l'EtherType annonce une étiquette VLAN (802.1Q, 802.1ad ou QinQ historique).
+pub fn is_vlan_ethertype(ethertype: EtherType) -> bool {
+    matches!(
+        ethertype,
+        EtherTypes::Vlan | EtherTypes::PBridge | EtherTypes::QinQ
+    )
+}
+
+/// Retire les étiquettes VLAN en tête de la charge utile et retourne l'EtherType et la charge utile encapsulés.
+///
+/// Seules les deux premières étiquettes sont conservées ; les suivantes éventuelles sont retirées sans être
+/// enregistrées. Une étiquette tronquée arrête le retrait et laisse l'EtherType VLAN.
+pub fn strip_vlan_tags(ethertype: EtherType, payload: &[u8]) -> (VlanTags, EtherType, &[u8]) {
+    let mut tags = VlanTags::default();
+    let mut ethertype = ethertype;
+    let mut payload = payload;
+
+    while is_vlan_ethertype(ethertype) {
+        let vlan_packet = match VlanPacket::new(payload) {
+            Some(vlan_packet) => vlan_packet,
+            None => break,
+        };
+        let tag = VlanTag {
+            vlan_id: vlan_packet.get_vlan_identifier(),
+            priority: vlan_packet.get_priority_code_point().0,
+            drop_eligible: vlan_packet.get_drop_eligible_indicator() != 0,
+        };
+        if tags.outer.is_none() {
+            tags.outer = Some(tag);
+        } else if tags.inner.is_none() {
+            tags.inner = Some(tag);
+        }
+
+        ethertype = vlan_packet.get_ethertype();
+        payload = &payload[VlanPacket::minimum_packet_size()..];
+    }
+    (tags, ethertype, payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_qinq_tags() {
+        // S-tag VLAN 100, PCP 5, DEI ; C-tag VLAN 20 ; puis ARP
+        let payload = [
+            0xb0, 0x64, 0x81, 0x00, // TCI S-tag, EtherType 802.1Q
+            0x00, 0x14, 0x08, 0x06, // TCI C-tag, EtherType ARP
+            0xaa, 0xbb,
+        ];
+
+        let (tags, ethertype, inner_payload) = strip_vlan_tags(EtherTypes::PBridge, &payload);
+
+        assert_eq!(
+            tags.outer,
+            Some(VlanTag {
+                vlan_id: 100,
+                priority: 5,
+                drop_eligible: true,
+            })
+        );
+        assert_eq!(tags.inner.map(|tag| tag.vlan_id), Some(20));
+        assert_eq!(ethertype, EtherTypes::Arp);
+        assert_eq!(inner_payload, &[0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn test_strip_untagged_and_truncated() {
+        let (tags, ethertype, payload) = strip_vlan_tags(EtherTypes::Ipv4, &[1, 2, 3]);
+        assert_eq!(tags, VlanTags::default());
+        assert_eq!(ethertype, EtherTypes::Ipv4);
+        assert_eq!(payload, &[1, 2, 3]);
+
+        let (tags, ethertype, _) = strip_vlan_tags(EtherTypes::Vlan, &[0x00, 0x0a]);
+        assert_eq!(tags.outer, None);
+        assert_eq!(ethertype, EtherTypes::Vlan);
+    }
+}
@@ -5,64 +5,204 @@
 //!
 //! ## Fonctions
 //!
-//! - [`all_interfaces`](fn.all_interfaces.html): Capture le trafic réseau sur toutes les interfaces disponibles.
+//! - [`all_interfaces`](fn.all_interfaces.html): Capture le trafic réseau sur toutes les interfaces disponibles,
+//!   en redécouvrant périodiquement les interfaces branchées ou retirées en cours de session.
 //! - [`one_interface`](fn.one_interface.html): Capture le trafic réseau sur une interface spécifique.
 //! - [`capture_packets`](fn.capture_packets.html): Fonction interne pour démarrer la capture des paquets sur une interface donnée.
+//! - [`stop_capture`](fn.stop_capture.html): Demande l'arrêt de la capture en cours.
 //!
 //! ## Tests
 //!
 //! Ce module contient également des tests pour la fonction `update_state_with_packet` et la fonction `capture_packets`.
 
-use log::{error, info};
+use log::{error, info, warn};
 use pnet::datalink::Channel::Ethernet;
 use pnet::datalink::{self, NetworkInterface};
-use pnet::packet::ethernet::EthernetPacket;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
 use tauri::{Manager, State};
+pub(crate) mod capture_config;
+pub(crate) mod diagnostics;
+pub(crate) mod interface_stats;
 pub(crate) mod layer_2_infos;
+pub(crate) mod session_event;
+pub(crate) mod tcp_connections;
 
-use crate::tauri_state::SonarState;
+use crate::tauri_state::{
+    CaptureConfigState, DhcpLeasesState, DnsNamesState, InterfaceStatsState, SonarState,
+};
 
-use self::layer_2_infos::PacketInfos;
+use self::capture_config::CaptureConfig;
+use self::diagnostics::diagnose_interface;
+use self::interface_stats::{update_interface_stats, InterfaceStats};
+use self::layer_2_infos::layer_3_infos::layer_4_infos::layer_7_infos::{
+    dhcp::record_leases, dns::resolve_names,
+};
+use self::layer_2_infos::{layer_3_infos::Reassemblers, link_type::LinkType, PacketInfos};
+use self::session_event::{emit_session_event, SessionEvent};
+use self::tcp_connections::TcpConnectionTracker;
+
+/// Intervalle entre deux redécouvertes des interfaces réseau en mode "toutes les interfaces".
+const INTERFACE_REDISCOVERY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Numéro de la session de capture en cours, incrémenté à chaque demande d'arrêt.
+///
+/// Chaque capture retient le numéro de session au démarrage, et s'arrête dès qu'il a changé.
+static CAPTURE_SESSION: AtomicU64 = AtomicU64::new(0);
+
+/// Demande l'arrêt de la capture en cours.
+///
+/// Les threads de capture s'arrêtent à la prochaine trame reçue, ou à l'expiration du délai de lecture
+/// ([`CaptureConfig::read_timeout_ms`]) si aucune trame n'arrive.
+pub fn stop_capture() {
+    CAPTURE_SESSION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Retourne le numéro de la session de capture en cours.
+fn current_session() -> u64 {
+    CAPTURE_SESSION.load(Ordering::SeqCst)
+}
+
+/// Indique si l'arrêt de la session de capture `session` a été demandé.
+fn is_stopped(session: u64) -> bool {
+    current_session() != session
+}
 
 /// Capture le trafic réseau sur toutes les interfaces disponibles.
 ///
+/// La liste des interfaces est relue toutes les [`INTERFACE_REDISCOVERY_INTERVAL`] : une capture est
+/// démarrée sur chaque nouvelle interface (clé USB réseau branchée en cours de session, par exemple)
+/// et la disparition d'une interface est signalée à l'interface utilisateur par un [`SessionEvent`].
+///
 /// # Arguments
 ///
 /// * `app` - Handle vers l'application Tauri, utilisé pour interagir avec l'interface utilisateur.
 /// * `state` - État global de l'application, contenant les données capturées.
+/// * `configs` - Configurations de capture par interface.
+/// * `stats` - Statistiques de capture par interface, mises à jour par chaque thread de capture.
+/// * `dns_names` - Table des noms DNS, apprise à partir des réponses DNS capturées.
+/// * `dhcp_leases` - Inventaire des baux DHCP, alimenté par les messages DHCP capturés.
 
-pub fn all_interfaces(app: tauri::AppHandle, state: State<SonarState>) {
-    let mut handles = vec![];
+pub fn all_interfaces(
+    app: tauri::AppHandle,
+    state: State<SonarState>,
+    configs: State<CaptureConfigState>,
+    stats: State<InterfaceStatsState>,
+    dns_names: State<DnsNamesState>,
+    dhcp_leases: State<DhcpLeasesState>,
+) {
+    let session = current_session();
     let (tx, rx) = mpsc::channel::<PacketInfos>();
 
     let state_clone = state.0.clone();
+    let dns_names = dns_names.0.clone();
+    let dhcp_leases = dhcp_leases.0.clone();
 
     thread::spawn(move || {
-        for new_packet in rx {
+        let mut tcp_connections = TcpConnectionTracker::default();
+        for mut new_packet in rx {
+            tcp_connections.track(&mut new_packet);
+            resolve_names(&dns_names, &mut new_packet);
+            record_leases(&dhcp_leases, &new_packet);
             update_state_with_packet(state_clone.clone(), new_packet);
         }
     });
 
-    // threads qui ecoute les trames
-    let interfaces = datalink::interfaces();
-    for interface in interfaces {
-        let app2 = app.clone();
-        let tx_clone = tx.clone();
-        let handle = thread::spawn(move || {
-            capture_packets(app2, interface, tx_clone);
-        });
-        handles.push(handle);
-    }
-    // Wait for all threads to complete
-    for handle in handles {
-        match handle.join() {
-            Ok(_) => (), // Thread completed without panicking
-            Err(e) => eprintln!("A thread panicked: {:?}", e),
+    // Noms des interfaces vues lors de la dernière découverte
+    let mut known_interfaces: HashSet<String> = HashSet::new();
+    // Noms des interfaces dont le thread de capture est en cours : une capture interrompue par une erreur
+    // (interface pas encore active, retirée...) est relancée à la découverte suivante
+    let captured_interfaces: Arc<Mutex<HashSet<String>>> = Arc::new(Mutex::new(HashSet::new()));
+    let mut first_discovery = true;
+
+    while !is_stopped(session) {
+        let interfaces = datalink::interfaces();
+        let (added, removed) = diff_interfaces(&known_interfaces, &interfaces);
+
+        for interface_name in removed {
+            warn!("Interface retirée pendant la capture : {}", interface_name);
+            known_interfaces.remove(&interface_name);
+            emit_session_event(
+                &app,
+                SessionEvent::InterfaceRemoved {
+                    interface: interface_name,
+                },
+            );
         }
+
+        for interface in added {
+            known_interfaces.insert(interface.name.clone());
+            if !first_discovery {
+                info!("Nouvelle interface détectée : {}", interface.name);
+                emit_session_event(
+                    &app,
+                    SessionEvent::InterfaceAdded {
+                        interface: interface.name.clone(),
+                    },
+                );
+            }
+        }
+
+        // threads qui ecoute les trames
+        for interface in interfaces {
+            let newly_captured = captured_interfaces
+                .lock()
+                .expect("Failed to lock the mutex")
+                .insert(interface.name.clone());
+            if !newly_captured {
+                continue;
+            }
+
+            let app2 = app.clone();
+            let tx_clone = tx.clone();
+            let config = configs.get(&interface.name);
+            let stats_clone = stats.0.clone();
+            let captured_interfaces = captured_interfaces.clone();
+            thread::spawn(move || {
+                let name = interface.name.clone();
+                if !capture_packets(app2, interface, &config, stats_clone, tx_clone, session) {
+                    captured_interfaces
+                        .lock()
+                        .expect("Failed to lock the mutex")
+                        .remove(&name);
+                }
+            });
+        }
+
+        first_discovery = false;
+        thread::sleep(INTERFACE_REDISCOVERY_INTERVAL);
     }
+    info!("Arrêt de la redécouverte des interfaces");
+}
+
+/// Compare les interfaces actuellement présentes avec celles connues lors de la découverte précédente.
+///
+/// Retourne les interfaces nouvellement apparues et les noms des interfaces qui ont disparu.
+fn diff_interfaces(
+    known: &HashSet<String>,
+    current: &[NetworkInterface],
+) -> (Vec<NetworkInterface>, Vec<String>) {
+    let current_names: HashSet<&str> = current.iter().map(|iface| iface.name.as_str()).collect();
+
+    let added = current
+        .iter()
+        .filter(|iface| !known.contains(&iface.name))
+        .cloned()
+        .collect();
+
+    let mut removed: Vec<String> = known
+        .iter()
+        .filter(|name| !current_names.contains(name.as_str()))
+        .cloned()
+        .collect();
+    removed.sort();
+
+    (added, removed)
 }
 
 /// Capture le trafic réseau sur une interface spécifique.
@@ -72,18 +212,37 @@ pub fn all_interfaces(app: tauri::AppHandle, state: State<SonarState>) {
 /// * `app` - Handle vers l'application Tauri.
 /// * `interface` - Nom de l'interface réseau sur laquelle effectuer la capture.
 /// * `state` - État global de l'application.
-pub fn one_interface(app: tauri::AppHandle, interface: &str, state: State<SonarState>) {
+/// * `configs` - Configurations de capture par interface.
+/// * `stats` - Statistiques de capture par interface.
+/// * `dns_names` - Table des noms DNS, apprise à partir des réponses DNS capturées.
+/// * `dhcp_leases` - Inventaire des baux DHCP, alimenté par les messages DHCP capturés.
+pub fn one_interface(
+    app: tauri::AppHandle,
+    interface: &str,
+    state: State<SonarState>,
+    configs: State<CaptureConfigState>,
+    stats: State<InterfaceStatsState>,
+    dns_names: State<DnsNamesState>,
+    dhcp_leases: State<DhcpLeasesState>,
+) {
     info!("L'interface choisie est: {}", interface);
+    let session = current_session();
 
     // thread fifo
     let (tx, rx) = mpsc::channel();
 
     // Clone the state for the thread
     let state_clone = state.0.clone();
+    let dns_names = dns_names.0.clone();
+    let dhcp_leases = dhcp_leases.0.clone();
 
     // Spawn a thread to process packets
     thread::spawn(move || {
-        for new_packet in rx {
+        let mut tcp_connections = TcpConnectionTracker::default();
+        for mut new_packet in rx {
+            tcp_connections.track(&mut new_packet);
+            resolve_names(&dns_names, &mut new_packet);
+            record_leases(&dhcp_leases, &new_packet);
             update_state_with_packet(state_clone.clone(), new_packet);
         }
     });
@@ -98,7 +257,15 @@ pub fn one_interface(app: tauri::AppHandle, interface: &str, state: State<SonarS
             panic!("Aucune interface de ce type: '{}'", interface);
         }
     };
-    capture_packets(app, captured_interface, tx);
+    let config = configs.get(interface);
+    capture_packets(
+        app,
+        captured_interface,
+        &config,
+        stats.0.clone(),
+        tx,
+        session,
+    );
 }
 
 /// Fonction interne pour démarrer la capture des paquets sur une interface donnée.
@@ -107,41 +274,73 @@ pub fn one_interface(app: tauri::AppHandle, interface: &str, state: State<SonarS
 ///
 /// * `app` - Handle vers l'application Tauri.
 /// * `interface` - Interface réseau sur laquelle capturer les paquets.
+/// * `config` - Configuration du canal de capture (promiscuité, tampons, délai de lecture).
+/// * `stats` - Statistiques de capture partagées, indexées par nom d'interface.
 /// * `tx` - Canal de transmission pour envoyer les informations de paquets capturés.
+/// * `session` - Numéro de la session de capture : la capture s'arrête lorsque son arrêt est demandé.
+///
+/// Retourne `false` si la capture a été interrompue par une erreur (canal impossible à ouvrir, erreur de
+/// lecture...), `true` si son arrêt a été demandé.
 
 fn capture_packets(
     app: tauri::AppHandle,
     interface: datalink::NetworkInterface,
+    config: &CaptureConfig,
+    stats: Arc<Mutex<HashMap<String, InterfaceStats>>>,
     tx: mpsc::Sender<PacketInfos>,
-) {
-    let (_, mut rx) = match datalink::channel(&interface, Default::default()) {
+    session: u64,
+) -> bool {
+    let (_, mut rx) = match datalink::channel(&interface, config.into()) {
         Ok(Ethernet(tx, rx)) => (tx, rx),
         Ok(_) => {
             error!("Type de canal non géré : {}", &interface);
-            panic!("Type de canal non géré : {}", &interface)
+            return false;
         }
         Err(e) => {
             error!(
-                "Une erreur s'est produite lors de la création du canal de liaison de données: {}",
-                &interface
+                "Une erreur s'est produite lors de la création du canal de liaison de données: {}: {}",
+                &interface, e
+            );
+            // Signale la cause probable (droits, interface désactivée...) à l'interface utilisateur
+            emit_session_event(
+                &app,
+                SessionEvent::CaptureFailed {
+                    diagnostic: diagnose_interface(&interface),
+                },
             );
-            panic!(
-                "Une erreur s'est produite lors de la création du canal de liaison de données: {}",
-                e
-            )
+            return false;
         }
     };
     let main_window = app.get_window("main").unwrap();
+    let link_type =
+        match LinkType::from_pcap_linktype(LinkType::pcap_linktype_for_interface(&interface)) {
+            Some(link_type) => link_type,
+            None => {
+                error!("Type de lien non géré : {}", &interface);
+                return false;
+            }
+        };
+
+    // État de réassemblage propre à ce thread et à cette session de capture
+    let mut reassemblers = Reassemblers::default();
 
     info!(
-        "Démarrage du thread de lecture de paquets sur l'interface :{}",
-        &interface
+        "Démarrage du thread de lecture de paquets sur l'interface :{} ({:?})",
+        &interface, link_type
     );
-    loop {
+    while !is_stopped(session) {
         match rx.next() {
             Ok(packet) => {
-                if let Some(ethernet_packet) = EthernetPacket::new(packet) {
-                    let packet_info = PacketInfos::new(&interface.name, &ethernet_packet);
+                let packet_info =
+                    PacketInfos::from_frame(&interface.name, link_type, packet, &mut reassemblers);
+                // La trame est comptée sous son propre EtherType (802.1Q, PPPoE, IP d'un tunnel...),
+                // et non sous celui du protocole encapsulé
+                let ethertype = link_type.ethertype(packet).map(|e| e.to_string());
+                update_interface_stats(&stats, &interface.name, |s| {
+                    s.record_frame(packet.len(), ethertype.as_deref())
+                });
+
+                if let Some(packet_info) = packet_info {
                     //println!("{packet_info}");
                     if let Err(err) = main_window.emit("frame", &packet_info) {
                         error!("Failed to emit event: {}", err);
@@ -151,12 +350,19 @@ fn capture_packets(
                     }
                 }
             }
+            // Le délai de lecture a expiré sans trame reçue : la capture continue, sauf si son arrêt
+            // a été demandé
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => continue,
             Err(e) => {
                 error!("An error occurred while reading: {}", e);
+                update_interface_stats(&stats, &interface.name, |s| s.record_read_error());
                 break;
             }
         }
     }
+    info!("Arrêt de la capture sur l'interface :{}", &interface);
+    // La boucle n'est quittée avant la demande d'arrêt que sur une erreur de lecture
+    is_stopped(session)
 }
 
 fn update_state_with_packet(state: Arc<Mutex<Vec<(PacketInfos, u32)>>>, new_packet: PacketInfos) {
@@ -166,15 +372,35 @@ fn update_state_with_packet(state: Arc<Mutex<Vec<(PacketInfos, u32)>>>, new_pack
     for (existing_packet, count) in state_locked.iter_mut() {
         // Définissez ici la logique pour déterminer si `new_packet` est "le même" que `existing_packet`.
         // Cela pourrait dépendre des adresses MAC, des adresses IP, du protocole, etc.
-        if existing_packet.mac_address_source == new_packet.mac_address_source &&
-           existing_packet.mac_address_destination == new_packet.mac_address_destination &&
-           existing_packet.interface == new_packet.interface &&
-           existing_packet.l_3_protocol == new_packet.l_3_protocol &&
-           existing_packet.layer_3_infos == new_packet.layer_3_infos 
+        if existing_packet.mac_address_source == new_packet.mac_address_source
+            && existing_packet.mac_address_destination == new_packet.mac_address_destination
+            && existing_packet.interface == new_packet.interface
+            && existing_packet.l_3_protocol == new_packet.l_3_protocol
+            && existing_packet.same_vlans(&new_packet)
+            && existing_packet
+                .layer_3_infos
+                .same_flow(&new_packet.layer_3_infos)
         {
             // Un paquet correspondant a été trouvé, incrémentez son compteur
             *count += 1;
             existing_packet.packet_size += new_packet.packet_size;
+            // Conserve les détails du dernier paquet (séquence ICMP...), qui ne font pas partie du flux,
+            // ainsi que les anomalies et les informations applicatives relevées sur l'ensemble du flux
+            let anomalies = std::mem::take(&mut existing_packet.layer_3_infos.anomalies);
+            let layer_7_infos = existing_packet
+                .layer_3_infos
+                .layer_4_infos
+                .layer_7_infos
+                .take();
+            existing_packet.layer_3_infos = new_packet.layer_3_infos.clone();
+            existing_packet.layer_3_infos.anomalies.extend(anomalies);
+            let new_layer_7_infos = &mut existing_packet.layer_3_infos.layer_4_infos.layer_7_infos;
+            if let Some(mut layer_7_infos) = layer_7_infos {
+                if let Some(new_layer_7_infos) = new_layer_7_infos.take() {
+                    layer_7_infos.merge(new_layer_7_infos);
+                }
+                *new_layer_7_infos = Some(layer_7_infos);
+            }
             is_found = true;
             break;
         }
@@ -189,15 +415,53 @@ fn update_state_with_packet(state: Arc<Mutex<Vec<(PacketInfos, u32)>>>, new_pack
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pnet::datalink::dummy::dummy_interface;
+    use pnet::packet::ethernet::EthernetPacket;
     use std::sync::{Arc, Mutex};
 
+    #[test]
+    fn test_stop_capture() {
+        let session = current_session();
+        assert!(!is_stopped(session));
+
+        stop_capture();
+        assert!(is_stopped(session));
+        assert!(!is_stopped(current_session()));
+    }
+
+    #[test]
+    fn test_diff_interfaces() {
+        let eth0 = dummy_interface(0);
+        let eth1 = dummy_interface(1);
+
+        // Première découverte : toutes les interfaces sont nouvelles
+        let (added, removed) = diff_interfaces(&HashSet::new(), std::slice::from_ref(&eth0));
+        assert_eq!(added, vec![eth0.clone()]);
+        assert!(removed.is_empty());
+
+        // Une interface est branchée en cours de session
+        let known: HashSet<String> = [eth0.name.clone()].into_iter().collect();
+        let (added, removed) = diff_interfaces(&known, &[eth0.clone(), eth1.clone()]);
+        assert_eq!(added, vec![eth1.clone()]);
+        assert!(removed.is_empty());
+
+        // L'interface connue disparaît
+        let (added, removed) = diff_interfaces(&known, std::slice::from_ref(&eth1));
+        assert_eq!(added, vec![eth1]);
+        assert_eq!(removed, vec![eth0.name]);
+    }
+
     #[test]
     fn test_update_state_with_packet() {
         let state = Arc::new(Mutex::new(vec![]));
         let buffer = vec![0u8; 64]; // Local buffer
         let ethernet_packet = EthernetPacket::new(&buffer).unwrap();
-        let packet = PacketInfos::new(&String::from("eth0"),  &ethernet_packet);
-        
+        let packet = PacketInfos::new(
+            &String::from("eth0"),
+            &ethernet_packet,
+            &mut Reassemblers::default(),
+        );
+
         // Add a packet to the state and verify it
         update_state_with_packet(state.clone(), packet.clone());
         assert_eq!(state.lock().unwrap().len(), 1);
@@ -208,11 +472,88 @@ mod tests {
         assert_eq!(state.lock().unwrap()[0].1, 2);
 
         // Add a different packet and verify that it's added as a new entry
-        let different_packet = PacketInfos::new(&String::from("eth2"), &ethernet_packet);
+        let different_packet = PacketInfos::new(
+            &String::from("eth2"),
+            &ethernet_packet,
+            &mut Reassemblers::default(),
+        );
         update_state_with_packet(state.clone(), different_packet.clone());
         assert_eq!(state.lock().unwrap().len(), 2);
     }
 
+    #[test]
+    fn test_update_state_with_packet_ignores_icmp_sequence() {
+        use layer_2_infos::layer_3_infos::layer_4_infos::icmp::IcmpInfos;
+
+        let state = Arc::new(Mutex::new(vec![]));
+        let ping = |sequence: u16| {
+            let mut packet = PacketInfos::default();
+            packet.layer_3_infos.ip_source = Some("10.0.0.1".to_string());
+            packet.layer_3_infos.ip_destination = Some("10.0.0.2".to_string());
+            packet.layer_3_infos.layer_4_infos.icmp = Some(IcmpInfos {
+                icmp_type: 8,
+                message: "Echo Request".to_string(),
+                identifier: Some(1),
+                sequence: Some(sequence),
+                ..Default::default()
+            });
+            packet
+        };
+
+        // Deux pings successifs appartiennent au même flux
+        update_state_with_packet(state.clone(), ping(1));
+        update_state_with_packet(state.clone(), ping(2));
+        {
+            let state_locked = state.lock().unwrap();
+            assert_eq!(state_locked.len(), 1);
+            assert_eq!(state_locked[0].1, 2);
+            let icmp = state_locked[0].0.layer_3_infos.layer_4_infos.icmp.as_ref();
+            assert_eq!(icmp.unwrap().sequence, Some(2));
+        }
+
+        // Un message d'un autre type forme un nouveau flux
+        let mut unreachable = ping(3);
+        unreachable.layer_3_infos.layer_4_infos.icmp = Some(IcmpInfos {
+            icmp_type: 3,
+            icmp_code: 1,
+            ..Default::default()
+        });
+        update_state_with_packet(state.clone(), unreachable);
+        assert_eq!(state.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_update_state_with_packet_keeps_layer_7_infos() {
+        use layer_2_infos::layer_3_infos::layer_4_infos::layer_7_infos::Layer7Infos;
+
+        let state = Arc::new(Mutex::new(vec![]));
+        let segment = |protocol: Option<&str>| {
+            let mut packet = PacketInfos::default();
+            packet.layer_3_infos.ip_source = Some("10.0.0.1".to_string());
+            packet.layer_3_infos.layer_4_infos.port_destination = Some("8000".to_string());
+            packet.layer_3_infos.layer_4_infos.layer_7_infos =
+                protocol.map(|protocol| Layer7Infos {
+                    protocol: Some(protocol.to_string()),
+                    ..Default::default()
+                });
+            packet
+        };
+
+        // Le segment suivant ne porte pas d'information applicative
+        update_state_with_packet(state.clone(), segment(Some("HTTP")));
+        update_state_with_packet(state.clone(), segment(None));
+
+        let state_locked = state.lock().unwrap();
+        assert_eq!(state_locked.len(), 1);
+        let layer_7_infos = state_locked[0]
+            .0
+            .layer_3_infos
+            .layer_4_infos
+            .layer_7_infos
+            .as_ref();
+        assert_eq!(layer_7_infos.unwrap().protocol.as_deref(), Some("HTTP"));
+    }
+
     // #[test]
     // fn test_capture_packets() {
     //     // Create a mock channel
@@ -0,0 +1,59 @@
+//! Événements de session de capture.
+//!
+//! Ces événements signalent à l'interface utilisateur les changements survenus pendant une capture
+//! (interfaces branchées ou retirées, capture impossible). Ils sont émis sur le canal Tauri `session_event`, à côté des
+//! trames émises sur le canal `frame`.
+
+use log::error;
+use serde::Serialize;
+use tauri::Manager;
+
+use super::diagnostics::CaptureDiagnostic;
+
+/// Événement survenu au cours d'une session de capture.
+#[derive(Debug, Serialize, Clone, Eq, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// Une nouvelle interface est apparue et sa capture a démarré.
+    InterfaceAdded { interface: String },
+    /// Une interface capturée a disparu du système.
+    InterfaceRemoved { interface: String },
+    /// Le canal de capture d'une interface n'a pas pu être ouvert.
+    CaptureFailed { diagnostic: CaptureDiagnostic },
+}
+
+/// Émet un événement de session vers la fenêtre principale de l'application.
+///
+/// # Arguments
+///
+/// * `app` - Handle vers l'application Tauri.
+/// * `event` - L'événement à transmettre à l'interface utilisateur.
+pub fn emit_session_event(app: &tauri::AppHandle, event: SessionEvent) {
+    match app.get_window("main") {
+        Some(main_window) => {
+            if let Err(err) = main_window.emit("session_event", &event) {
+                error!("Failed to emit session event: {}", err);
+            }
+        }
+        None => error!(
+            "Fenêtre principale introuvable pour l'événement {:?}",
+            event
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_event_serialization() {
+        let event = SessionEvent::InterfaceRemoved {
+            interface: String::from("usb0"),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"interface_removed","interface":"usb0"}"#
+        );
+    }
+}
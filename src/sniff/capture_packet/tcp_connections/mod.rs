@@ -0,0 +1,343 @@
+//! # Suivi des connexions TCP
+//!
+//! Un segment isolé ne permet pas de savoir quel côté a ouvert la connexion ni si elle a abouti. Ce module
+//! suit chaque connexion, identifiée par la paire de ses extrémités (adresse et port), à partir des drapeaux
+//! observés dans les deux sens :
+//!
+//! - SYN : le client ouvre la connexion ;
+//! - SYN-ACK : le serveur accepte ;
+//! - ACK du client après le SYN-ACK : la connexion est établie ;
+//! - FIN : fermeture d'un côté, puis des deux ;
+//! - RST : connexion refusée si le serveur répond ainsi à un SYN, interrompue sinon.
+//!
+//! Les connexions déjà ouvertes au début de la capture sont considérées comme établies, sans client connu.
+//! Le nombre de connexions suivies est borné : les connexions inactives, puis les plus anciennes, sont oubliées.
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+
+use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::layer_4_infos::tcp::{
+    TcpConnectionState, TcpInfos, TcpRole, ACK, FIN, RST, SYN,
+};
+use crate::sniff::capture_packet::layer_2_infos::PacketInfos;
+
+/// Nombre maximal de connexions suivies simultanément.
+const MAX_CONNECTIONS: usize = 65536;
+/// Délai d'inactivité au-delà duquel une connexion peut être oubliée.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+/// Intervalle minimal entre deux purges des connexions inactives.
+const IDLE_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Extrémité d'une connexion : adresse IP et port.
+type Endpoint = (String, String);
+/// Paire ordonnée des extrémités d'une connexion.
+type ConnectionKey = (Endpoint, Endpoint);
+
+/// Connexion en cours de suivi.
+#[derive(Debug)]
+struct Connection {
+    /// Extrémité qui a ouvert la connexion, si le SYN a été observé.
+    client: Option<Endpoint>,
+    state: TcpConnectionState,
+    /// Extrémité qui a envoyé le premier FIN. Le FIN est suivi par extrémité plutôt que par rôle, le client
+    /// n'étant pas connu pour les connexions déjà ouvertes au début de la capture.
+    fin_sender: Option<Endpoint>,
+    last_seen: Instant,
+}
+
+/// Suit l'état des connexions TCP observées par un thread de traitement des paquets.
+#[derive(Debug, Default)]
+pub struct TcpConnectionTracker {
+    connections: HashMap<ConnectionKey, Connection>,
+    /// Connexions classées par date de dernière activité, de la plus ancienne à la plus récente.
+    by_last_seen: BTreeSet<(Instant, ConnectionKey)>,
+    last_sweep: Option<Instant>,
+}
+
+impl TcpConnectionTracker {
+    /// Met à jour la connexion du paquet et y renseigne l'état de la connexion et le rôle de l'émetteur.
+    ///
+    /// Les paquets qui ne sont pas des segments TCP sont laissés inchangés.
+    pub fn track(&mut self, packet: &mut PacketInfos) {
+        self.track_at(packet, Instant::now());
+    }
+
+    fn track_at(&mut self, packet: &mut PacketInfos, now: Instant) {
+        let layer_3_infos = &mut packet.layer_3_infos;
+        let source = match (
+            &layer_3_infos.ip_source,
+            &layer_3_infos.layer_4_infos.port_source,
+        ) {
+            (Some(ip), Some(port)) => (ip.clone(), port.clone()),
+            _ => return,
+        };
+        let destination = match (
+            &layer_3_infos.ip_destination,
+            &layer_3_infos.layer_4_infos.port_destination,
+        ) {
+            (Some(ip), Some(port)) => (ip.clone(), port.clone()),
+            _ => return,
+        };
+        let tcp = match layer_3_infos.layer_4_infos.tcp.as_mut() {
+            Some(tcp) => tcp,
+            None => return,
+        };
+
+        let key = if source <= destination {
+            (source.clone(), destination.clone())
+        } else {
+            (destination.clone(), source.clone())
+        };
+        self.expire(now);
+        if !self.connections.contains_key(&key) && self.connections.len() >= MAX_CONNECTIONS {
+            self.evict_oldest();
+        }
+
+        let connection = self
+            .connections
+            .entry(key.clone())
+            .or_insert_with(|| Connection {
+                client: None,
+                state: TcpConnectionState::Established,
+                fin_sender: None,
+                last_seen: now,
+            });
+        self.by_last_seen
+            .remove(&(connection.last_seen, key.clone()));
+        self.by_last_seen.insert((now, key));
+        connection.last_seen = now;
+        connection.update(tcp, &source, &destination);
+
+        tcp.connection_state = Some(connection.state);
+        tcp.role = connection.client.as_ref().map(|client| {
+            if *client == source {
+                TcpRole::Client
+            } else {
+                TcpRole::Server
+            }
+        });
+    }
+
+    /// Oublie les connexions inactives depuis plus de [`IDLE_TIMEOUT`].
+    fn expire(&mut self, now: Instant) {
+        if self
+            .last_sweep
+            .is_some_and(|last_sweep| now.duration_since(last_sweep) < IDLE_SWEEP_INTERVAL)
+        {
+            return;
+        }
+        self.last_sweep = Some(now);
+
+        while self
+            .by_last_seen
+            .first()
+            .is_some_and(|(last_seen, _)| now.duration_since(*last_seen) >= IDLE_TIMEOUT)
+        {
+            if let Some((_, key)) = self.by_last_seen.pop_first() {
+                self.connections.remove(&key);
+            }
+        }
+    }
+
+    /// Libère de la place en oubliant la connexion la plus ancienne.
+    fn evict_oldest(&mut self) {
+        if let Some((_, key)) = self.by_last_seen.pop_first() {
+            self.connections.remove(&key);
+        }
+    }
+}
+
+impl Connection {
+    /// Applique les drapeaux d'un segment envoyé de `source` vers `destination`.
+    fn update(&mut self, tcp: &TcpInfos, source: &Endpoint, destination: &Endpoint) {
+        if tcp.has_flags(RST) {
+            let from_server = self.client.as_ref().is_some_and(|client| client != source);
+            self.state = match self.state {
+                TcpConnectionState::SynSent if from_server => TcpConnectionState::Refused,
+                TcpConnectionState::Refused => TcpConnectionState::Refused,
+                _ => TcpConnectionState::Reset,
+            };
+        } else if tcp.has_flags(SYN | ACK) {
+            if self.client.is_none() || self.state != TcpConnectionState::SynSent {
+                self.client = Some(destination.clone());
+            }
+            self.state = TcpConnectionState::SynAckReceived;
+        } else if tcp.has_flags(SYN) {
+            // Un nouveau SYN ouvre une nouvelle connexion sur la même paire d'extrémités
+            *self = Connection {
+                client: Some(source.clone()),
+                state: TcpConnectionState::SynSent,
+                fin_sender: None,
+                last_seen: self.last_seen,
+            };
+        } else if tcp.has_flags(FIN) {
+            match &self.fin_sender {
+                // FIN de l'autre extrémité : la connexion est fermée des deux côtés
+                Some(fin_sender) if fin_sender != source => self.state = TcpConnectionState::Closed,
+                // FIN retransmis
+                Some(_) => {}
+                None => {
+                    self.fin_sender = Some(source.clone());
+                    self.state = TcpConnectionState::Closing;
+                }
+            }
+        } else if self.state == TcpConnectionState::SynAckReceived
+            && self.client.as_ref() == Some(source)
+        {
+            self.state = TcpConnectionState::Established;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(source: (&str, u16), destination: (&str, u16), flags: u8) -> PacketInfos {
+        let mut packet = PacketInfos::default();
+        packet.layer_3_infos.ip_source = Some(source.0.to_string());
+        packet.layer_3_infos.ip_destination = Some(destination.0.to_string());
+        packet.layer_3_infos.layer_4_infos.port_source = Some(source.1.to_string());
+        packet.layer_3_infos.layer_4_infos.port_destination = Some(destination.1.to_string());
+        packet.layer_3_infos.layer_4_infos.tcp = Some(TcpInfos {
+            flags,
+            ..Default::default()
+        });
+        packet
+    }
+
+    fn track(
+        tracker: &mut TcpConnectionTracker,
+        mut packet: PacketInfos,
+    ) -> (Option<TcpConnectionState>, Option<TcpRole>) {
+        tracker.track(&mut packet);
+        let tcp = packet.layer_3_infos.layer_4_infos.tcp.unwrap();
+        (tcp.connection_state, tcp.role)
+    }
+
+    const CLIENT: (&str, u16) = ("10.0.0.1", 50000);
+    const SERVER: (&str, u16) = ("10.0.0.2", 443);
+
+    #[test]
+    fn test_handshake_and_close() {
+        let mut tracker = TcpConnectionTracker::default();
+
+        assert_eq!(
+            track(&mut tracker, segment(CLIENT, SERVER, SYN)),
+            (Some(TcpConnectionState::SynSent), Some(TcpRole::Client))
+        );
+        assert_eq!(
+            track(&mut tracker, segment(SERVER, CLIENT, SYN | ACK)),
+            (
+                Some(TcpConnectionState::SynAckReceived),
+                Some(TcpRole::Server)
+            )
+        );
+        assert_eq!(
+            track(&mut tracker, segment(CLIENT, SERVER, ACK)),
+            (Some(TcpConnectionState::Established), Some(TcpRole::Client))
+        );
+        assert_eq!(
+            track(&mut tracker, segment(SERVER, CLIENT, FIN | ACK)).0,
+            Some(TcpConnectionState::Closing)
+        );
+        assert_eq!(
+            track(&mut tracker, segment(CLIENT, SERVER, FIN | ACK)).0,
+            Some(TcpConnectionState::Closed)
+        );
+    }
+
+    #[test]
+    fn test_refused_connection() {
+        let mut tracker = TcpConnectionTracker::default();
+
+        track(&mut tracker, segment(CLIENT, SERVER, SYN));
+        assert_eq!(
+            track(&mut tracker, segment(SERVER, CLIENT, RST | ACK)),
+            (Some(TcpConnectionState::Refused), Some(TcpRole::Server))
+        );
+    }
+
+    #[test]
+    fn test_mid_stream_connection() {
+        let mut tracker = TcpConnectionTracker::default();
+
+        assert_eq!(
+            track(&mut tracker, segment(SERVER, CLIENT, ACK)),
+            (Some(TcpConnectionState::Established), None)
+        );
+        assert_eq!(
+            track(&mut tracker, segment(CLIENT, SERVER, FIN | ACK)),
+            (Some(TcpConnectionState::Closing), None)
+        );
+        // Le FIN retransmis ne ferme pas la connexion
+        assert_eq!(
+            track(&mut tracker, segment(CLIENT, SERVER, FIN | ACK)).0,
+            Some(TcpConnectionState::Closing)
+        );
+        assert_eq!(
+            track(&mut tracker, segment(SERVER, CLIENT, FIN | ACK)).0,
+            Some(TcpConnectionState::Closed)
+        );
+
+        let other_client = ("10.0.0.3", 50001);
+        track(&mut tracker, segment(other_client, SERVER, ACK));
+        assert_eq!(
+            track(&mut tracker, segment(other_client, SERVER, RST)).0,
+            Some(TcpConnectionState::Reset)
+        );
+    }
+
+    #[test]
+    fn test_non_tcp_packet_is_ignored() {
+        let mut tracker = TcpConnectionTracker::default();
+        let mut packet = segment(CLIENT, SERVER, SYN);
+        packet.layer_3_infos.layer_4_infos.tcp = None;
+
+        tracker.track(&mut packet);
+
+        assert!(tracker.connections.is_empty());
+    }
+
+    #[test]
+    fn test_evicts_idle_connections() {
+        let mut tracker = TcpConnectionTracker::default();
+        let start = Instant::now();
+        tracker.track_at(&mut segment(CLIENT, SERVER, SYN), start);
+        tracker.track_at(
+            &mut segment(SERVER, CLIENT, SYN | ACK),
+            start + Duration::from_secs(1),
+        );
+        assert_eq!(tracker.by_last_seen.len(), 1);
+
+        tracker.track_at(
+            &mut segment(("10.0.0.3", 50001), SERVER, SYN),
+            start + Duration::from_secs(1) + IDLE_TIMEOUT,
+        );
+
+        assert_eq!(tracker.connections.len(), 1);
+        assert_eq!(tracker.by_last_seen.len(), 1);
+    }
+
+    #[test]
+    fn test_evicts_oldest_connection() {
+        let mut tracker = TcpConnectionTracker::default();
+        let start = Instant::now();
+        tracker.track_at(&mut segment(CLIENT, SERVER, SYN), start);
+        tracker.track_at(
+            &mut segment(("10.0.0.3", 50001), SERVER, SYN),
+            start + Duration::from_secs(1),
+        );
+
+        tracker.evict_oldest();
+
+        let remaining: Vec<&ConnectionKey> = tracker.connections.keys().collect();
+        assert_eq!(
+            remaining,
+            vec![&(
+                ("10.0.0.2".to_string(), "443".to_string()),
+                ("10.0.0.3".to_string(), "50001".to_string())
+            )]
+        );
+    }
+}
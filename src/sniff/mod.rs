@@ -7,7 +7,14 @@
 pub(crate) mod capture_packet;
 use capture_packet::{all_interfaces, one_interface};
 
-use crate::tauri_state::SonarState;
+pub use capture_packet::capture_config::CaptureConfig;
+pub use capture_packet::diagnostics::{diagnose_interfaces, CaptureDiagnostic, CaptureStatus};
+pub use capture_packet::interface_stats::InterfaceStats;
+pub use capture_packet::stop_capture;
+
+use crate::tauri_state::{
+    CaptureConfigState, DhcpLeasesState, DnsNamesState, InterfaceStatsState, SonarState,
+};
 
 /// Démarre la capture de paquets jusqu'à interruption par l'utilisateur.
 ///
@@ -20,14 +27,30 @@ use crate::tauri_state::SonarState;
 /// * `app` - Handle vers l'application Tauri, utilisé pour interagir avec l'état de l'application et l'interface utilisateur.
 /// * `interface` - Le nom de l'interface réseau sur laquelle effectuer la capture, ou une chaîne spéciale pour indiquer toutes les interfaces.
 /// * `state` - L'état partagé de l'application, encapsulé dans un objet `SonarState` pour maintenir les données à travers l'application.
+/// * `configs` - Les configurations de capture (promiscuité, tampons, délai de lecture) choisies pour chaque interface.
+/// * `stats` - Les statistiques de capture par interface, alimentées pendant la capture.
+/// * `dns_names` - La table des noms DNS, apprise à partir des réponses DNS capturées.
+/// * `dhcp_leases` - L'inventaire des baux DHCP, alimenté par les messages DHCP capturés.
 pub fn scan_until_interrupt(
     app: tauri::AppHandle,
     interface: &str,
     state: tauri::State<SonarState>,
+    configs: tauri::State<CaptureConfigState>,
+    stats: tauri::State<InterfaceStatsState>,
+    dns_names: tauri::State<DnsNamesState>,
+    dhcp_leases: tauri::State<DhcpLeasesState>,
 ) {
     match check_interface(interface) {
-        true => all_interfaces(app, state),
-        false => one_interface(app, interface, state),
+        true => all_interfaces(app, state, configs, stats, dns_names, dhcp_leases),
+        false => one_interface(
+            app,
+            interface,
+            state,
+            configs,
+            stats,
+            dns_names,
+            dhcp_leases,
+        ),
     }
 }
 
@@ -43,6 +66,21 @@ fn check_interface(interface: &str) -> bool {
     matches!(interface, "Toutes les interfaces")
 }
 
+/// Vérifie, avant de démarrer la capture, que les interfaces sélectionnées peuvent être capturées.
+///
+/// Retourne un diagnostic par interface concernée : toutes les interfaces si l'utilisateur a choisi
+/// "Toutes les interfaces", sinon uniquement l'interface demandée.
+///
+/// # Arguments
+///
+/// * `interface` - Le nom de l'interface réseau sélectionnée par l'utilisateur.
+pub fn check_capture_permissions(interface: &str) -> Vec<CaptureDiagnostic> {
+    match check_interface(interface) {
+        true => diagnose_interfaces(None),
+        false => diagnose_interfaces(Some(interface)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
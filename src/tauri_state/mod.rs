@@ -3,8 +3,13 @@
 //! Ce module fournit les structures nécessaires pour maintenir l'état
 //! actuel de l'application Sonar, en particulier pour suivre les trames réseau.
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
+use crate::sniff::capture_packet::capture_config::CaptureConfig;
+use crate::sniff::capture_packet::interface_stats::InterfaceStats;
+use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::layer_4_infos::layer_7_infos::dhcp::DhcpLeaseInventory;
+use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::layer_4_infos::layer_7_infos::dns::DnsNameMap;
 use crate::sniff::capture_packet::layer_2_infos::PacketInfos;
 
 /// `SonarState` encapsule l'état global de l'application Sonar.
@@ -52,3 +57,76 @@ impl SonarState {
         }
     }
 }
+
+/// `CaptureConfigState` conserve la configuration de capture choisie pour chaque interface.
+///
+/// Les interfaces sans configuration explicite utilisent [`CaptureConfig::default`].
+#[derive(Default)]
+pub struct CaptureConfigState(pub Arc<Mutex<HashMap<String, CaptureConfig>>>);
+
+impl CaptureConfigState {
+    /// Retourne la configuration de capture d'une interface.
+    ///
+    /// # Arguments
+    ///
+    /// * `interface` - Le nom de l'interface réseau.
+    pub fn get(&self, interface: &str) -> CaptureConfig {
+        let configs = self.0.lock().expect("Failed to lock the mutex");
+        configs.get(interface).cloned().unwrap_or_default()
+    }
+
+    /// Définit la configuration de capture d'une interface.
+    ///
+    /// La configuration est prise en compte au prochain démarrage de la capture sur cette interface.
+    ///
+    /// # Arguments
+    ///
+    /// * `interface` - Le nom de l'interface réseau.
+    /// * `config` - La configuration à appliquer.
+    pub fn set(&self, interface: &str, config: CaptureConfig) {
+        let mut configs = self.0.lock().expect("Failed to lock the mutex");
+        configs.insert(interface.to_string(), config);
+    }
+}
+
+/// `InterfaceStatsState` conserve les statistiques de capture de chaque interface.
+///
+/// Les compteurs sont alimentés par les threads de capture et consultés par l'interface utilisateur
+/// et l'export Excel.
+#[derive(Default)]
+pub struct InterfaceStatsState(pub Arc<Mutex<HashMap<String, InterfaceStats>>>);
+
+impl InterfaceStatsState {
+    /// Retourne une copie des statistiques de toutes les interfaces capturées.
+    pub fn snapshot(&self) -> HashMap<String, InterfaceStats> {
+        self.0.lock().expect("Failed to lock the mutex").clone()
+    }
+}
+
+/// `DnsNamesState` conserve la table des noms DNS de chaque adresse IP.
+///
+/// La table est apprise par les threads de capture à partir des réponses DNS, et consultée par le graphe
+/// et l'export Excel.
+#[derive(Default)]
+pub struct DnsNamesState(pub Arc<Mutex<DnsNameMap>>);
+
+impl DnsNamesState {
+    /// Retourne une copie de la table des noms DNS.
+    pub fn snapshot(&self) -> DnsNameMap {
+        self.0.lock().expect("Failed to lock the mutex").clone()
+    }
+}
+
+/// `DhcpLeasesState` conserve l'inventaire des baux DHCP.
+///
+/// L'inventaire est alimenté par les threads de capture à chaque message DHCP, avant la fusion des flux qui
+/// ne conserve que le dernier message d'un échange, et consulté par le graphe et l'export Excel.
+#[derive(Default)]
+pub struct DhcpLeasesState(pub Arc<Mutex<DhcpLeaseInventory>>);
+
+impl DhcpLeasesState {
+    /// Retourne une copie de l'inventaire des baux DHCP.
+    pub fn snapshot(&self) -> DhcpLeaseInventory {
+        self.0.lock().expect("Failed to lock the mutex").clone()
+    }
+}
@@ -147,10 +147,13 @@ mod tests {
     use pnet::datalink::dummy::{dummy_interface, interfaces};
 
     use pnet::packet::ethernet::{EtherTypes, EthernetPacket, MutableEthernetPacket};
-    use pnet::packet::ip::IpNextHeaderProtocols;
+    use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
     use pnet::packet::ipv4::MutableIpv4Packet;
+    use pnet::packet::ipv6::MutableIpv6Packet;
+    use pnet::packet::tcp::MutableTcpPacket;
+    use pnet::packet::udp::MutableUdpPacket;
     use pnet::packet::Packet;
-    use std::net::{AddrParseError, Ipv4Addr};
+    use std::net::{AddrParseError, Ipv4Addr, Ipv6Addr};
 
     fn create_ipv4_packet(src_ip: Ipv4Addr, dst_ip: Ipv4Addr) -> Vec<u8> {
         let mut buf = [0u8; 34]; // Ethernet header (14 bytes) + IPv4 header (20 bytes)
@@ -171,6 +174,43 @@ mod tests {
         buf.to_vec()
     }
 
+    /// Construit une trame Ethernet transportant un paquet IPv4 avec ses options et sa charge utile de couche 4.
+    fn create_ipv4_packet_with_payload(
+        src_ip: Ipv4Addr,
+        dst_ip: Ipv4Addr,
+        options: &[u8],
+        protocol: IpNextHeaderProtocol,
+        payload: &[u8],
+    ) -> Vec<u8> {
+        let header_len = 20 + options.len();
+        let mut ipv4_buf = vec![0u8; header_len + payload.len()];
+        let mut ipv4_packet = MutableIpv4Packet::new(&mut ipv4_buf[..]).unwrap();
+        ipv4_packet.set_version(4);
+        ipv4_packet.set_header_length((header_len / 4) as u8);
+        ipv4_packet.set_total_length((header_len + payload.len()) as u16);
+        ipv4_packet.set_ttl(64);
+        ipv4_packet.set_next_level_protocol(protocol);
+        ipv4_packet.set_source(src_ip);
+        ipv4_packet.set_destination(dst_ip);
+        ipv4_buf[20..header_len].copy_from_slice(options);
+        ipv4_buf[header_len..].copy_from_slice(payload);
+
+        let mut buf = vec![0u8; 14 + ipv4_buf.len()];
+        let mut eth_packet = MutableEthernetPacket::new(&mut buf[..]).unwrap();
+        eth_packet.set_ethertype(EtherTypes::Ipv4);
+        eth_packet.set_payload(&ipv4_buf);
+        buf
+    }
+
+    fn create_tcp_segment(port_source: u16, port_destination: u16) -> Vec<u8> {
+        let mut tcp_buf = vec![0u8; 20];
+        let mut tcp_packet = MutableTcpPacket::new(&mut tcp_buf[..]).unwrap();
+        tcp_packet.set_source(port_source);
+        tcp_packet.set_destination(port_destination);
+        tcp_packet.set_data_offset(5);
+        tcp_buf
+    }
+
     #[test]
     fn test_dummy_interface_creation() {
         // Create a dummy interface
@@ -214,13 +254,15 @@ mod tests {
 
     #[test]
     fn test_get_layer_3_infos_ipv4() -> Result<(), AddrParseError> {
-        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::get_layer_3_infos;
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::{
+            get_layer_3_infos, Reassemblers,
+        };
         let src_ip = "192.168.1.1".parse()?;
         let dst_ip = "192.168.1.2".parse()?;
         let packet_data = create_ipv4_packet(src_ip, dst_ip);
         let ethernet_packet = EthernetPacket::new(&packet_data[..]).unwrap();
 
-        let layer3_infos = get_layer_3_infos(&ethernet_packet);
+        let layer3_infos = get_layer_3_infos(&ethernet_packet, &mut Reassemblers::default());
 
         assert_eq!(layer3_infos.ip_source, Some("192.168.1.1".to_string()));
         assert_eq!(layer3_infos.ip_destination, Some("192.168.1.2".to_string()));
@@ -228,4 +270,491 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_packet_infos_from_raw_ip_frame() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::{
+            layer_3_infos::Reassemblers, link_type::LinkType, PacketInfos,
+        };
+        let packet_data = create_ipv4_packet("10.8.0.2".parse()?, "10.8.0.1".parse()?);
+        // Une interface tun livre le paquet IPv4 sans l'en-tête Ethernet de 14 octets
+        let raw_ip_frame = &packet_data[14..];
+
+        let packet_infos = PacketInfos::from_frame(
+            "wg0",
+            LinkType::RawIp,
+            raw_ip_frame,
+            &mut Reassemblers::default(),
+        )
+        .unwrap();
+
+        assert_eq!(packet_infos.mac_address_source, "");
+        assert_eq!(packet_infos.mac_address_destination, "");
+        assert_eq!(packet_infos.l_3_protocol, "Ipv4");
+        assert_eq!(
+            packet_infos.layer_3_infos.ip_source,
+            Some("10.8.0.2".to_string())
+        );
+        assert_eq!(
+            packet_infos.layer_3_infos.ip_destination,
+            Some("10.8.0.1".to_string())
+        );
+        assert_eq!(packet_infos.packet_size, 20);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_packet_infos_from_linux_sll_frame() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::{
+            layer_3_infos::Reassemblers, link_type::LinkType, PacketInfos,
+        };
+        let packet_data = create_ipv4_packet("192.168.1.1".parse()?, "192.168.1.2".parse()?);
+        // En-tête Linux cooked v1 : 14 octets de métadonnées suivis du protocole (0x0800)
+        let mut sll_frame = vec![0u8; 14];
+        sll_frame.extend_from_slice(&[0x08, 0x00]);
+        sll_frame.extend_from_slice(&packet_data[14..]);
+
+        // LINKTYPE_LINUX_SLL
+        let link_type = LinkType::from_pcap_linktype(113).unwrap();
+        let packet_infos =
+            PacketInfos::from_frame("any", link_type, &sll_frame, &mut Reassemblers::default())
+                .unwrap();
+
+        assert_eq!(packet_infos.mac_address_source, "");
+        assert_eq!(
+            packet_infos.layer_3_infos.ip_source,
+            Some("192.168.1.1".to_string())
+        );
+        assert_eq!(
+            packet_infos.layer_3_infos.l_4_protocol,
+            Some("Tcp".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_layer_3_infos_pppoe_session() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::{
+            get_layer_3_infos, Reassemblers,
+        };
+        let ipv4_packet = &create_ipv4_packet("100.64.0.10".parse()?, "1.1.1.1".parse()?)[14..];
+
+        // En-tête Ethernet, en-tête PPPoE (session 0x0042) puis protocole PPP IPv4 (0x0021)
+        let mut buf = vec![0u8; 14];
+        let mut eth_packet = MutableEthernetPacket::new(&mut buf[..]).unwrap();
+        eth_packet.set_ethertype(EtherTypes::PppoeSession);
+        buf.extend_from_slice(&[0x11, 0x00, 0x00, 0x42]);
+        buf.extend_from_slice(&(ipv4_packet.len() as u16 + 2).to_be_bytes());
+        buf.extend_from_slice(&[0x00, 0x21]);
+        buf.extend_from_slice(ipv4_packet);
+        let ethernet_packet = EthernetPacket::new(&buf[..]).unwrap();
+
+        let layer3_infos = get_layer_3_infos(&ethernet_packet, &mut Reassemblers::default());
+
+        assert_eq!(layer3_infos.ip_source, Some("100.64.0.10".to_string()));
+        assert_eq!(layer3_infos.ip_destination, Some("1.1.1.1".to_string()));
+        let pppoe = layer3_infos.pppoe.unwrap();
+        assert_eq!(pppoe.session_id, 0x42);
+        assert_eq!(pppoe.ppp_protocol, Some("Ipv4".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_layer_3_infos_ipv6_extension_headers() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::{
+            get_layer_3_infos, Reassemblers,
+        };
+        let src_ip: Ipv6Addr = "2001:db8::1".parse()?;
+        let dst_ip: Ipv6Addr = "2001:db8::2".parse()?;
+
+        let mut udp_buf = [0u8; 8];
+        let mut udp_packet = MutableUdpPacket::new(&mut udp_buf[..]).unwrap();
+        udp_packet.set_source(5353);
+        udp_packet.set_destination(53);
+        udp_packet.set_length(8);
+
+        // En-tête Hop-by-Hop (8 octets) annonçant UDP, suivi du datagramme UDP
+        let mut ipv6_payload = vec![17, 0, 5, 2, 0, 0, 1, 0];
+        ipv6_payload.extend_from_slice(udp_packet.packet());
+
+        let mut ipv6_buf = vec![0u8; 40 + ipv6_payload.len()];
+        let mut ipv6_packet = MutableIpv6Packet::new(&mut ipv6_buf[..]).unwrap();
+        ipv6_packet.set_version(6);
+        ipv6_packet.set_payload_length(ipv6_payload.len() as u16);
+        ipv6_packet.set_next_header(IpNextHeaderProtocols::Hopopt);
+        ipv6_packet.set_source(src_ip);
+        ipv6_packet.set_destination(dst_ip);
+        ipv6_packet.set_payload(&ipv6_payload);
+
+        let mut buf = vec![0u8; 14 + ipv6_buf.len()];
+        let mut eth_packet = MutableEthernetPacket::new(&mut buf[..]).unwrap();
+        eth_packet.set_ethertype(EtherTypes::Ipv6);
+        eth_packet.set_payload(&ipv6_buf);
+        let ethernet_packet = EthernetPacket::new(&buf[..]).unwrap();
+
+        let layer3_infos = get_layer_3_infos(&ethernet_packet, &mut Reassemblers::default());
+
+        assert_eq!(layer3_infos.l_4_protocol, Some("Udp".to_string()));
+        assert_eq!(layer3_infos.ipv6_extension_headers, vec!["Hop-by-Hop"]);
+        assert_eq!(
+            layer3_infos.layer_4_infos.port_source,
+            Some("5353".to_string())
+        );
+        assert_eq!(
+            layer3_infos.layer_4_infos.port_destination,
+            Some("53".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_layer_4_infos_ipv4_tcp() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::{
+            get_layer_3_infos, Reassemblers,
+        };
+        let packet_data = create_ipv4_packet_with_payload(
+            "192.168.1.10".parse()?,
+            "192.168.1.1".parse()?,
+            &[],
+            IpNextHeaderProtocols::Tcp,
+            &create_tcp_segment(51000, 443),
+        );
+        let ethernet_packet = EthernetPacket::new(&packet_data[..]).unwrap();
+
+        let layer3_infos = get_layer_3_infos(&ethernet_packet, &mut Reassemblers::default());
+
+        assert_eq!(layer3_infos.l_4_protocol, Some("Tcp".to_string()));
+        assert_eq!(
+            layer3_infos.layer_4_infos.port_source,
+            Some("51000".to_string())
+        );
+        assert_eq!(
+            layer3_infos.layer_4_infos.port_destination,
+            Some("443".to_string())
+        );
+        let tcp = layer3_infos.layer_4_infos.tcp.unwrap();
+        assert!(tcp.flag_names.is_empty());
+        assert_eq!(tcp.connection_state, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_layer_4_infos_tcp_stream_reassembly() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::{
+            get_layer_3_infos, Reassemblers,
+        };
+        // Requête HTTP répartie sur deux segments consécutifs
+        let segment = |sequence: u32, payload: &[u8]| {
+            let mut tcp_buf = create_tcp_segment(51000, 80);
+            tcp_buf.extend_from_slice(payload);
+            let mut tcp_packet = MutableTcpPacket::new(&mut tcp_buf[..]).unwrap();
+            tcp_packet.set_sequence(sequence);
+            tcp_packet.set_flags(0x18);
+            create_ipv4_packet_with_payload(
+                "192.168.1.10".parse().unwrap(),
+                "192.168.1.1".parse().unwrap(),
+                &[],
+                IpNextHeaderProtocols::Tcp,
+                &tcp_buf,
+            )
+        };
+        let first_payload = b"GET /index.html HTTP/1.1\r\nHost: hmi.lan\r\nUser-";
+        let first = segment(1000, first_payload);
+        let last = segment(
+            1000 + first_payload.len() as u32,
+            b"Agent: curl/8.0\r\n\r\n",
+        );
+        let http_host = |packet_data: &[u8], reassemblers: &mut Reassemblers| {
+            get_layer_3_infos(&EthernetPacket::new(packet_data).unwrap(), reassemblers)
+                .layer_4_infos
+                .layer_7_infos
+                .and_then(|layer_7_infos| layer_7_infos.http)
+                .and_then(|http| http.host)
+        };
+
+        let mut reassemblers = Reassemblers::default();
+        assert_eq!(http_host(&first, &mut reassemblers), None);
+        assert_eq!(
+            http_host(&last, &mut reassemblers),
+            Some("hmi.lan".to_string())
+        );
+
+        // Une nouvelle capture ne reprend pas le flux laissé à moitié par la précédente
+        assert_eq!(http_host(&last, &mut Reassemblers::default()), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_layer_4_infos_ipv4_with_options() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::{
+            get_layer_3_infos, Reassemblers,
+        };
+        let mut udp_buf = [0u8; 8];
+        let mut udp_packet = MutableUdpPacket::new(&mut udp_buf[..]).unwrap();
+        udp_packet.set_source(68);
+        udp_packet.set_destination(67);
+        udp_packet.set_length(8);
+        // Option Router Alert (4 octets) : IHL = 6
+        let packet_data = create_ipv4_packet_with_payload(
+            "0.0.0.0".parse()?,
+            "255.255.255.255".parse()?,
+            &[0x94, 0x04, 0x00, 0x00],
+            IpNextHeaderProtocols::Udp,
+            &udp_buf,
+        );
+        let ethernet_packet = EthernetPacket::new(&packet_data[..]).unwrap();
+
+        let layer3_infos = get_layer_3_infos(&ethernet_packet, &mut Reassemblers::default());
+
+        assert_eq!(layer3_infos.l_4_protocol, Some("Udp".to_string()));
+        assert_eq!(
+            layer3_infos.layer_4_infos.port_source,
+            Some("68".to_string())
+        );
+        assert_eq!(
+            layer3_infos.layer_4_infos.port_destination,
+            Some("67".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_layer_4_infos_ipv4_ignores_trailing_padding() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::{
+            get_layer_3_infos, Reassemblers,
+        };
+        let mut packet_data = create_ipv4_packet_with_payload(
+            "10.0.0.1".parse()?,
+            "10.0.0.2".parse()?,
+            &[],
+            IpNextHeaderProtocols::Icmp,
+            &[8, 0, 0, 0, 0x12, 0x34, 0x00, 0x01],
+        );
+        // Bourrage Ethernet jusqu'à la taille minimale de trame
+        packet_data.resize(60, 0xff);
+        let ethernet_packet = EthernetPacket::new(&packet_data[..]).unwrap();
+
+        let layer3_infos = get_layer_3_infos(&ethernet_packet, &mut Reassemblers::default());
+
+        let icmp = layer3_infos.layer_4_infos.icmp.unwrap();
+        assert_eq!(icmp.icmp_type, 8);
+        assert_eq!(icmp.identifier, Some(0x1234));
+        assert_eq!(icmp.sequence, Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_layer_4_infos_ipv4_fragments() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::{
+            get_layer_3_infos, Reassemblers,
+        };
+        let mut udp_buf = [0u8; 24];
+        let mut udp_packet = MutableUdpPacket::new(&mut udp_buf[..]).unwrap();
+        udp_packet.set_source(161);
+        udp_packet.set_destination(40000);
+        udp_packet.set_length(24);
+
+        // Deux fragments : 16 octets avec MF, puis les 8 derniers octets au décalage 2 (x 8 octets)
+        let fragment = |offset: u16, more_fragments: bool, payload: &[u8]| {
+            let mut packet_data = create_ipv4_packet_with_payload(
+                "10.1.1.1".parse().unwrap(),
+                "10.1.1.2".parse().unwrap(),
+                &[],
+                IpNextHeaderProtocols::Udp,
+                payload,
+            );
+            let mut ipv4_packet = MutableIpv4Packet::new(&mut packet_data[14..]).unwrap();
+            ipv4_packet.set_identification(0xbeef);
+            ipv4_packet.set_fragment_offset(offset);
+            ipv4_packet.set_flags(if more_fragments { 1 } else { 0 });
+            packet_data
+        };
+        let first = fragment(0, true, &udp_buf[..16]);
+        let last = fragment(2, false, &udp_buf[16..]);
+
+        // Sans les fragments précédents, le dernier fragment ne peut pas être rattaché à son flux
+        let alone_infos = get_layer_3_infos(
+            &EthernetPacket::new(&last[..]).unwrap(),
+            &mut Reassemblers::default(),
+        );
+        assert_eq!(alone_infos.layer_4_infos.port_source, None);
+
+        let mut reassemblers = Reassemblers::default();
+        let first_infos =
+            get_layer_3_infos(&EthernetPacket::new(&first[..]).unwrap(), &mut reassemblers);
+        let last_infos =
+            get_layer_3_infos(&EthernetPacket::new(&last[..]).unwrap(), &mut reassemblers);
+
+        // Le dernier fragment est rattaché au flux grâce au réassemblage
+        for layer3_infos in [&first_infos, &last_infos] {
+            assert_eq!(
+                layer3_infos.layer_4_infos.port_source,
+                Some("161".to_string())
+            );
+            assert_eq!(
+                layer3_infos.layer_4_infos.port_destination,
+                Some("40000".to_string())
+            );
+            assert!(layer3_infos.anomalies.is_empty());
+        }
+        // Seul le datagramme complet est analysé jusqu'à la couche 7
+        assert!(first_infos.layer_4_infos.layer_7_infos.is_none());
+        assert!(last_infos.layer_4_infos.layer_7_infos.is_some());
+
+        // Un fragment dupliqué n'est pas signalé
+        get_layer_3_infos(&EthernetPacket::new(&first[..]).unwrap(), &mut reassemblers);
+        let duplicate_infos =
+            get_layer_3_infos(&EthernetPacket::new(&first[..]).unwrap(), &mut reassemblers);
+        assert!(duplicate_infos.anomalies.is_empty());
+
+        // Un fragment qui recouvre des données déjà reçues est signalé
+        let overlapping = fragment(1, true, &udp_buf[8..]);
+        let overlapping_infos = get_layer_3_infos(
+            &EthernetPacket::new(&overlapping[..]).unwrap(),
+            &mut reassemblers,
+        );
+        assert!(overlapping_infos.anomalies.contains("Overlapping fragment"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_packet_infos_keeps_vlan_tags() {
+        use crate::sniff::capture_packet::layer_2_infos::{
+            layer_3_infos::Reassemblers, link_type::LinkType, PacketInfos,
+        };
+
+        // Trame QinQ : S-tag VLAN 300 (PCP 3), C-tag VLAN 42, puis une requête ARP
+        let mut buf = vec![0u8; 14];
+        let mut eth_packet = MutableEthernetPacket::new(&mut buf[..]).unwrap();
+        eth_packet.set_ethertype(EtherTypes::PBridge);
+        buf.extend_from_slice(&[0x61, 0x2c, 0x81, 0x00]);
+        buf.extend_from_slice(&[0x00, 0x2a, 0x08, 0x06]);
+        buf.extend_from_slice(&[0, 1, 8, 0, 6, 4, 0, 1]);
+        buf.extend_from_slice(&[0x02, 0, 0, 0, 0, 0x01, 192, 168, 42, 1]);
+        buf.extend_from_slice(&[0, 0, 0, 0, 0, 0, 192, 168, 42, 2]);
+
+        let packet_infos = PacketInfos::from_frame(
+            "eth0",
+            LinkType::Ethernet,
+            &buf,
+            &mut Reassemblers::default(),
+        )
+        .unwrap();
+
+        assert_eq!(packet_infos.l_3_protocol, "Arp");
+        let outer = packet_infos.vlan_outer.unwrap();
+        assert_eq!(outer.vlan_id, 300);
+        assert_eq!(outer.priority, 3);
+        assert!(!outer.drop_eligible);
+        assert_eq!(packet_infos.vlan_inner.unwrap().vlan_id, 42);
+        assert_eq!(
+            packet_infos.layer_3_infos.ip_source,
+            Some("192.168.42.1".to_string())
+        );
+        assert!(packet_infos.layer_3_infos.arp.is_some());
+    }
+
+    #[test]
+    fn test_get_layer_3_infos_mpls() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::layer_3_infos::{
+            get_layer_3_infos, Reassemblers,
+        };
+        let ipv4_frame = create_ipv4_packet_with_payload(
+            "172.16.0.1".parse()?,
+            "172.16.0.2".parse()?,
+            &[],
+            IpNextHeaderProtocols::Tcp,
+            &create_tcp_segment(179, 50000),
+        );
+
+        // Deux étiquettes (100 puis 200 en fond de pile) au-dessus du paquet IPv4
+        let mut buf = vec![0u8; 14];
+        let mut eth_packet = MutableEthernetPacket::new(&mut buf[..]).unwrap();
+        eth_packet.set_ethertype(EtherTypes::Mpls);
+        buf.extend_from_slice(&[0x00, 0x06, 0x40, 0xfe]);
+        buf.extend_from_slice(&[0x00, 0x0c, 0x81, 0xfe]);
+        buf.extend_from_slice(&ipv4_frame[14..]);
+        let ethernet_packet = EthernetPacket::new(&buf[..]).unwrap();
+
+        let layer3_infos = get_layer_3_infos(&ethernet_packet, &mut Reassemblers::default());
+
+        let mpls = layer3_infos.mpls.unwrap();
+        let labels: Vec<u32> = mpls.labels.iter().map(|l| l.label).collect();
+        assert_eq!(labels, vec![100, 200]);
+        assert!(mpls.labels[1].bottom_of_stack);
+        assert_eq!(layer3_infos.ip_source, Some("172.16.0.1".to_string()));
+        assert_eq!(
+            layer3_infos.layer_4_infos.port_source,
+            Some("179".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_packet_infos_from_vxlan_frame() -> Result<(), AddrParseError> {
+        use crate::sniff::capture_packet::layer_2_infos::{
+            layer_3_infos::Reassemblers, link_type::LinkType, PacketInfos,
+        };
+        let mut inner_frame = create_ipv4_packet_with_payload(
+            "10.0.0.5".parse()?,
+            "10.0.0.6".parse()?,
+            &[],
+            IpNextHeaderProtocols::Tcp,
+            &create_tcp_segment(40000, 22),
+        );
+        let mut inner_ethernet = MutableEthernetPacket::new(&mut inner_frame[..]).unwrap();
+        inner_ethernet.set_source("02:00:00:00:00:05".parse().unwrap());
+        inner_ethernet.set_destination("02:00:00:00:00:06".parse().unwrap());
+
+        // En-tête UDP vers le port VXLAN 4789, puis en-tête VXLAN avec le VNI 5000
+        let mut vxlan = vec![0xc0, 0x00, 0x12, 0xb5, 0, 0, 0, 0];
+        vxlan.extend_from_slice(&[0x08, 0, 0, 0, 0x00, 0x13, 0x88, 0x00]);
+        vxlan.extend_from_slice(&inner_frame);
+        let outer_frame = create_ipv4_packet_with_payload(
+            "192.0.2.1".parse()?,
+            "192.0.2.2".parse()?,
+            &[],
+            IpNextHeaderProtocols::Udp,
+            &vxlan,
+        );
+
+        let packet_infos = PacketInfos::from_frame(
+            "eth0",
+            LinkType::Ethernet,
+            &outer_frame,
+            &mut Reassemblers::default(),
+        )
+        .unwrap();
+
+        // La trame interne détermine le flux
+        assert_eq!(packet_infos.mac_address_source, "02:00:00:00:00:05");
+        assert_eq!(packet_infos.mac_address_destination, "02:00:00:00:00:06");
+        assert_eq!(
+            packet_infos.layer_3_infos.ip_source,
+            Some("10.0.0.5".to_string())
+        );
+        assert_eq!(
+            packet_infos.layer_3_infos.layer_4_infos.port_destination,
+            Some("22".to_string())
+        );
+        assert_eq!(packet_infos.packet_size, outer_frame.len());
+
+        let tunnel = packet_infos.tunnel.unwrap();
+        assert_eq!(tunnel.kind, "VXLAN");
+        assert_eq!(tunnel.tunnel_id, Some(5000));
+        assert_eq!(tunnel.outer_ip_source, "192.0.2.1");
+        assert_eq!(tunnel.outer_ip_destination, "192.0.2.2");
+
+        Ok(())
+    }
 }